@@ -1,7 +0,0 @@
-// Декларация модулей проекта
-pub mod controllers;
-pub mod errors;
-pub mod middleware;
-pub mod models;
-pub mod repositories;
-pub mod services;
\ No newline at end of file
@@ -0,0 +1,21 @@
+-- Миграция для отслеживания изменений отдельных полей пользователя
+-- Версия: 2.1
+-- Дата: 2025-07-15
+
+-- Время последнего изменения имени и возраста (NULL, если поле не менялось)
+ALTER TABLE users ADD COLUMN name_updated_at TIMESTAMPTZ NULL;
+ALTER TABLE users ADD COLUMN age_updated_at TIMESTAMPTZ NULL;
+
+-- Обновляем триггерную функцию: updated_at меняется только при фактическом изменении строки
+CREATE OR REPLACE FUNCTION update_updated_at_column()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF ROW(NEW.*) IS DISTINCT FROM ROW(OLD.*) THEN
+        NEW.updated_at = CURRENT_TIMESTAMP;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON COLUMN users.name_updated_at IS 'Дата и время последнего изменения имени';
+COMMENT ON COLUMN users.age_updated_at IS 'Дата и время последнего изменения возраста';
@@ -0,0 +1,12 @@
+-- Миграция для временной блокировки аккаунта после неудачных попыток входа
+-- Версия: 2.2
+-- Дата: 2025-07-20
+
+-- Счетчик неудачных попыток входа подряд
+ALTER TABLE users ADD COLUMN failed_login_attempts INTEGER NOT NULL DEFAULT 0;
+
+-- Время, до которого вход в аккаунт заблокирован (NULL — блокировки нет)
+ALTER TABLE users ADD COLUMN locked_until TIMESTAMPTZ NULL;
+
+COMMENT ON COLUMN users.failed_login_attempts IS 'Количество неудачных попыток входа подряд';
+COMMENT ON COLUMN users.locked_until IS 'Дата и время окончания временной блокировки входа';
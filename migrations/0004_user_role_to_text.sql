@@ -0,0 +1,15 @@
+-- Миграция для хранения основной роли пользователя как TEXT
+-- Версия: 2.2.1
+-- Дата: 2025-07-22
+
+-- Код передает роль текстовым параметром (без учета регистра при чтении), а Postgres не приводит
+-- text к enum user_role неявно: INSERT/UPDATE users.role падали бы на базе с enum.
+-- Храним роль как TEXT с CHECK на канонические значения
+ALTER TABLE users ALTER COLUMN role DROP DEFAULT;
+ALTER TABLE users ALTER COLUMN role TYPE TEXT USING role::text;
+ALTER TABLE users ALTER COLUMN role SET DEFAULT 'User';
+ALTER TABLE users ADD CONSTRAINT users_role_check CHECK (role IN ('User', 'Admin', 'Moderator'));
+
+DROP TYPE IF EXISTS user_role;
+
+COMMENT ON COLUMN users.role IS 'Роль пользователя в системе (User, Admin, Moderator)';
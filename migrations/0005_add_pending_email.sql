@@ -0,0 +1,8 @@
+-- Миграция для смены email с подтверждением
+-- Версия: 2.3
+-- Дата: 2025-07-24
+
+-- Новый email, ожидающий подтверждения (NULL — смена email не запрошена)
+ALTER TABLE users ADD COLUMN pending_email TEXT NULL;
+
+COMMENT ON COLUMN users.pending_email IS 'Новый email пользователя, ожидающий подтверждения';
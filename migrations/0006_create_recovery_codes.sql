@@ -0,0 +1,18 @@
+-- Миграция для кодов восстановления двухфакторной аутентификации
+-- Версия: 2.4
+-- Дата: 2025-07-28
+
+-- Одноразовые коды восстановления (хранится только SHA-256 хеш кода)
+CREATE TABLE IF NOT EXISTS user_recovery_codes (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    used_at TIMESTAMPTZ NULL
+);
+
+-- Поиск неиспользованного кода пользователя при входе
+CREATE INDEX IF NOT EXISTS idx_user_recovery_codes_user_id ON user_recovery_codes (user_id, code_hash);
+
+COMMENT ON TABLE user_recovery_codes IS 'Одноразовые коды восстановления доступа при утере устройства 2FA';
+COMMENT ON COLUMN user_recovery_codes.used_at IS 'Время использования кода (NULL — код еще действителен)';
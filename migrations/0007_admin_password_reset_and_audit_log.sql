@@ -0,0 +1,25 @@
+-- Миграция для сброса пароля администратором и журнала аудита
+-- Версия: 2.5
+-- Дата: 2025-07-30
+
+-- Требование сменить пароль при следующем входе (после сброса администратором)
+ALTER TABLE users ADD COLUMN must_change_password BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN users.must_change_password IS 'Пользователь должен сменить пароль при следующем входе';
+
+-- Журнал аудита действий с аккаунтами
+CREATE TABLE IF NOT EXISTS audit_log (
+    id UUID PRIMARY KEY,
+    actor_id UUID NULL,
+    user_id UUID NULL,
+    action TEXT NOT NULL,
+    details JSONB NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Выборка событий по пользователю в хронологическом порядке
+CREATE INDEX IF NOT EXISTS idx_audit_log_user_id ON audit_log (user_id, created_at);
+
+COMMENT ON TABLE audit_log IS 'Журнал аудита действий с аккаунтами';
+COMMENT ON COLUMN audit_log.actor_id IS 'Кто выполнил действие (NULL — система)';
+COMMENT ON COLUMN audit_log.user_id IS 'Над каким пользователем выполнено действие';
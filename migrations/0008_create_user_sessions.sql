@@ -0,0 +1,19 @@
+-- Миграция для серверных сессий пользователей
+-- Версия: 2.6
+-- Дата: 2025-08-01
+
+-- Сессия создается при каждом входе; ее ID записывается в токен (claim sid),
+-- поэтому отозванная сессия делает токен недействительным до истечения его срока
+CREATE TABLE IF NOT EXISTS user_sessions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NULL
+);
+
+-- Выборка активных сессий пользователя
+CREATE INDEX IF NOT EXISTS idx_user_sessions_user_id ON user_sessions (user_id, expires_at);
+
+COMMENT ON TABLE user_sessions IS 'Сессии пользователей, выданные при входе';
+COMMENT ON COLUMN user_sessions.revoked_at IS 'Время отзыва сессии (NULL — сессия не отозвана)';
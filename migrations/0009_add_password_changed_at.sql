@@ -0,0 +1,8 @@
+-- Миграция для учета возраста пароля
+-- Версия: 2.7
+-- Дата: 2025-08-04
+
+-- Время последней смены пароля (NULL — пароль не менялся с момента регистрации)
+ALTER TABLE users ADD COLUMN password_changed_at TIMESTAMPTZ NULL;
+
+COMMENT ON COLUMN users.password_changed_at IS 'Время последней смены пароля (NULL — пароль задан при регистрации)';
@@ -0,0 +1,36 @@
+-- Миграция для поиска по email без учета регистра
+-- Версия: 2.8
+-- Дата: 2025-08-06
+
+-- Проверка перед созданием индекса: если в базе уже есть адреса, отличающиеся только регистром,
+-- миграция останавливается с перечнем конфликтов. Какую учетную запись оставить, решает
+-- администратор (объединить или переименовать email), после чего миграция запускается повторно
+DO $$
+DECLARE
+    duplicates TEXT;
+BEGIN
+    SELECT string_agg(lower_email || ' (' || accounts || ')', ', ')
+    INTO duplicates
+    FROM (
+        SELECT LOWER(email) AS lower_email, COUNT(*) AS accounts
+        FROM users
+        GROUP BY LOWER(email)
+        HAVING COUNT(*) > 1
+        ORDER BY LOWER(email)
+        LIMIT 20
+    ) conflicts;
+
+    IF duplicates IS NOT NULL THEN
+        RAISE EXCEPTION 'Email, отличающиеся только регистром, принадлежат разным пользователям: %', duplicates
+            USING HINT = 'Объедините или переименуйте эти учетные записи и повторите миграцию';
+    END IF;
+END
+$$;
+
+-- Уникальный функциональный индекс по LOWER(email): запросы вида LOWER(email) = LOWER($1)
+-- используют его вместо последовательного сканирования, а адреса, отличающиеся только
+-- регистром, не могут принадлежать разным пользователям
+CREATE UNIQUE INDEX users_email_lower_key ON users (LOWER(email));
+
+-- Обычный индекс по email больше не используется запросами (уникальность email сохраняется)
+DROP INDEX IF EXISTS idx_users_email;
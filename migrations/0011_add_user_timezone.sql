@@ -0,0 +1,8 @@
+-- Миграция для хранения часового пояса пользователя
+-- Версия: 2.9
+-- Дата: 2025-08-07
+
+-- Часовой пояс в формате IANA (например, Europe/Moscow); NULL — не задан
+ALTER TABLE users ADD COLUMN timezone TEXT NULL;
+
+COMMENT ON COLUMN users.timezone IS 'Часовой пояс пользователя в формате IANA';
@@ -0,0 +1,11 @@
+-- Миграция для учета успешных входов
+-- Версия: 3.0
+-- Дата: 2025-08-08
+
+-- Счетчик успешных входов и время последнего входа (обновляются одним UPDATE при входе)
+ALTER TABLE users
+    ADD COLUMN login_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN last_login_at TIMESTAMPTZ NULL;
+
+COMMENT ON COLUMN users.login_count IS 'Количество успешных входов';
+COMMENT ON COLUMN users.last_login_at IS 'Время последнего успешного входа';
@@ -0,0 +1,24 @@
+-- Миграция для refresh-токенов с ротацией
+-- Версия: 3.1
+-- Дата: 2025-08-09
+
+-- Refresh-токен выдается при входе и заменяется новым при каждом обновлении.
+-- Все токены одной цепочки ротации имеют общий family_id; повторное предъявление
+-- уже использованного токена отзывает всю цепочку вместе с сессией
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL,
+    session_id UUID NOT NULL REFERENCES user_sessions(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ NULL,
+    revoked_at TIMESTAMPTZ NULL
+);
+
+-- Отзыв цепочки ротации
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family_id ON refresh_tokens (family_id);
+
+COMMENT ON TABLE refresh_tokens IS 'Refresh-токены (хранятся только SHA-256 хеши)';
+COMMENT ON COLUMN refresh_tokens.used_at IS 'Время ротации токена (повторное использование — признак кражи)';
@@ -0,0 +1,30 @@
+-- Миграция для регистрации ключей доступа (WebAuthn/passkey)
+-- Версия: 3.2
+-- Дата: 2025-08-12
+
+-- Challenge незавершенной регистрации ключа. Одноразовый: удаляется при завершении регистрации
+CREATE TABLE IF NOT EXISTS webauthn_challenges (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    challenge TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_webauthn_challenges_user_id ON webauthn_challenges (user_id);
+
+-- Зарегистрированные ключи доступа пользователя
+CREATE TABLE IF NOT EXISTS webauthn_credentials (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    credential_id TEXT NOT NULL UNIQUE,
+    public_key BYTEA NOT NULL,
+    sign_count BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_webauthn_credentials_user_id ON webauthn_credentials (user_id);
+
+COMMENT ON COLUMN webauthn_challenges.challenge IS 'Challenge в base64url (без дополнения)';
+COMMENT ON COLUMN webauthn_credentials.credential_id IS 'ID ключа в base64url (без дополнения)';
+COMMENT ON COLUMN webauthn_credentials.public_key IS 'Открытый ключ в формате COSE_Key';
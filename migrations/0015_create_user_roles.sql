@@ -0,0 +1,15 @@
+-- Миграция для дополнительных ролей пользователей
+-- Версия: 3.3
+-- Дата: 2025-08-14
+
+-- Роли сверх основной (users.role остается для обратной совместимости и старых токенов).
+-- Учитываются при проверке прав, если включен FEATURE_MULTIPLE_ROLES
+CREATE TABLE IF NOT EXISTS user_roles (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('User', 'Admin', 'Moderator')),
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, role)
+);
+
+COMMENT ON TABLE user_roles IS 'Дополнительные роли пользователей';
+COMMENT ON COLUMN user_roles.role IS 'Роль в каноническом написании (как в enum user_role)';
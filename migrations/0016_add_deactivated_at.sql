@@ -0,0 +1,9 @@
+-- Миграция для самостоятельной деактивации аккаунта
+-- Версия: 3.4
+-- Дата: 2025-08-15
+
+-- Время деактивации аккаунта самим пользователем. Пока не истекло окно REACTIVATION_WINDOW_DAYS,
+-- аккаунт можно восстановить входом; деактивация администратором это поле сбрасывает
+ALTER TABLE users ADD COLUMN deactivated_at TIMESTAMPTZ NULL;
+
+COMMENT ON COLUMN users.deactivated_at IS 'Время деактивации аккаунта пользователем (NULL — не деактивирован самостоятельно)';
@@ -0,0 +1,21 @@
+-- Миграция для самостоятельного сброса пароля
+-- Версия: 3.5
+-- Дата: 2025-08-18
+
+-- Токены сброса пароля (хранится только SHA-256 хеш токена). Токен действует до expires_at,
+-- пока не использован и не отозван более новым запросом сверх PASSWORD_RESET_MAX_ACTIVE
+CREATE TABLE IF NOT EXISTS password_reset_tokens (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ NULL,
+    invalidated_at TIMESTAMPTZ NULL
+);
+
+-- Подсчет запросов пользователя за окно и поиск действующих токенов
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user_id ON password_reset_tokens (user_id, created_at);
+
+COMMENT ON TABLE password_reset_tokens IS 'Токены самостоятельного сброса пароля (хранятся только SHA-256 хеши)';
+COMMENT ON COLUMN password_reset_tokens.invalidated_at IS 'Время отзыва токена более новым запросом (NULL — не отозван)';
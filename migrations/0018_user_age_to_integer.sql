@@ -0,0 +1,7 @@
+-- Миграция для согласования типа возраста с моделью
+-- Версия: 3.5.1
+-- Дата: 2025-08-20
+
+-- Модель хранит возраст как i32, а SMALLINT декодируется только в i16.
+-- Ограничение CHECK (13..120) переносится вместе с колонкой
+ALTER TABLE users ALTER COLUMN age TYPE INTEGER;
@@ -0,0 +1,18 @@
+-- Миграция для хранения ключей доступа в формате webauthn-rs
+-- Версия: 3.5.2
+-- Дата: 2025-08-21
+
+-- Незавершенные регистрации живут 5 минут, поэтому они просто отменяются: клиент начнет заново.
+-- Состояние регистрации (challenge и параметры проверки ответа) хранит webauthn-rs
+DELETE FROM webauthn_challenges;
+ALTER TABLE webauthn_challenges ADD COLUMN registration_state JSONB NOT NULL;
+
+-- Ключи, сохраненные прежней проверкой, не переносятся в Passkey (в них нет данных аттестации).
+-- Входа по ключу доступа еще нет, поэтому пользователи ничего не теряют и регистрируют ключ заново
+DELETE FROM webauthn_credentials;
+ALTER TABLE webauthn_credentials DROP COLUMN public_key;
+ALTER TABLE webauthn_credentials DROP COLUMN sign_count;
+ALTER TABLE webauthn_credentials ADD COLUMN passkey JSONB NOT NULL;
+
+COMMENT ON COLUMN webauthn_challenges.registration_state IS 'Состояние регистрации webauthn-rs (PasskeyRegistration)';
+COMMENT ON COLUMN webauthn_credentials.passkey IS 'Ключ доступа webauthn-rs (Passkey): открытый ключ, алгоритм и счетчик подписей';
@@ -0,0 +1,75 @@
+use reqwest::{Client, Method};
+use sha1::{Digest, Sha1};
+use std::env;
+use std::time::Duration;
+
+use crate::clients::outbound_request;
+use crate::errors::AppError;
+
+// Адрес API Pwned Passwords по умолчанию
+const DEFAULT_HIBP_API_URL: &str = "https://api.pwnedpasswords.com";
+
+// Таймаут запроса к HIBP: медленный внешний сервис не должен задерживать регистрацию
+const HIBP_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Клиент проверки паролей по базе утечек (k-анонимность: в сервис уходят только
+// первые 5 символов SHA-1 хеша пароля)
+#[derive(Debug, Clone)]
+pub struct HibpClient {
+    http: Client,
+    base_url: String,
+}
+
+impl HibpClient {
+    pub fn new(base_url: &str) -> Self {
+        let http = Client::builder()
+            .timeout(HIBP_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // Клиент из окружения: проверка включается PASSWORD_BREACH_CHECK=true, адрес — HIBP_API_URL
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("PASSWORD_BREACH_CHECK")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let base_url = env::var("HIBP_API_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_HIBP_API_URL.to_string());
+        Some(Self::new(&base_url))
+    }
+
+    // Встречается ли пароль в известных утечках
+    pub async fn is_password_pwned(&self, password: &str, request_id: Option<&str>) -> Result<bool, AppError> {
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(5);
+
+        let url = format!("{}/range/{}", self.base_url, prefix);
+        let body = outbound_request(&self.http, Method::GET, &url, request_id)
+            .header("Add-Padding", "true")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Ошибка запроса к HIBP: {}", e)))?
+            .text()
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Ошибка чтения ответа HIBP: {}", e)))?;
+
+        // Ответ — строки "СУФФИКС:КОЛИЧЕСТВО"; строки дополнения имеют количество 0
+        Ok(body.lines().any(|line| match line.trim().split_once(':') {
+            Some((candidate, count)) => {
+                candidate.eq_ignore_ascii_case(suffix) && count.trim().parse::<u64>().is_ok_and(|count| count > 0)
+            }
+            None => false,
+        }))
+    }
+}
@@ -0,0 +1,18 @@
+// Модуль clients — HTTP-клиенты внешних сервисов.
+// Соглашение: исходящие запросы строятся через outbound_request, чтобы X-Request-ID
+// входящего запроса передавался дальше и цепочку вызовов можно было связать по логам
+use reqwest::{Client, Method, RequestBuilder};
+
+use crate::utils::REQUEST_ID_HEADER;
+
+// Объявляем подмодуль hibp — проверка паролей по базе утечек Have I Been Pwned
+pub mod hibp;
+
+// Создает исходящий запрос с X-Request-ID текущего входящего запроса (если он известен)
+pub fn outbound_request(client: &Client, method: Method, url: &str, request_id: Option<&str>) -> RequestBuilder {
+    let builder = client.request(method, url);
+    match request_id {
+        Some(request_id) => builder.header(REQUEST_ID_HEADER, request_id),
+        None => builder,
+    }
+}
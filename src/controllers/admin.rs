@@ -0,0 +1,718 @@
+use futures_util::StreamExt;
+use hyper::body::{Body, Bytes};
+use hyper::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE, LINK};
+use hyper::{Request, Response, StatusCode};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::config::{AppConfig, ConfigStore};
+use crate::controllers::user::{json_response, parse_body};
+use crate::errors::AppError;
+use crate::models::{
+    AdminResetPasswordRequest, AdminUserResponse, AuditEventListResponse, ConfigReloadResponse, BulkStatusRequest, BulkStatusResponse, RevokedSessionsResponse, SessionListResponse,
+    UserListResponse, UserPageResponse, UserResponse, UserRole, UserRolesRequest,
+};
+use crate::middleware::rate_limit::RateLimiters;
+use crate::services::audit::{list_user_audit_service, AuditWriter};
+use crate::services::session::{
+    active_session_stats_service, list_user_sessions_service, revoke_user_sessions_service,
+};
+use crate::services::user::{
+    admin_reset_password_service, bulk_update_status_service, export_users_service, list_users_service,
+    set_user_roles_service, unlock_user_service, user_roles_service,
+};
+use crate::utils::{
+    pagination_link_header, pagination_link_header_with_query, pagination_link_header_without_total,
+    current_request_id, path_param_uuid, query_param_i64, to_response_json_with, MAX_PAGE_SIZE,
+};
+
+// Шаблон пути сброса пароля пользователя
+pub const ADMIN_RESET_PASSWORD_PATH: &str = "/api/v1/admin/users/:id/reset-password";
+
+// Шаблон пути сессий пользователя
+pub const ADMIN_USER_SESSIONS_PATH: &str = "/api/v1/admin/users/:id/sessions";
+
+// Шаблон пути разблокировки аккаунта
+pub const ADMIN_UNLOCK_USER_PATH: &str = "/api/v1/admin/users/:id/unlock";
+
+// Шаблон пути журнала аудита пользователя
+pub const ADMIN_USER_AUDIT_PATH: &str = "/api/v1/admin/users/:id/audit";
+
+// Шаблон пути ролей пользователя
+pub const ADMIN_USER_ROLES_PATH: &str = "/api/v1/admin/users/:id/roles";
+
+// Параметры списка пользователей из строки запроса
+struct ListUsersQuery {
+    offset: i64,
+    limit: i64,
+    search: Option<String>,
+    include_total: bool,
+}
+
+// Читает offset, limit, q и include_total из строки запроса. Нечисловые и переполняющие значения
+// offset и limit — 400, значения вне диапазона заменяются значениями по умолчанию
+// (limit — default_limit, DEFAULT_PAGE_SIZE из конфигурации запроса)
+fn parse_list_query(query: Option<&str>, default_limit: i64) -> Result<ListUsersQuery, AppError> {
+    let mut params = ListUsersQuery {
+        offset: 0,
+        limit: default_limit,
+        search: None,
+        include_total: false,
+    };
+
+    // Декодируем параметры (q может содержать пробелы и кириллицу в percent-encoding)
+    let pairs: Vec<(String, String)> = serde_urlencoded::from_str(query.unwrap_or("")).unwrap_or_default();
+    for (key, value) in pairs {
+        match key.as_str() {
+            "offset" => {
+                let offset = query_param_i64("offset", &value)?;
+                if offset >= 0 {
+                    params.offset = offset;
+                }
+            }
+            "limit" => {
+                let limit = query_param_i64("limit", &value)?;
+                if limit > 0 {
+                    params.limit = limit.min(MAX_PAGE_SIZE);
+                }
+            }
+            "q" => params.search = Some(value),
+            "include_total" => params.include_total = matches!(value.as_str(), "true" | "1"),
+            _ => {}
+        }
+    }
+
+    Ok(params)
+}
+
+// Читает page и per_page из строки запроса: по умолчанию первая страница размера default_per_page
+// (DEFAULT_PAGE_SIZE из конфигурации запроса). Нечисловые значения — 400, per_page больше
+// MAX_PAGE_SIZE ограничивается, остальные значения вне диапазона заменяются значениями по умолчанию
+fn parse_page_query(query: Option<&str>, default_per_page: i64) -> Result<(i64, i64), AppError> {
+    let mut page = 1;
+    let mut per_page = default_per_page;
+
+    let pairs: Vec<(String, String)> = serde_urlencoded::from_str(query.unwrap_or("")).unwrap_or_default();
+    for (key, value) in pairs {
+        match key.as_str() {
+            "page" => {
+                let value = query_param_i64("page", &value)?;
+                if value > 0 {
+                    page = value;
+                }
+            }
+            "per_page" => {
+                let value = query_param_i64("per_page", &value)?;
+                if value > 0 {
+                    per_page = value.min(MAX_PAGE_SIZE);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok((page, per_page))
+}
+
+// Обработчик для GET /api/v1/users — страница пользователей по номеру (?page=, ?per_page=)
+// вместе с общим количеством
+pub async fn list_users_page(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    let result = match parse_page_query(req.uri().query(), config.default_page_size) {
+        Ok((page, per_page)) => match (page - 1).checked_mul(per_page) {
+            Some(offset) => list_users_service(offset, per_page, None, true, &pool)
+                .await
+                .map(|(users, total)| UserPageResponse {
+                    data: users.iter().map(UserResponse::from).collect(),
+                    total: total.unwrap_or_default(),
+                    page,
+                    per_page,
+                }),
+            None => Err(AppError::BadRequest(
+                "Некорректный параметр запроса 'page': значение вне допустимого диапазона".to_string(),
+            )),
+        },
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(body) => match json_response(&body, StatusCode::OK, request_id.as_deref()) {
+            Ok(response) => Ok(response),
+            Err(e) => Ok(e.into_response(request_id.as_deref())),
+        },
+        Err(e) => {
+            log::warn!(
+                "Ошибка при получении страницы пользователей [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для GET /api/v1/admin/users — список пользователей с пагинацией
+// и необязательным поиском по подстроке имени или email (?q=).
+// Общее количество считается только при ?include_total=true, иначе total = null
+pub async fn list_users(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+    let ListUsersQuery { offset, limit, search, include_total } = match parse_list_query(req.uri().query(), config.default_page_size) {
+        Ok(params) => params,
+        Err(e) => {
+            log::warn!(
+                "Некорректные параметры списка пользователей [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            return Ok(e.into_response(request_id.as_deref()));
+        }
+    };
+
+    let (users, total) = match list_users_service(offset, limit, search.as_deref(), include_total, &pool).await {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!(
+                "Ошибка при получении списка пользователей [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            return Ok(e.into_response(request_id.as_deref()));
+        }
+    };
+
+    // Без общего количества следующая страница предполагается, если текущая заполнена целиком
+    let has_next = users.len() as i64 == limit;
+    let body = UserListResponse {
+        items: users.iter().map(AdminUserResponse::from).collect(),
+        total,
+        offset,
+        limit,
+    };
+
+    let mut response = match json_response(&body, StatusCode::OK, request_id.as_deref()) {
+        Ok(response) => response,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    // Ссылки на соседние страницы для клиентов, не разбирающих тело ответа
+    // Строка поиска и include_total сохраняются в ссылках, чтобы навигация шла по той же выборке
+    let mut query_params = Vec::new();
+    if let Some(q) = search.as_deref() {
+        query_params.push(("q", q.trim()));
+    }
+    if include_total {
+        query_params.push(("include_total", "true"));
+    }
+    let query = serde_urlencoded::to_string(&query_params).unwrap_or_default();
+    let links = match total {
+        Some(total) => pagination_link_header_with_query(req.uri().path(), &query, total, offset, limit),
+        None => pagination_link_header_without_total(req.uri().path(), &query, offset, limit, has_next),
+    };
+    if let Ok(value) = HeaderValue::from_str(&links) {
+        response.headers_mut().insert(LINK, value);
+    }
+
+    Ok(response)
+}
+
+// Обработчик для GET /api/v1/admin/users/export — выгрузка всех пользователей в NDJSON.
+// Строки читаются курсором и сразу отправляются клиенту, поэтому память не зависит от размера таблицы
+pub async fn export_users(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+    // Строки сериализуются в отдельной задаче, уже без конфигурации запроса, поэтому стиль ключей берется заранее
+    let json_case = match AppConfig::from_request(&req) {
+        Ok(config) => config.json_case,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    let (mut sender, body) = Body::channel();
+    let task_request_id = request_id.clone();
+    tokio::spawn(async move {
+        let request_id = task_request_id.as_deref().unwrap_or("unknown");
+        let mut users = export_users_service(&pool);
+        let mut exported = 0u64;
+
+        while let Some(user) = users.next().await {
+            let line = match user.and_then(|user| {
+                to_response_json_with(&user, json_case).map_err(|e| AppError::Internal(anyhow::anyhow!(e)))
+            }) {
+                Ok(line) => line,
+                Err(e) => {
+                    // Заголовки уже отправлены: обрываем тело, чтобы клиент не принял неполную выгрузку за полную
+                    log::error!("Ошибка при экспорте пользователей [request_id={}]: {:?}", request_id, e);
+                    sender.abort();
+                    return;
+                }
+            };
+
+            if sender.send_data(Bytes::from(line + "\n")).await.is_err() {
+                log::debug!("Клиент закрыл соединение во время экспорта [request_id={}]", request_id);
+                return;
+            }
+            exported += 1;
+        }
+
+        log::info!("Экспортировано пользователей: {} [request_id={}]", exported, request_id);
+    });
+
+    let mut response = Response::new(body);
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"));
+    response.headers_mut().insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    if let Some(value) = request_id.as_deref().and_then(|id| HeaderValue::from_str(id).ok()) {
+        response.headers_mut().insert("X-Request-ID", value);
+    }
+
+    Ok(response)
+}
+
+// Обработчик для POST /api/v1/admin/users/status — массовое изменение статуса пользователей
+pub async fn bulk_update_status(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Извлекаем данные модератора из extensions (добавлены middleware)
+    let (actor_id, actor_role) = match (req.extensions().get::<Uuid>(), req.extensions().get::<UserRole>()) {
+        (Some(id), Some(role)) => (*id, *role),
+        _ => {
+            log::error!("user_id или роль отсутствуют в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(None));
+        }
+    };
+
+    // Используем вспомогательную функцию для парсинга тела запроса
+    let (bulk_request, request_id) = match parse_body::<BulkStatusRequest>(req).await {
+        Ok(result) => result,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    // Валидируем данные
+    if let Err(validation_errors) = bulk_request.validate() {
+        return Ok(AppError::from(validation_errors).into_response(request_id.as_deref()));
+    }
+
+    // Принудительная деактивация администраторов доступна только администраторам
+    if bulk_request.force && actor_role != UserRole::Admin {
+        log::warn!(
+            "Попытка массовой деактивации с force без прав администратора [request_id={}] [user_id={}]",
+            request_id.as_deref().unwrap_or("unknown"),
+            actor_id
+        );
+        let error = AppError::Forbidden("Параметр force доступен только администраторам".to_string());
+        return Ok(error.into_response(request_id.as_deref()));
+    }
+
+    log::info!(
+        "Запрос на массовое изменение статуса [request_id={}] [user_id={}] [count={}]",
+        request_id.as_deref().unwrap_or("unknown"),
+        actor_id,
+        bulk_request.ids.len()
+    );
+
+    // Вызываем сервис для изменения статусов
+    let results = match bulk_update_status_service(
+        &bulk_request.ids,
+        bulk_request.is_active,
+        bulk_request.force,
+        &pool,
+    )
+    .await
+    {
+        Ok(results) => results,
+        Err(e) => {
+            log::error!(
+                "Ошибка при массовом изменении статуса [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            return Ok(e.into_response(request_id.as_deref()));
+        }
+    };
+
+    // Формируем и возвращаем ответ с результатом по каждому ID
+    let response = json_response(&BulkStatusResponse { results }, StatusCode::OK, request_id.as_deref())
+        .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+
+    Ok(response)
+}
+
+// Обработчик для POST /api/v1/admin/users/:id/reset-password — сброс пароля без текущего пароля
+pub async fn reset_user_password(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Фоновая запись аудита, если сервер передал ее запросу (FEATURE_ASYNC_AUDIT)
+    let audit = AuditWriter::from_request(&req);
+
+    // Извлекаем ID администратора из extensions (добавлен middleware)
+    let actor_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(None));
+        }
+    };
+
+    let user_id = match path_param_uuid(ADMIN_RESET_PASSWORD_PATH, req.uri().path(), "id") {
+        Ok(id) => id,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    // Используем вспомогательную функцию для парсинга тела запроса
+    let (reset_request, request_id) = match parse_body::<AdminResetPasswordRequest>(req).await {
+        Ok(result) => result,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    // Валидируем данные
+    if let Err(validation_errors) = reset_request.validate() {
+        return Ok(AppError::from(validation_errors).into_response(request_id.as_deref()));
+    }
+
+    log::info!(
+        "Запрос на сброс пароля администратором [request_id={}] [admin_id={}] [user_id={}]",
+        request_id.as_deref().unwrap_or("unknown"),
+        actor_id,
+        user_id
+    );
+
+    match admin_reset_password_service(actor_id, user_id, &reset_request, audit.as_ref(), &pool).await {
+        Ok(()) => {
+            let success_response = json!({
+                "success": true,
+                "message": "Пароль пользователя сброшен",
+                "must_change_password": reset_request.must_change_password
+            });
+            let response = json_response(&success_response, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при сбросе пароля [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для POST /api/v1/admin/users/:id/unlock — снятие блокировки входа до ее истечения
+pub async fn unlock_user(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Фоновая запись аудита, если сервер передал ее запросу (FEATURE_ASYNC_AUDIT)
+    let audit = AuditWriter::from_request(&req);
+
+    let request_id = current_request_id(&req);
+
+    // Извлекаем ID администратора из extensions (добавлен middleware)
+    let actor_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(request_id.as_deref()));
+        }
+    };
+
+    let user_id = match path_param_uuid(ADMIN_UNLOCK_USER_PATH, req.uri().path(), "id") {
+        Ok(id) => id,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    log::info!(
+        "Запрос на разблокировку аккаунта [request_id={}] [admin_id={}] [user_id={}]",
+        request_id.as_deref().unwrap_or("unknown"),
+        actor_id,
+        user_id
+    );
+
+    match unlock_user_service(actor_id, user_id, audit.as_ref(), &pool).await {
+        Ok(()) => {
+            let success_response = json!({
+                "success": true,
+                "message": "Аккаунт пользователя разблокирован"
+            });
+            let response = json_response(&success_response, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при разблокировке аккаунта [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для GET /api/v1/admin/users/:id/sessions — активные сессии любого пользователя
+pub async fn list_user_sessions(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    let user_id = match path_param_uuid(ADMIN_USER_SESSIONS_PATH, req.uri().path(), "id") {
+        Ok(id) => id,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    match list_user_sessions_service(user_id, &pool).await {
+        Ok(items) => {
+            let response = json_response(&SessionListResponse { items }, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при получении сессий пользователя [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для GET /api/v1/admin/users/:id/audit — журнал аудита одного пользователя
+// с пагинацией (?offset=&limit=), новые события первыми
+pub async fn list_user_audit(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    let user_id = match path_param_uuid(ADMIN_USER_AUDIT_PATH, req.uri().path(), "id") {
+        Ok(id) => id,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    // Поиск и include_total к журналу не относятся: используются только offset и limit
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+    let ListUsersQuery { offset, limit, .. } = match parse_list_query(req.uri().query(), config.default_page_size) {
+        Ok(params) => params,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    let (events, total) = match list_user_audit_service(user_id, offset, limit, &pool).await {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!(
+                "Ошибка при получении журнала аудита пользователя [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            return Ok(e.into_response(request_id.as_deref()));
+        }
+    };
+
+    let body = AuditEventListResponse {
+        items: events,
+        total,
+        offset,
+        limit,
+    };
+    let mut response = match json_response(&body, StatusCode::OK, request_id.as_deref()) {
+        Ok(response) => response,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    // Ссылки на соседние страницы журнала
+    let links = pagination_link_header(req.uri().path(), total, offset, limit);
+    if let Ok(value) = HeaderValue::from_str(&links) {
+        response.headers_mut().insert(LINK, value);
+    }
+
+    Ok(response)
+}
+
+// Обработчик для GET /api/v1/admin/users/:id/roles — основная и дополнительные роли пользователя
+pub async fn get_user_roles(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    let user_id = match path_param_uuid(ADMIN_USER_ROLES_PATH, req.uri().path(), "id") {
+        Ok(id) => id,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    match user_roles_service(user_id, &pool).await {
+        Ok(roles) => {
+            let response = json_response(&roles, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при получении ролей пользователя [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для PUT /api/v1/admin/users/:id/roles — замена дополнительных ролей пользователя.
+// Основная роль не меняется; дополнительные учитываются при проверке прав с FEATURE_MULTIPLE_ROLES
+pub async fn set_user_roles(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Фоновая запись аудита, если сервер передал ее запросу (FEATURE_ASYNC_AUDIT)
+    let audit = AuditWriter::from_request(&req);
+
+    // Извлекаем ID администратора из extensions (добавлен middleware)
+    let actor_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(None));
+        }
+    };
+
+    let user_id = match path_param_uuid(ADMIN_USER_ROLES_PATH, req.uri().path(), "id") {
+        Ok(id) => id,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    let (roles_request, request_id) = match parse_body::<UserRolesRequest>(req).await {
+        Ok(result) => result,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    log::info!(
+        "Запрос на изменение ролей [request_id={}] [admin_id={}] [user_id={}] [роли={:?}]",
+        request_id.as_deref().unwrap_or("unknown"),
+        actor_id,
+        user_id,
+        roles_request.roles
+    );
+
+    match set_user_roles_service(actor_id, user_id, &roles_request, audit.as_ref(), &pool).await {
+        Ok(roles) => {
+            let response = json_response(&roles, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при изменении ролей [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для DELETE /api/v1/admin/users/:id/sessions — отзыв всех сессий пользователя
+pub async fn revoke_user_sessions(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Фоновая запись аудита, если сервер передал ее запросу (FEATURE_ASYNC_AUDIT)
+    let audit = AuditWriter::from_request(&req);
+
+    let request_id = current_request_id(&req);
+
+    // Извлекаем ID администратора из extensions (добавлен middleware)
+    let actor_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(request_id.as_deref()));
+        }
+    };
+
+    let user_id = match path_param_uuid(ADMIN_USER_SESSIONS_PATH, req.uri().path(), "id") {
+        Ok(id) => id,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    log::info!(
+        "Запрос на отзыв сессий пользователя [request_id={}] [admin_id={}] [user_id={}]",
+        request_id.as_deref().unwrap_or("unknown"),
+        actor_id,
+        user_id
+    );
+
+    match revoke_user_sessions_service(actor_id, user_id, audit.as_ref(), &pool).await {
+        Ok(revoked) => {
+            let response = json_response(&RevokedSessionsResponse { revoked }, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при отзыве сессий [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для GET /api/v1/admin/sessions/stats — число активных сессий всего и по ролям
+pub async fn session_stats(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    match active_session_stats_service(&pool).await {
+        Ok(stats) => {
+            let response = json_response(&stats, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при подсчете активных сессий [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для POST /api/v1/admin/broadcast — перезагрузка конфигурации без перезапуска:
+// перечитывается .env и применяются флаги возможностей, лимиты запросов и CORS
+// (остальные настройки требуют перезапуска, см. AppConfig::with_reloadable).
+// Если новая конфигурация не собирается, действует прежняя, а ответ — 409 с причиной.
+// Хранилище конфигурации и ограничители передает сервер из AppState
+pub async fn reload_config(
+    req: Request<Body>,
+    _pool: PgPool,
+    store: Arc<ConfigStore>,
+    limiters: Arc<RateLimiters>,
+) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    let changed = match store.reload(None) {
+        Ok(changed) => changed,
+        Err(e) => {
+            log::error!(
+                "Конфигурация не перезагружена, действует прежняя [request_id={}]: {}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            return Ok(AppError::Conflict(format!("конфигурация не перезагружена: {}", e)).into_response(request_id.as_deref()));
+        }
+    };
+    limiters.apply(&store.load());
+
+    log::info!(
+        "Конфигурация перезагружена [request_id={}] [admin_id={}], изменены: {:?}",
+        request_id.as_deref().unwrap_or("unknown"),
+        req.extensions().get::<Uuid>().map(|id| id.to_string()).unwrap_or_else(|| "unknown".to_string()),
+        changed
+    );
+
+    let response = json_response(&ConfigReloadResponse { changed }, StatusCode::OK, request_id.as_deref())
+        .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+    Ok(response)
+}
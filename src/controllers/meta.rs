@@ -0,0 +1,101 @@
+use chrono::Utc;
+use hyper::body::Body;
+use hyper::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Request, Response, StatusCode};
+use sqlx::PgPool;
+
+use crate::config::AppConfig;
+use crate::controllers::user::{json_response, json_response_with_cache, CachePolicy};
+use crate::models::{ApiRootResponse, ServerTimeResponse, VersionResponse};
+use crate::utils::current_request_id;
+use std::env;
+
+// Обработчик для GET /api/v1/version — версия сервиса (публичный ответ, кешируется)
+pub async fn version(req: Request<Body>, _pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    let body = VersionResponse {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+    };
+
+    // Версия меняется только при деплое, поэтому ответ можно кешировать
+    let response = json_response_with_cache(&body, StatusCode::OK, request_id.as_deref(), CachePolicy::public(&config))
+        .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+
+    Ok(response)
+}
+
+// Обработчик для GET /api/v1/time — текущее время сервера. Клиент вычитает его из своего времени
+// и учитывает смещение часов при проверке срока действия JWT. Ответ не кешируется
+pub async fn server_time(req: Request<Body>, _pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    let now = Utc::now();
+    let body = ServerTimeResponse {
+        timestamp: now.timestamp(),
+        timestamp_ms: now.timestamp_millis(),
+        time: now,
+    };
+
+    let response = json_response(&body, StatusCode::OK, request_id.as_deref())
+        .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+
+    Ok(response)
+}
+
+// Обработчик для GET / — описание API, чтобы корень сервиса не отвечал 404
+pub async fn root(req: Request<Body>, _pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    let body = ApiRootResponse {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        description: env!("CARGO_PKG_DESCRIPTION"),
+        docs: env::var("API_DOCS_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| env!("CARGO_PKG_REPOSITORY").to_string()),
+        health: "/health",
+    };
+
+    let response = json_response_with_cache(&body, StatusCode::OK, request_id.as_deref(), CachePolicy::public(&config))
+        .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+
+    Ok(response)
+}
+
+// Обработчик для GET /api/v1/features — включенные на развертывании возможности,
+// по которым клиенты решают, какие элементы интерфейса показывать
+pub async fn features(req: Request<Body>, _pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    // Флаги меняются только вместе с конфигурацией при деплое, поэтому ответ кешируется
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+    let response = json_response_with_cache(&config.features, StatusCode::OK, request_id.as_deref(), CachePolicy::public(&config))
+        .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+
+    Ok(response)
+}
+
+// Обработчик для GET /api/v1/ping — самая легкая проба доступности: текст "pong" без JSON,
+// CORS и обращения к БД
+pub fn ping() -> Response<Body> {
+    let mut response = Response::new(Body::from("pong"));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+    response.headers_mut().insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
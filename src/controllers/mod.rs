@@ -3,3 +3,11 @@
 // Объявляем подмодуль user, содержащий контроллеры для работы с пользователями
 pub mod user;
 
+// Объявляем подмодуль admin, содержащий контроллеры для модераторов и администраторов
+pub mod admin;
+
+// Объявляем подмодуль meta, содержащий публичные служебные эндпоинты (версия API)
+pub mod meta;
+
+// Объявляем подмодуль webauthn, содержащий регистрацию ключей доступа (passkey)
+pub mod webauthn;
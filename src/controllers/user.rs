@@ -1,69 +1,211 @@
 use hyper::body::{Body, Bytes};
-use hyper::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use chrono::SubsecRound;
+use hyper::header::{HeaderValue, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE, IF_MODIFIED_SINCE, LAST_MODIFIED, SET_COOKIE};
 use hyper::{Request, Response, StatusCode};
 use serde_json::json;
 use sqlx::PgPool;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::clients::hibp::HibpClient;
+use crate::config::AppConfig;
 use crate::errors::AppError;
-use crate::models::{LoginRequest, UpdateUserRequest, UserRequest, UserResponse, ChangePasswordRequest};
-use crate::services::user::{create_user_service, login_service, update_user_service, change_password_service};
-
-// Вспомогательная функция для парсинга JSON-тела запроса
-async fn parse_json<T: serde::de::DeserializeOwned + std::fmt::Debug>(
-    req: &Request<Body>,
+use crate::middleware::auth::{auth_cookie, request_fingerprint};
+use crate::middleware::proxy::client_ip;
+use crate::middleware::rate_limit::RateLimiters;
+use crate::models::{
+    ChangePasswordRequest, Claims, CookieSessionResponse, LoginRequest, PasswordResetRequest, RecoveryCodesResponse, RefreshTokenRequest,
+    TokenInfoResponse, UpdateUserRequest, User, UserRequest, UserResponse, UserRole,
+};
+use crate::repositories::connection::RequestConnection;
+use crate::repositories::user::find_user_by_id;
+use crate::services::audit::AuditWriter;
+use crate::services::password_reset::request_password_reset_service;
+use crate::services::recovery_code::generate_recovery_codes_service;
+use crate::services::user::{
+    change_password_service, create_user_service, deactivate_user_service, exchange_token_service, get_user_service, login_service_classified,
+    refresh_token_service, security_status_service, update_user_tracked_service,
+};
+use crate::utils::{
+    camel_to_snake, current_request_id, decompress_gzip, http_date, parse_fields_param, parse_http_date, path_param_uuid, project_fields,
+    read_body_limited, rename_json_keys, request_body_limit, strip_json_preamble, to_response_json, JsonCase,
+};
+
+// Шаблон пути профиля пользователя по ID
+pub const USER_BY_ID_PATH: &str = "/api/v1/users/:id";
+
+// Вспомогательная функция для парсинга тела запроса.
+// Формат выбирается по Content-Type: application/x-www-form-urlencoded (для старых клиентов,
+// отправляющих формы) или JSON (по умолчанию, в том числе без Content-Type)
+pub(crate) async fn parse_body<T: serde::de::DeserializeOwned + std::fmt::Debug>(
+    req: Request<Body>,
 ) -> Result<(T, Option<String>), AppError> {
-    // Извлекаем request_id из заголовка, если есть
-    let request_id = req
+    // ID запроса: внутренний (с суффиксом для повторного X-Request-ID) или из заголовка
+    let request_id = current_request_id(&req);
+    let config = AppConfig::from_request(&req)?;
+
+    let is_form = req
         .headers()
-        .get("X-Request-ID")
+        .get(CONTENT_TYPE)
         .and_then(|v| v.to_str().ok())
-        .map(String::from);
+        .map(|v| v.trim_start().to_ascii_lowercase().starts_with("application/x-www-form-urlencoded"))
+        .unwrap_or(false);
+    let content_encoding = req
+        .headers()
+        .get(CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_ascii_lowercase());
 
-    // Парсим тело запроса
-    let body_bytes: Bytes = hyper::body::to_bytes(req.into_body())
-        .await
-        .map_err(|e| {
-            log::error!(
-                "Ошибка чтения тела запроса [request_id={}]: {:?}",
+    // Предельный размер тела зависит от класса маршрута (вход — маленький, массовые операции — большой)
+    let body_limit = request_body_limit(&req);
+
+    // Читаем тело не больше лимита класса маршрута (защита от DoS): сверх лимита — 413 без
+    // буферизации остатка
+    let body_bytes = read_body_limited(req.into_body(), body_limit).await.map_err(|e| {
+        log::warn!(
+            "Ошибка чтения тела запроса [request_id={}]: {:?}",
+            request_id.as_deref().unwrap_or("unknown"),
+            e
+        );
+        e
+    })?;
+
+    // Проверяем, что тело не пустое
+    if body_bytes.is_empty() {
+        return Err(AppError::BadRequest("Тело запроса не может быть пустым".to_string()));
+    }
+
+    // Сжатое тело (например, при массовом импорте) распаковывается не больше лимита класса
+    // маршрута, иначе маленькое сжатое тело входа раскрылось бы до MAX_DECOMPRESSED_BODY_SIZE
+    let body_bytes = match content_encoding.as_deref() {
+        None | Some("identity") => body_bytes,
+        Some("gzip") | Some("x-gzip") => {
+            let decompressed_limit = body_limit.min(config.max_decompressed_body_size);
+            let decompressed = decompress_gzip(&body_bytes, decompressed_limit).map_err(|e| {
+                log::warn!(
+                    "Не удалось распаковать тело запроса [request_id={}]: {:?}",
+                    request_id.as_deref().unwrap_or("unknown"),
+                    e
+                );
+                e
+            })?;
+            Bytes::from(decompressed)
+        }
+        Some(other) => {
+            return Err(AppError::BadRequest(format!("Неподдерживаемый Content-Encoding: {}", other)));
+        }
+    };
+
+    // Парсим данные формы
+    if is_form {
+        let result: T = serde_urlencoded::from_bytes(&body_bytes).map_err(|e| {
+            log::warn!(
+                "Ошибка парсинга данных формы [request_id={}]: {:?}",
                 request_id.as_deref().unwrap_or("unknown"),
                 e
             );
-            AppError::BadRequest("Не удалось прочитать тело запроса".to_string())
+            AppError::BadRequest(format!("Некорректные данные формы: {}", e))
         })?;
+        return Ok((result, request_id));
+    }
 
-    // Проверяем, что тело не пустое
+    // BOM и пробелы в начале тела не считаются ошибкой
+    let body_bytes = strip_json_preamble(&body_bytes);
     if body_bytes.is_empty() {
         return Err(AppError::BadRequest("Тело запроса не может быть пустым".to_string()));
     }
 
-    // Ограничиваем размер для защиты от DoS
-    if body_bytes.len() > 1024 * 1024 {
-        // 1 MB лимит
-        return Err(AppError::BadRequest("Тело запроса слишком большое".to_string()));
-    }
-
-    // Парсим JSON
-    let result: T = serde_json::from_slice(&body_bytes).map_err(|e| {
+    // Парсим JSON. В режиме JSON_CASE=camel принимаются ключи и в camelCase, и в snake_case
+    let parsed = match config.json_case {
+        JsonCase::Snake => serde_json::from_slice(body_bytes),
+        JsonCase::Camel => serde_json::from_slice::<serde_json::Value>(body_bytes)
+            .and_then(|value| serde_json::from_value(rename_json_keys(value, camel_to_snake))),
+    };
+    let result: T = parsed.map_err(|e| {
         log::warn!(
             "Ошибка парсинга JSON [request_id={}]: {:?}",
             request_id.as_deref().unwrap_or("unknown"),
             e
         );
+
+        // Все тела запросов API — объекты. Если JSON корректен, но верхний уровень другого вида
+        // (частая ошибка — массив вместо объекта), сообщаем об этом вместо ошибки serde
+        if e.classify() == serde_json::error::Category::Data {
+            if let Some(kind) = serde_json::from_slice::<serde_json::Value>(body_bytes)
+                .ok()
+                .as_ref()
+                .and_then(non_object_json_kind)
+            {
+                return AppError::BadRequest(format!("Некорректный JSON: ожидается JSON-объект, получен {}", kind));
+            }
+        }
+
         AppError::BadRequest(format!("Некорректный JSON: {}", e))
     })?;
 
     Ok((result, request_id))
 }
 
+// Название вида JSON-значения верхнего уровня, если это не объект
+fn non_object_json_kind(value: &serde_json::Value) -> Option<&'static str> {
+    match value {
+        serde_json::Value::Object(_) => None,
+        serde_json::Value::Array(_) => Some("массив"),
+        serde_json::Value::String(_) => Some("строка"),
+        serde_json::Value::Number(_) => Some("число"),
+        serde_json::Value::Bool(_) => Some("логическое значение"),
+        serde_json::Value::Null => Some("null"),
+    }
+}
+
+// Профиль пользователя для ответа; при ?fields= в него попадают только запрошенные поля
+fn user_response_json(user: &User, fields: Option<&[String]>) -> Result<serde_json::Value, AppError> {
+    let value = serde_json::to_value(UserResponse::from(user)).map_err(|e| AppError::Internal(anyhow::anyhow!(e)))?;
+    Ok(match fields {
+        Some(fields) => project_fields(value, fields),
+        None => value,
+    })
+}
+
 // Вспомогательная функция для создания JSON-ответа
-fn json_response<T: serde::Serialize>(
+pub(crate) fn json_response<T: serde::Serialize>(
+    data: &T,
+    status: StatusCode,
+    request_id: Option<&str>,
+) -> Result<Response<Body>, AppError> {
+    json_response_with_cache(data, status, request_id, CachePolicy::NoStore)
+}
+
+// Политика кеширования ответа
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CachePolicy {
+    NoStore,                  // Данные пользователя и токены — не кешируются (по умолчанию)
+    Public { max_age: u64 },  // Публичные неизменяемые данные — кешируются клиентами и прокси
+}
+
+impl CachePolicy {
+    // Публичный кеш со временем жизни из PUBLIC_CACHE_MAX_AGE конфигурации запроса
+    pub fn public(config: &AppConfig) -> Self {
+        CachePolicy::Public { max_age: config.public_cache_max_age }
+    }
+
+    fn header_value(self) -> HeaderValue {
+        match self {
+            CachePolicy::NoStore => HeaderValue::from_static("no-store"),
+            CachePolicy::Public { max_age } => HeaderValue::from_str(&format!("public, max-age={}", max_age))
+                .unwrap_or_else(|_| HeaderValue::from_static("no-store")),
+        }
+    }
+}
+
+// То же, что json_response, но с явно заданной политикой кеширования
+pub(crate) fn json_response_with_cache<T: serde::Serialize>(
     data: &T,
     status: StatusCode,
     request_id: Option<&str>,
+    cache: CachePolicy,
 ) -> Result<Response<Body>, AppError> {
-    let json = serde_json::to_string(data).map_err(|e| {
+    let json = to_response_json(data).map_err(|e| {
         log::error!(
             "Ошибка сериализации JSON [request_id={}]: {:?}",
             request_id.unwrap_or("unknown"),
@@ -75,7 +217,7 @@ fn json_response<T: serde::Serialize>(
     let mut response = Response::builder()
         .status(status)
         .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
-        .header(CACHE_CONTROL, HeaderValue::from_static("no-store"))
+        .header(CACHE_CONTROL, cache.header_value())
         .body(Body::from(json))
         .map_err(|e| AppError::Internal(anyhow::anyhow!(e)))?;
 
@@ -89,14 +231,49 @@ fn json_response<T: serde::Serialize>(
     Ok(response)
 }
 
+// Вспомогательная функция для ответа 304 Not Modified (без тела)
+fn not_modified_response(request_id: Option<&str>) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::NOT_MODIFIED;
+    response
+        .headers_mut()
+        .insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+    // Добавляем request_id в заголовок ответа, если он был
+    if let Some(id) = request_id {
+        if let Ok(value) = HeaderValue::from_str(id) {
+            response.headers_mut().insert("X-Request-ID", value);
+        }
+    }
+
+    response
+}
+
 // Обработчик для POST /api/users — создание пользователя
 pub async fn create_user(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
     // Начало обработки запроса
     let start_time = std::time::Instant::now();
     log::info!("Начало обработки запроса на создание пользователя");
 
-    // Используем вспомогательную функцию для парсинга JSON
-    let (user_request, request_id) = match parse_json::<UserRequest>(req).await {
+    // ID запроса для исходящих вызовов берем до разбора тела, которое поглощает запрос
+    let outbound_request_id = current_request_id(&req);
+
+    // При закрытой регистрации (FEATURE_SIGNUPS_OPEN=false) тело запроса не читается
+    match AppConfig::from_request(&req) {
+        Ok(config) if config.features.signups_open => {}
+        Ok(_) => {
+            log::info!(
+                "Регистрация отклонена: самостоятельная регистрация закрыта [request_id={}]",
+                outbound_request_id.as_deref().unwrap_or("unknown")
+            );
+            let error = AppError::Forbidden("самостоятельная регистрация закрыта".to_string());
+            return Ok(error.into_response(outbound_request_id.as_deref()));
+        }
+        Err(e) => return Ok(e.into_response(outbound_request_id.as_deref())),
+    }
+
+    // Используем вспомогательную функцию для парсинга тела запроса
+    let (user_request, request_id) = match parse_body::<UserRequest>(req).await {
         Ok(result) => result,
         Err(e) => return Ok(e.into_response(None)),
     };
@@ -111,6 +288,30 @@ pub async fn create_user(req: Request<Body>, pool: PgPool) -> Result<Response<Bo
         return Ok(AppError::from(validation_errors).into_response(request_id.as_deref()));
     }
 
+    // Проверка пароля по базе утечек (PASSWORD_BREACH_CHECK). Недоступность сервиса
+    // не блокирует регистрацию
+    if let Some(hibp) = HibpClient::from_env() {
+        match hibp.is_password_pwned(&user_request.password, outbound_request_id.as_deref()).await {
+            Ok(true) => {
+                log::warn!(
+                    "Регистрация с паролем из известных утечек отклонена [request_id={}]",
+                    request_id.as_deref().unwrap_or("unknown")
+                );
+                let error = AppError::validation_errors(vec![(
+                    "password".to_string(),
+                    "Пароль встречается в известных утечках, выберите другой".to_string(),
+                )]);
+                return Ok(error.into_response(request_id.as_deref()));
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!(
+                "Не удалось проверить пароль по базе утечек [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            ),
+        }
+    }
+
     // Вызываем сервис для создания пользователя
     let user = match create_user_service(user_request, &pool).await {
         Ok(user) => {
@@ -159,8 +360,26 @@ pub async fn login(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, h
         .map(|addr| addr.to_string())
         .unwrap_or_else(|| "unknown".to_string());
 
-    // Используем вспомогательную функцию для парсинга JSON
-    let (login_request, request_id) = match parse_json::<LoginRequest>(req).await {
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    // Неудачные попытки ограничиваются по IP клиента, определенному так же, как для общего
+    // лимита запросов: после исчерпания лимита вход сразу отклоняется
+    let limiters = RateLimiters::from_request(&req);
+    if let Some(limiters) = &limiters {
+        if let Err(response) = limiters.login.check_request(&req, &config.trusted_proxies) {
+            return Ok(response);
+        }
+    }
+    let client_ip = client_ip(&req, &config.trusted_proxies);
+
+    // Отпечаток клиента вычисляем до разбора тела, которое поглощает запрос
+    let fingerprint = request_fingerprint(&req);
+
+    // Используем вспомогательную функцию для парсинга тела запроса
+    let (login_request, request_id) = match parse_body::<LoginRequest>(req).await {
         Ok(result) => result,
         Err(e) => {
             log::warn!(
@@ -191,7 +410,7 @@ pub async fn login(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, h
     );
 
     // Вызываем сервис для авторизации
-    let auth_result = match login_service(login_request.clone(), &pool).await {
+    let auth_result = match login_service_classified(login_request.clone(), &config, Some(fingerprint), &pool).await {
         Ok(result) => {
             log::info!(
                 "Успешная авторизация [ip={}] [request_id={}] [email={}] [user_id={}]",
@@ -202,15 +421,18 @@ pub async fn login(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, h
             );
             result
         }
-        Err(e) => {
+        Err(failure) => {
             log::warn!(
                 "Неудачная авторизация [ip={}] [request_id={}] [email={}]: {:?}",
                 remote_addr,
                 request_id.as_deref().unwrap_or("unknown"),
                 login_request.email,
-                e
+                failure.error
             );
-            return Ok(e.into_response(request_id.as_deref()));
+            if let (Some(limiters), Some(ip), Some(reason)) = (&limiters, client_ip, failure.reason) {
+                limiters.login.record_failure(ip, reason);
+            }
+            return Ok(failure.error.into_response(request_id.as_deref()));
         }
     };
 
@@ -221,6 +443,179 @@ pub async fn login(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, h
     Ok(response)
 }
 
+// Обработчик для POST /api/v1/auth/password-reset — запрос сброса пароля по email.
+// Ответ одинаковый для существующих, неизвестных и превысивших лимит адресов, чтобы по нему
+// нельзя было перебирать зарегистрированные email
+pub async fn request_password_reset(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+    if !config.features.password_reset {
+        return Ok(AppError::NotFound("Сброс пароля отключен".to_string()).into_response(request_id.as_deref()));
+    }
+
+    let (reset_request, request_id) = match parse_body::<PasswordResetRequest>(req).await {
+        Ok(result) => result,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    if let Err(validation_errors) = reset_request.validate() {
+        return Ok(AppError::from(validation_errors).into_response(request_id.as_deref()));
+    }
+
+    match request_password_reset_service(&reset_request.email, config.password_reset, &pool).await {
+        // Токен передается пользователю только по email и в ответ и логи не попадает
+        Ok(_token) => {
+            log::info!(
+                "Обработан запрос сброса пароля [request_id={}]",
+                request_id.as_deref().unwrap_or("unknown")
+            );
+            let body = json!({
+                "message": "Если аккаунт с таким email существует, на него отправлена ссылка для сброса пароля"
+            });
+            let response = json_response(&body, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при запросе сброса пароля [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для GET /api/v1/users/me — профиль текущего пользователя
+pub async fn get_current_user(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Извлекаем user_id из extensions (добавлен middleware)
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(None));
+        }
+    };
+
+    let request_id = current_request_id(&req);
+
+    let fields = match parse_fields_param(req.uri().query(), UserResponse::FIELDS) {
+        Ok(fields) => fields,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    match find_user_by_id(user_id, &pool).await {
+        Ok(user) => {
+            // Профиль содержит персональные данные, поэтому ответ не кешируется (no-store)
+            let response = user_response_json(&user, fields.as_deref())
+                .and_then(|body| json_response(&body, StatusCode::OK, request_id.as_deref()))
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => Ok(e.into_response(request_id.as_deref())),
+    }
+}
+
+// Обработчик для GET /api/v1/users/me/security — сводка безопасности аккаунта
+pub async fn get_security_status(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Извлекаем user_id из extensions (добавлен middleware)
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(None));
+        }
+    };
+
+    let request_id = current_request_id(&req);
+
+    // Сводка читает несколько таблиц — все запросы идут через соединение запроса
+    let db = RequestConnection::from_request(&req, &pool);
+    let mut conn = match db.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    match security_status_service(user_id, &mut conn).await {
+        Ok(status) => {
+            let response = json_response(&status, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при получении сводки безопасности [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для GET /api/v1/users/:id — профиль пользователя по ID.
+// Поддерживает условные запросы: Last-Modified по updated_at и ответ 304 на If-Modified-Since
+pub async fn get_user(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Извлекаем данные пользователя из extensions (добавлены middleware)
+    let (actor_id, actor_role) = match (req.extensions().get::<Uuid>(), req.extensions().get::<UserRole>()) {
+        (Some(id), Some(role)) => (*id, *role),
+        _ => {
+            log::error!("user_id или роль отсутствуют в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(None));
+        }
+    };
+
+    let request_id = current_request_id(&req);
+
+    let user_id = match path_param_uuid(USER_BY_ID_PATH, req.uri().path(), "id") {
+        Ok(id) => id,
+        Err(e) => {
+            log::warn!(
+                "Некорректный ID в пути [request_id={}] [path={}]",
+                request_id.as_deref().unwrap_or("unknown"),
+                req.uri().path()
+            );
+            return Ok(e.into_response(request_id.as_deref()));
+        }
+    };
+
+    let fields = match parse_fields_param(req.uri().query(), UserResponse::FIELDS) {
+        Ok(fields) => fields,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    // Дата из If-Modified-Since (некорректная дата игнорируется, как того требует RFC 7232)
+    let if_modified_since = req
+        .headers()
+        .get(IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_http_date);
+
+    match get_user_service(actor_id, actor_role, user_id, &pool).await {
+        Ok(user) => {
+            // HTTP-date содержит только секунды, поэтому updated_at сравнивается без долей секунды
+            let last_modified = user.updated_at.trunc_subsecs(0);
+            let mut response = if if_modified_since.is_some_and(|since| last_modified <= since) {
+                not_modified_response(request_id.as_deref())
+            } else {
+                user_response_json(&user, fields.as_deref())
+                    .and_then(|body| json_response(&body, StatusCode::OK, request_id.as_deref()))
+                    .unwrap_or_else(|e| e.into_response(request_id.as_deref()))
+            };
+            if let Ok(value) = HeaderValue::from_str(&http_date(last_modified)) {
+                response.headers_mut().insert(LAST_MODIFIED, value);
+            }
+            Ok(response)
+        }
+        Err(e) => Ok(e.into_response(request_id.as_deref())),
+    }
+}
+
 // Обработчик для PATCH /api/users/me — обновление данных пользователя
 pub async fn update_user(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
     // Извлекаем user_id из extensions (добавлен middleware)
@@ -232,14 +627,24 @@ pub async fn update_user(req: Request<Body>, pool: PgPool) -> Result<Response<Bo
         }
     };
 
-    // Используем вспомогательную функцию для парсинга JSON
-    let (update_request, request_id) = match parse_json::<UpdateUserRequest>(req).await {
+    // Параметр частичного ответа проверяется до разбора тела (тело забирает запрос целиком)
+    let fields = match parse_fields_param(req.uri().query(), UserResponse::FIELDS) {
+        Ok(fields) => fields,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    // Используем вспомогательную функцию для парсинга тела запроса
+    let (update_request, request_id) = match parse_body::<UpdateUserRequest>(req).await {
         Ok(result) => result,
         Err(e) => return Ok(e.into_response(None)),
     };
 
     // Проверяем, что хотя бы одно поле задано
-    if update_request.name.is_none() && update_request.age.is_none() {
+    if update_request.name.is_none()
+        && update_request.age.is_none()
+        && update_request.email.is_none()
+        && update_request.timezone.is_none()
+    {
         let error = AppError::BadRequest("Необходимо указать хотя бы одно поле для обновления".to_string());
         return Ok(error.into_response(request_id.as_deref()));
     }
@@ -263,8 +668,17 @@ pub async fn update_user(req: Request<Body>, pool: PgPool) -> Result<Response<Bo
     );
 
     // Вызываем сервис для обновления пользователя
-    let updated_user = match update_user_service(user_id, update_request, &pool).await {
-        Ok(user) => {
+    let updated_user = match update_user_tracked_service(user_id, update_request, &pool).await {
+        Ok((_, false)) => {
+            // Все поля совпадают с текущими — отвечаем 304 без тела
+            log::info!(
+                "Обновление пользователя не содержит изменений [request_id={}] [user_id={}]",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id
+            );
+            return Ok(not_modified_response(request_id.as_deref()));
+        }
+        Ok((user, true)) => {
             log::info!(
                 "Пользователь успешно обновлен [request_id={}] [user_id={}]",
                 request_id.as_deref().unwrap_or("unknown"),
@@ -283,11 +697,9 @@ pub async fn update_user(req: Request<Body>, pool: PgPool) -> Result<Response<Bo
         }
     };
 
-    // Создаем безопасный ответ (без чувствительных данных)
-    let user_response = UserResponse::from(&updated_user);
-
-    // Формируем и возвращаем ответ
-    let response = json_response(&user_response, StatusCode::OK, request_id.as_deref())
+    // Формируем безопасный ответ (без чувствительных данных) и возвращаем его
+    let response = user_response_json(&updated_user, fields.as_deref())
+        .and_then(|body| json_response(&body, StatusCode::OK, request_id.as_deref()))
         .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
 
     Ok(response)
@@ -304,8 +716,8 @@ pub async fn change_password(req: Request<Body>, pool: PgPool) -> Result<Respons
         }
     };
 
-    // Используем вспомогательную функцию для парсинга JSON
-    let (change_pwd_request, request_id) = match parse_json::<ChangePasswordRequest>(req).await {
+    // Используем вспомогательную функцию для парсинга тела запроса
+    let (change_pwd_request, request_id) = match parse_body::<ChangePasswordRequest>(req).await {
         Ok(result) => result,
         Err(e) => return Ok(e.into_response(None)),
     };
@@ -358,4 +770,206 @@ pub async fn change_password(req: Request<Body>, pool: PgPool) -> Result<Respons
             Ok(e.into_response(request_id.as_deref()))
         }
     }
-}
\ No newline at end of file
+}
+
+// Обработчик для POST /api/v1/users/me/2fa/recovery-codes — выпуск новых кодов восстановления.
+// Маршрут доступен только при FEATURE_2FA, иначе отвечает 404
+pub async fn generate_recovery_codes(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    // Извлекаем user_id из extensions (добавлен middleware)
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(request_id.as_deref()));
+        }
+    };
+
+    match AppConfig::from_request(&req) {
+        Ok(config) if config.features.two_factor => {}
+        Ok(_) => {
+            let error = AppError::NotFound("выпуск кодов восстановления".to_string());
+            return Ok(error.into_response(request_id.as_deref()));
+        }
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    }
+
+    log::info!(
+        "Запрос на выпуск кодов восстановления [request_id={}] [user_id={}]",
+        request_id.as_deref().unwrap_or("unknown"),
+        user_id
+    );
+
+    match generate_recovery_codes_service(user_id, &pool).await {
+        Ok(codes) => {
+            // Коды показываются один раз; json_response запрещает их кеширование (no-store)
+            let response = json_response(&RecoveryCodesResponse { codes }, StatusCode::CREATED, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при выпуске кодов восстановления [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для POST /api/v1/users/me/deactivate — самостоятельная деактивация аккаунта
+pub async fn deactivate_current_user(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Фоновая запись аудита, если сервер передал ее запросу (FEATURE_ASYNC_AUDIT)
+    let audit = AuditWriter::from_request(&req);
+
+    // Извлекаем user_id из extensions (добавлен middleware)
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(None));
+        }
+    };
+
+    let request_id = current_request_id(&req);
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    log::info!(
+        "Запрос на деактивацию аккаунта [request_id={}] [user_id={}]",
+        request_id.as_deref().unwrap_or("unknown"),
+        user_id
+    );
+
+    match deactivate_user_service(user_id, config.reactivation_window_days, audit.as_ref(), &pool).await {
+        Ok(deactivation) => {
+            let response = json_response(&deactivation, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при деактивации аккаунта [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для GET /api/v1/auth/verify — проверка токена без обращения к БД
+pub async fn verify_token(req: Request<Body>, _pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    // Claims добавлены auth_middleware после успешной проверки подписи и срока действия
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims,
+        None => {
+            log::error!("claims отсутствуют в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(request_id.as_deref()));
+        }
+    };
+
+    let response = json_response(&TokenInfoResponse::from(claims), StatusCode::OK, request_id.as_deref())
+        .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+
+    Ok(response)
+}
+
+// Обработчик для POST /api/v1/auth/to-cookie — обмен токена из заголовка на cookie-сессию.
+// С ?revoke_bearer=true исходный токен после обмена перестает приниматься
+pub async fn exchange_token_for_cookie(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    // Claims добавлены auth_middleware после успешной проверки токена
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => {
+            log::error!("claims отсутствуют в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(request_id.as_deref()));
+        }
+    };
+
+    let pairs: Vec<(String, String)> =
+        serde_urlencoded::from_str(req.uri().query().unwrap_or("")).unwrap_or_default();
+    let revoke_bearer = pairs
+        .iter()
+        .any(|(key, value)| key == "revoke_bearer" && matches!(value.as_str(), "true" | "1"));
+
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    let (token, expires_in, bearer_revoked) = match exchange_token_service(&claims, revoke_bearer, &config, &pool).await {
+        Ok(result) => result,
+        Err(e) => {
+            log::warn!(
+                "Ошибка обмена токена на cookie-сессию [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            return Ok(e.into_response(request_id.as_deref()));
+        }
+    };
+
+    let body = CookieSessionResponse { expires_in, bearer_revoked };
+    let mut response = match json_response(&body, StatusCode::OK, request_id.as_deref()) {
+        Ok(response) => response,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+    match HeaderValue::from_str(&auth_cookie(&token, expires_in)) {
+        Ok(cookie) => {
+            response.headers_mut().insert(SET_COOKIE, cookie);
+        }
+        Err(e) => {
+            log::error!("Некорректное значение cookie сессии: {:?}", e);
+            return Ok(AppError::Internal(anyhow::anyhow!(e)).into_response(request_id.as_deref()));
+        }
+    }
+
+    Ok(response)
+}
+
+// Обработчик для POST /api/v1/auth/refresh — новый JWT по refresh-токену (без JWT в запросе).
+// Предъявленный refresh-токен погашается, в ответе выдается новый
+pub async fn refresh_token(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Отпечаток клиента и конфигурацию берем до разбора тела, которое поглощает запрос
+    let fingerprint = request_fingerprint(&req);
+    let config = match AppConfig::from_request(&req) {
+        Ok(config) => config,
+        Err(e) => return Ok(e.into_response(current_request_id(&req).as_deref())),
+    };
+
+    let (refresh_request, request_id) = match parse_body::<RefreshTokenRequest>(req).await {
+        Ok(result) => result,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    if let Err(validation_errors) = refresh_request.validate() {
+        return Ok(AppError::from(validation_errors).into_response(request_id.as_deref()));
+    }
+
+    match refresh_token_service(&refresh_request.refresh_token, Some(fingerprint), &config, &pool).await {
+        Ok(refreshed) => {
+            let response = json_response(&refreshed, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::warn!(
+                "Ошибка обновления токена [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
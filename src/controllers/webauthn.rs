@@ -0,0 +1,98 @@
+use hyper::body::Body;
+use hyper::{Request, Response, StatusCode};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::{AppConfig, WebauthnSettings};
+use crate::controllers::user::{json_response, parse_body};
+use crate::errors::AppError;
+use crate::models::WebauthnRegisterFinishRequest;
+use crate::services::webauthn::{finish_registration_service, start_registration_service};
+use crate::utils::current_request_id;
+
+// Настройки WebAuthn из конфигурации запроса. Без WEBAUTHN_RP_ID и WEBAUTHN_ORIGIN
+// регистрация ключей выключена, и маршруты отвечают 404
+fn webauthn_settings(req: &Request<Body>) -> Result<WebauthnSettings, AppError> {
+    AppConfig::from_request(req)?
+        .webauthn
+        .clone()
+        .ok_or_else(|| AppError::NotFound("регистрация ключей доступа".to_string()))
+}
+
+// Обработчик для POST /api/v1/auth/webauthn/register/start — параметры создания ключа доступа
+pub async fn webauthn_register_start(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    let request_id = current_request_id(&req);
+
+    // Извлекаем user_id из extensions (добавлен middleware)
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(request_id.as_deref()));
+        }
+    };
+
+    let settings = match webauthn_settings(&req) {
+        Ok(settings) => settings,
+        Err(e) => return Ok(e.into_response(request_id.as_deref())),
+    };
+
+    match start_registration_service(user_id, &settings, &pool).await {
+        Ok(options) => {
+            // Challenge одноразовый: json_response запрещает кеширование ответа (no-store)
+            let response = json_response(&options, StatusCode::OK, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при начале регистрации ключа доступа [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Обработчик для POST /api/v1/auth/webauthn/register/finish — проверка ответа аутентификатора
+// и сохранение ключа доступа
+pub async fn webauthn_register_finish(req: Request<Body>, pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Извлекаем user_id из extensions (добавлен middleware)
+    let user_id = match req.extensions().get::<Uuid>() {
+        Some(id) => *id,
+        None => {
+            log::error!("user_id отсутствует в middleware, возможный баг в коде");
+            return Ok(AppError::Unauthorized.into_response(None));
+        }
+    };
+
+    let settings = match webauthn_settings(&req) {
+        Ok(settings) => settings,
+        Err(e) => return Ok(e.into_response(current_request_id(&req).as_deref())),
+    };
+
+    // Используем вспомогательную функцию для парсинга тела запроса
+    let (finish_request, request_id) = match parse_body::<WebauthnRegisterFinishRequest>(req).await {
+        Ok(result) => result,
+        Err(e) => return Ok(e.into_response(None)),
+    };
+
+    match finish_registration_service(user_id, &settings, finish_request, &pool).await {
+        Ok(credential) => {
+            let response = json_response(&credential, StatusCode::CREATED, request_id.as_deref())
+                .unwrap_or_else(|e| e.into_response(request_id.as_deref()));
+            Ok(response)
+        }
+        Err(e) => {
+            log::warn!(
+                "Регистрация ключа доступа отклонена [request_id={}] [user_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id,
+                e
+            );
+            Ok(e.into_response(request_id.as_deref()))
+        }
+    }
+}
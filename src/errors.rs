@@ -6,6 +6,8 @@ use serde::Serialize;
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::config::{AppConfig, ErrorDetail, DEFAULT_MAX_FIELD_ERRORS};
+
 // Enum для ошибок приложения с расширенными типами
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -15,6 +17,9 @@ pub enum AppError {
     #[error("Ошибка авторизации: недействительный токен")]
     InvalidToken,
     
+    #[error("Ошибка авторизации: срок действия токена истек")]
+    TokenExpired,
+    
     #[error("Ошибка авторизации: недостаточно прав или {0}")]
     Forbidden(String), // Изменено: добавлен параметр для передачи сообщения
     
@@ -24,12 +29,21 @@ pub enum AppError {
     #[error("Ошибка запроса: {0}")]
     BadRequest(String),
     
-    #[error("Ошибка валидации: {0}")]
-    ValidationError(String),
+    #[error("Ошибка валидации: {}", join_field_errors(.0))]
+    ValidationError(Vec<FieldError>),
+    
+    #[error("Неприемлемый формат ответа: {0}")]
+    NotAcceptable(String),
     
     #[error("Конфликт данных: {0}")]
     Conflict(String),
     
+    #[error("Аккаунт временно заблокирован до {0}")]
+    AccountLocked(chrono::DateTime<chrono::Utc>),
+    
+    #[error("Аккаунт деактивирован, восстановление доступно до {0}")]
+    ReactivationAvailable(chrono::DateTime<chrono::Utc>),
+    
     #[error("Превышен лимит запросов")]
     RateLimited,
     
@@ -41,27 +55,62 @@ pub enum AppError {
     
     #[error("Сервис временно недоступен")]
     ServiceUnavailable,
+    
+    #[error("Слишком большое тело запроса: {0}")]
+    PayloadTooLarge(String),
 }
 
+// Сообщение о занятом email (по нему же определяется код USER_EMAIL_TAKEN)
+const EMAIL_TAKEN_MESSAGE: &str = "Пользователь с таким email уже существует";
+
 // Структура для сериализации ошибок в JSON
 #[derive(Serialize)]
 struct ErrorResponse {
     status: u16,
     error: String,
+    code: &'static str,
     message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     details: Option<String>,
     trace_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     field_errors: Option<Vec<FieldError>>,
-    timestamp: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    field_errors_truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locked_until: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    reactivation_available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reactivate_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<String>,
 }
 
-// Структура для сериализации ошибок валидации полей
-#[derive(Serialize)]
-struct FieldError {
-    field: String,
-    message: String,
+// Сколько ошибок полей включать в ответ: MAX_FIELD_ERRORS обрабатываемого запроса
+// (вне запроса — значение по умолчанию)
+fn max_field_errors() -> usize {
+    AppConfig::current()
+        .map(|config| config.max_field_errors)
+        .unwrap_or(DEFAULT_MAX_FIELD_ERRORS)
+}
+
+// Ошибка валидации одного поля
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>, // Код нарушенного правила (length, email, range...), если известен
+}
+
+// Ошибки полей одной строкой "поле: ошибка; поле: ошибка" (для details и логов)
+fn join_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| if e.field.is_empty() { e.message.clone() } else { format!("{}: {}", e.field, e.message) })
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 // Расширенная реализация преобразования ошибок в HTTP-ответы
@@ -76,6 +125,8 @@ impl AppError {
         let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
         
         // Определяем статус и сообщение на основе типа ошибки
+        // (сообщение NotFound формируется здесь, чтобы ссылка на него жила до конца функции)
+        let not_found_message;
         let (status, error_type, message, details) = match &self {
             AppError::Unauthorized => {
                 (StatusCode::UNAUTHORIZED, "Unauthorized", "Требуется авторизация", None)
@@ -83,6 +134,9 @@ impl AppError {
             AppError::InvalidToken => {
                 (StatusCode::UNAUTHORIZED, "InvalidToken", "Недействительный токен авторизации", None)
             }
+            AppError::TokenExpired => {
+                (StatusCode::UNAUTHORIZED, "TokenExpired", "Срок действия токена истек", None)
+            }
             AppError::Forbidden(msg) => {
                 // Исправлено: используем переданное сообщение или дефолтное
                 let message = if msg.is_empty() {
@@ -94,19 +148,43 @@ impl AppError {
             }
             AppError::NotFound(resource) => {
                 // Формируем сообщение
-                let message = format!("Ресурс не найден: {}", resource);
-                (StatusCode::NOT_FOUND, "NotFound", message.as_str(), None)
+                not_found_message = format!("Ресурс не найден: {}", resource);
+                (StatusCode::NOT_FOUND, "NotFound", not_found_message.as_str(), None)
             }
             AppError::BadRequest(msg) => {
                // Конвертируем String в &str для согласованности с другими вариантами
               (StatusCode::BAD_REQUEST, "BadRequest", msg.as_str(), None)
             }
-            AppError::ValidationError(msg) => {
-                (StatusCode::BAD_REQUEST, "ValidationError", "Ошибка валидации данных", Some(msg.clone()))
+            AppError::ValidationError(_) => {
+                (StatusCode::BAD_REQUEST, "ValidationError", "Ошибка валидации данных", None)
+            }
+            AppError::NotAcceptable(msg) => {
+                (StatusCode::NOT_ACCEPTABLE, "NotAcceptable", msg.as_str(), None)
             }
             AppError::Conflict(msg) => {
                 (StatusCode::CONFLICT, "Conflict", msg.as_str(), None)
             }
+            AppError::AccountLocked(until) => {
+                let remaining = (*until - chrono::Utc::now()).num_seconds().max(0);
+                (
+                    StatusCode::FORBIDDEN,
+                    "AccountLocked",
+                    "Аккаунт временно заблокирован из-за неудачных попыток входа",
+                    Some(format!(
+                        "Повторите попытку через {} мин. {} сек.",
+                        remaining / 60,
+                        remaining % 60
+                    )),
+                )
+            }
+            AppError::ReactivationAvailable(_) => {
+                (
+                    StatusCode::FORBIDDEN,
+                    "ReactivationAvailable",
+                    "Аккаунт деактивирован",
+                    Some("Чтобы восстановить аккаунт, повторите вход с reactivate: true".to_string()),
+                )
+            }
             AppError::RateLimited => {
                 (StatusCode::TOO_MANY_REQUESTS, "RateLimited", "Превышен лимит запросов", None)
             }
@@ -117,7 +195,7 @@ impl AppError {
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "InternalServerError",
                     "Внутренняя ошибка сервера",
-                    None,
+                    Some(format!("{:#}", err)),
                 )
             }
             AppError::Database(err) => {
@@ -127,7 +205,7 @@ impl AppError {
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "DatabaseError",
                     "Ошибка при работе с базой данных",
-                    None,
+                    Some(err.to_string()),
                 )
             }
             AppError::ServiceUnavailable => {
@@ -138,21 +216,59 @@ impl AppError {
                     None,
                 )
             }
+            AppError::PayloadTooLarge(msg) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "PayloadTooLarge", msg.as_str(), None)
+            }
+        };
+        
+        // Ошибки валидации дополнительно возвращаются по полям, не более MAX_FIELD_ERRORS
+        let (field_errors, field_errors_truncated) = match &self {
+            AppError::ValidationError(errors) => {
+                let limit = max_field_errors();
+                (Some(errors.iter().take(limit).cloned().collect::<Vec<_>>()), errors.len() > limit)
+            }
+            _ => (None, false),
+        };
+        // details содержит только включенные в ответ ошибки, а коды правил в него не попадают
+        let details = match &field_errors {
+            Some(errors) => Some(join_field_errors(errors)),
+            None => details,
+        };
+
+        // Для заблокированного аккаунта сообщаем время снятия блокировки
+        let locked_until = match &self {
+            AppError::AccountLocked(until) => Some(*until),
+            _ => None,
+        };
+
+        // Для деактивированного аккаунта в пределах окна восстановления подсказываем клиенту
+        // предложить восстановление
+        let reactivate_before = match &self {
+            AppError::ReactivationAvailable(until) => Some(*until),
+            _ => None,
         };
         
+        // Подробности ответов 5xx скрываются позже, в redact_server_error, по режиму ERROR_DETAIL
+        let code = self.code();
+
         // Создаем структуру ответа
         let error_response = ErrorResponse {
             status: status.as_u16(),
             error: error_type.to_string(),
+            code,
             message: message.to_string(),
             details: details.clone(),
             trace_id,
-            field_errors: None, // Здесь можно добавить ошибки полей при необходимости
-            timestamp: now,
+            field_errors,
+            field_errors_truncated,
+            locked_until: locked_until.map(|until| until.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+            reactivation_available: reactivate_before.is_some(),
+            reactivate_before: reactivate_before.map(|until| until.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+            timestamp: Some(now),
         };
         
         // Сериализуем в JSON
-        let body = match serde_json::to_string(&error_response) {
+        let body = match crate::utils::to_response_json(&error_response) {
             Ok(json) => Body::from(json),
             Err(e) => {
                 log::error!("Ошибка сериализации JSON: {}", e);
@@ -172,19 +288,121 @@ impl AppError {
             response.headers_mut().insert("X-Trace-ID", value);
         }
         
+        // Подсказываем клиенту, когда можно повторить вход
+        if let Some(until) = locked_until {
+            let retry_after = (until - chrono::Utc::now()).num_seconds().max(0);
+            response
+                .headers_mut()
+                .insert(hyper::header::RETRY_AFTER, HeaderValue::from(retry_after as u64));
+        }
+        
         response
     }
     
+    // Стабильный машиночитаемый код ошибки. В отличие от message не зависит от формулировки
+    // и языка, поэтому клиенты должны ветвиться по нему
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Unauthorized => "UNAUTHORIZED",
+            AppError::InvalidToken => "TOKEN_INVALID",
+            AppError::TokenExpired => "TOKEN_EXPIRED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::ValidationError(_) => "VALIDATION_FAILED",
+            AppError::NotAcceptable(_) => "NOT_ACCEPTABLE",
+            AppError::Conflict(msg) if msg == EMAIL_TAKEN_MESSAGE => "USER_EMAIL_TAKEN",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::AccountLocked(_) => "ACCOUNT_LOCKED",
+            AppError::ReactivationAvailable(_) => "REACTIVATION_AVAILABLE",
+            AppError::RateLimited => "RATE_LIMITED",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+            AppError::Database(_) => "DATABASE_ERROR",
+            AppError::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            AppError::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+        }
+    }
+    
+    // Конфликт из-за занятого email (при регистрации или смене email)
+    pub fn email_taken() -> Self {
+        AppError::Conflict(EMAIL_TAKEN_MESSAGE.to_string())
+    }
+    
     // Вспомогательный метод для создания ошибки валидации с несколькими полями
+    // (код правила не указывается)
     pub fn validation_errors(errors: Vec<(String, String)>) -> Self {
-        // Создаем строку с описанием всех ошибок
-        let message = errors
-            .iter()
-            .map(|(field, msg)| format!("{}: {}", field, msg))
-            .collect::<Vec<_>>()
-            .join("; ");
-        
-        AppError::ValidationError(message)
+        AppError::ValidationError(
+            errors
+                .into_iter()
+                .map(|(field, message)| FieldError { field, message, code: None })
+                .collect(),
+        )
+    }
+}
+
+// В режиме minimal (ERROR_DETAIL, по умолчанию) заменяет тело ответа 5xx: остаются статус, общее
+// сообщение и trace_id для поиска в логах, а details, тип ошибки БД и время не раскрываются.
+// Заголовки ответа сохраняются; остальные ответы возвращаются без изменений
+pub fn redact_server_error(mut response: Response<Body>, detail: ErrorDetail) -> Response<Body> {
+    let status = response.status();
+    if detail == ErrorDetail::Full || !status.is_server_error() {
+        return response;
+    }
+
+    let (error_type, code, message) = match status {
+        StatusCode::SERVICE_UNAVAILABLE => ("ServiceUnavailable", "SERVICE_UNAVAILABLE", "Сервис временно недоступен"),
+        _ => ("InternalServerError", "INTERNAL_ERROR", "Внутренняя ошибка сервера"),
+    };
+    let trace_id = response
+        .headers()
+        .get("X-Trace-ID")
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let error_response = ErrorResponse {
+        status: status.as_u16(),
+        error: error_type.to_string(),
+        code,
+        message: message.to_string(),
+        details: None,
+        trace_id,
+        field_errors: None,
+        field_errors_truncated: false,
+        locked_until: None,
+        reactivation_available: false,
+        reactivate_before: None,
+        timestamp: None,
+    };
+
+    let body = crate::utils::to_response_json(&error_response)
+        .unwrap_or_else(|_| r#"{"error":"InternalServerError"}"#.to_string());
+    response.headers_mut().remove(hyper::header::CONTENT_LENGTH);
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    *response.body_mut() = Body::from(body);
+    response
+}
+
+// Временная ошибка соединения с БД (сеть, перезапуск сервера, исчерпанный пул),
+// после которой повтор того же запроса может оказаться успешным
+pub fn is_transient_db_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        // Класс 08 — ошибки соединения, 57P01..57P03 — сервер останавливается или еще не готов
+        sqlx::Error::Database(db_err) => db_err
+            .code()
+            .is_some_and(|code| code.starts_with("08") || matches!(code.as_ref(), "57P01" | "57P02" | "57P03")),
+        _ => false,
+    }
+}
+
+// Конфликт сериализации (SQLSTATE 40001): транзакция откачена из-за параллельного изменения
+// и может быть повторена целиком
+pub fn is_serialization_failure(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Database(db_err) => db_err.code().is_some_and(|code| code == "40001"),
+        _ => false,
     }
 }
 
@@ -195,10 +413,23 @@ impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
         match err {
             sqlx::Error::RowNotFound => AppError::NotFound("Запись не найдена".to_string()),
+            // Пул исчерпан (истек DB_ACQUIRE_TIMEOUT_MS) или БД временно недоступна —
+            // клиент может повторить запрос позже
+            err if is_transient_db_error(&err) => AppError::ServiceUnavailable,
             sqlx::Error::Database(dberr) if dberr.constraint().is_some() => {
                 let constraint = dberr.constraint().unwrap_or("unknown");
-                if constraint.contains("email") {
-                    AppError::Conflict("Пользователь с таким email уже существует".to_string())
+                if dberr.is_check_violation() {
+                    // CHECK-ограничение (например, users_age_check) — ошибка входных данных, а не сервера
+                    let field = constraint
+                        .trim_start_matches("users_")
+                        .trim_end_matches("_check")
+                        .to_string();
+                    AppError::validation_errors(vec![(
+                        field,
+                        "Значение нарушает ограничение базы данных".to_string(),
+                    )])
+                } else if constraint.contains("email") {
+                    AppError::email_taken()
                 } else {
                     AppError::Database(sqlx::Error::Database(dberr))
                 }
@@ -213,7 +444,7 @@ impl From<jsonwebtoken::errors::Error> for AppError {
     fn from(err: jsonwebtoken::errors::Error) -> Self {
         use jsonwebtoken::errors::ErrorKind;
         match err.kind() {
-            ErrorKind::ExpiredSignature => AppError::InvalidToken,
+            ErrorKind::ExpiredSignature => AppError::TokenExpired,
             ErrorKind::InvalidToken => AppError::InvalidToken,
             _ => AppError::Unauthorized,
         }
@@ -248,14 +479,18 @@ impl From<validator::ValidationErrors> for AppError {
         
         for (field, errors) in err.field_errors() {
             if let Some(error) = errors.first() {
-                if let Some(message) = &error.message {
-                    field_errors.push((field.to_string(), message.to_string()));
-                } else {
-                    field_errors.push((field.to_string(), "Ошибка валидации".to_string()));
-                }
+                field_errors.push(FieldError {
+                    field: field.to_string(),
+                    message: error
+                        .message
+                        .as_ref()
+                        .map(|message| message.to_string())
+                        .unwrap_or_else(|| "Ошибка валидации".to_string()),
+                    code: Some(error.code.to_string()), // length, email, range...
+                });
             }
         }
         
-        AppError::validation_errors(field_errors)
+        AppError::ValidationError(field_errors)
     }
 }
\ No newline at end of file
@@ -0,0 +1,17 @@
+// Проверки middleware возвращают готовый ответ в Err (Result<(), Response<Body>>), поэтому
+// размер варианта Err не ограничиваем
+#![allow(clippy::result_large_err)]
+
+// Декларация модулей проекта
+pub mod clients;
+pub mod config;
+pub mod controllers;
+pub mod errors;
+pub mod metrics;
+pub mod middleware;
+pub mod models;
+pub mod repositories;
+pub mod routes;
+pub mod server;
+pub mod services;
+pub mod utils;
@@ -0,0 +1,273 @@
+// Модуль счетчиков для мониторинга аутентификации (экспортируются в /metrics)
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::models::ActiveSessionStats;
+use crate::utils::password_hash_limiter;
+
+// Причина неудачного входа (метка reason у auth_login_failure_total)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoginFailureReason {
+    BadPassword,
+    NotFound,
+    Locked,
+    Inactive,
+}
+
+impl LoginFailureReason {
+    const ALL: [LoginFailureReason; 4] = [
+        LoginFailureReason::BadPassword,
+        LoginFailureReason::NotFound,
+        LoginFailureReason::Locked,
+        LoginFailureReason::Inactive,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginFailureReason::BadPassword => "bad_password",
+            LoginFailureReason::NotFound => "not_found",
+            LoginFailureReason::Locked => "locked",
+            LoginFailureReason::Inactive => "inactive",
+        }
+    }
+}
+
+// Причина отклонения токена (метка reason у auth_token_rejected_total)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenRejectionReason {
+    Expired,
+    InvalidSignature,
+    Invalid, // Прочие ошибки: неверный формат, claims или отпечаток клиента
+}
+
+impl TokenRejectionReason {
+    const ALL: [TokenRejectionReason; 3] = [
+        TokenRejectionReason::Expired,
+        TokenRejectionReason::InvalidSignature,
+        TokenRejectionReason::Invalid,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenRejectionReason::Expired => "expired",
+            TokenRejectionReason::InvalidSignature => "invalid_signature",
+            TokenRejectionReason::Invalid => "invalid",
+        }
+    }
+}
+
+static LOGIN_SUCCESS: AtomicU64 = AtomicU64::new(0);
+static LOGIN_FAILURE: [AtomicU64; 4] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+static TOKEN_REJECTED: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+static AUDIT_EVENTS_LOST: AtomicU64 = AtomicU64::new(0);
+
+// Индекс счетчика в массиве совпадает с позицией причины в ALL
+fn login_failure_counter(reason: LoginFailureReason) -> &'static AtomicU64 {
+    let index = LoginFailureReason::ALL.iter().position(|r| *r == reason).unwrap_or(0);
+    &LOGIN_FAILURE[index]
+}
+
+fn token_rejected_counter(reason: TokenRejectionReason) -> &'static AtomicU64 {
+    let index = TokenRejectionReason::ALL.iter().position(|r| *r == reason).unwrap_or(0);
+    &TOKEN_REJECTED[index]
+}
+
+pub fn record_login_success() {
+    LOGIN_SUCCESS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_login_failure(reason: LoginFailureReason) {
+    login_failure_counter(reason).fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_token_rejected(reason: TokenRejectionReason) {
+    token_rejected_counter(reason).fetch_add(1, Ordering::Relaxed);
+    if reason == TokenRejectionReason::InvalidSignature {
+        signature_failure_detector().record(Instant::now());
+    }
+}
+
+pub fn record_audit_event_lost() {
+    AUDIT_EVENTS_LOST.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn login_success_count() -> u64 {
+    LOGIN_SUCCESS.load(Ordering::Relaxed)
+}
+
+pub fn login_failure_count(reason: LoginFailureReason) -> u64 {
+    login_failure_counter(reason).load(Ordering::Relaxed)
+}
+
+pub fn token_rejected_count(reason: TokenRejectionReason) -> u64 {
+    token_rejected_counter(reason).load(Ordering::Relaxed)
+}
+
+pub fn audit_events_lost_count() -> u64 {
+    AUDIT_EVENTS_LOST.load(Ordering::Relaxed)
+}
+
+// Окно подсчета ошибок подписи JWT для обнаружения всплеска
+const SIGNATURE_FAILURE_WINDOW: Duration = Duration::from_secs(60);
+
+// Порог ошибок подписи за окно по умолчанию
+const DEFAULT_SIGNATURE_FAILURE_SURGE_THRESHOLD: u64 = 50;
+
+// Обнаружение всплеска ошибок подписи: массовые InvalidSignature обычно означают, что JWT_SECRET
+// сменили и все ранее выданные токены стали недействительны. Счетчик в фиксированном окне;
+// при достижении порога пишется ошибка в лог (один раз за окно) и увеличивается счетчик всплесков
+pub struct SignatureFailureDetector {
+    threshold: u64,
+    window: Duration,
+    state: Mutex<SignatureFailureWindow>,
+    surges: AtomicU64,
+}
+
+struct SignatureFailureWindow {
+    started_at: Option<Instant>, // Начало текущего окна (None — ошибок еще не было)
+    failures: u64,               // Ошибок подписи в текущем окне
+    alerted: bool,               // Сообщено ли о всплеске в текущем окне
+}
+
+impl SignatureFailureDetector {
+    pub fn new(threshold: u64, window: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            state: Mutex::new(SignatureFailureWindow { started_at: None, failures: 0, alerted: false }),
+            surges: AtomicU64::new(0),
+        }
+    }
+
+    // Учитывает ошибку подписи. Возвращает true, если этой ошибкой в окне достигнут порог
+    pub fn record(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match state.started_at {
+            Some(started_at) if now.duration_since(started_at) < self.window => {}
+            _ => *state = SignatureFailureWindow { started_at: Some(now), failures: 0, alerted: false },
+        }
+        state.failures += 1;
+
+        if state.alerted || state.failures < self.threshold {
+            return false;
+        }
+        state.alerted = true;
+        self.surges.fetch_add(1, Ordering::Relaxed);
+        log::error!(
+            "Всплеск ошибок подписи JWT: {} за {} с. Возможно, JWT_SECRET сменили без поддержки нескольких ключей \
+             и все выданные токены недействительны",
+            state.failures,
+            self.window.as_secs()
+        );
+        true
+    }
+
+    // Ошибок подписи в текущем окне (0, если окно истекло)
+    pub fn failures_in_window(&self, now: Instant) -> u64 {
+        let state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match state.started_at {
+            Some(started_at) if now.duration_since(started_at) < self.window => state.failures,
+            _ => 0,
+        }
+    }
+
+    // Сколько раз обнаружен всплеск
+    pub fn surge_count(&self) -> u64 {
+        self.surges.load(Ordering::Relaxed)
+    }
+}
+
+// Общий детектор; порог — SIGNATURE_FAILURE_SURGE_THRESHOLD ошибок в минуту
+pub fn signature_failure_detector() -> &'static SignatureFailureDetector {
+    static DETECTOR: OnceLock<SignatureFailureDetector> = OnceLock::new();
+    DETECTOR.get_or_init(|| {
+        let threshold = env::var("SIGNATURE_FAILURE_SURGE_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(DEFAULT_SIGNATURE_FAILURE_SURGE_THRESHOLD);
+        SignatureFailureDetector::new(threshold, SIGNATURE_FAILURE_WINDOW)
+    })
+}
+
+// Активные сессии в текстовом формате Prometheus (gauge: значение читается из БД при сборе метрик)
+pub fn render_session_metrics(stats: &ActiveSessionStats) -> String {
+    let mut output = String::new();
+
+    output.push_str("# HELP active_sessions_total Активные сессии\n");
+    output.push_str("# TYPE active_sessions_total gauge\n");
+    output.push_str(&format!("active_sessions_total {}\n", stats.total));
+
+    output.push_str("# HELP active_sessions Активные сессии по ролям пользователей\n");
+    output.push_str("# TYPE active_sessions gauge\n");
+    for (role, count) in &stats.by_role {
+        output.push_str(&format!("active_sessions{{role=\"{}\"}} {}\n", role, count));
+    }
+
+    output
+}
+
+// Счетчики аутентификации в текстовом формате Prometheus
+pub fn render_auth_metrics() -> String {
+    let mut output = String::new();
+
+    output.push_str("# HELP auth_login_success_total Успешные входы\n");
+    output.push_str("# TYPE auth_login_success_total counter\n");
+    output.push_str(&format!("auth_login_success_total {}\n", login_success_count()));
+
+    output.push_str("# HELP auth_login_failure_total Неудачные входы по причинам\n");
+    output.push_str("# TYPE auth_login_failure_total counter\n");
+    for reason in LoginFailureReason::ALL {
+        output.push_str(&format!(
+            "auth_login_failure_total{{reason=\"{}\"}} {}\n",
+            reason.as_str(),
+            login_failure_count(reason)
+        ));
+    }
+
+    output.push_str("# HELP auth_token_rejected_total Отклоненные токены по причинам\n");
+    output.push_str("# TYPE auth_token_rejected_total counter\n");
+    for reason in TokenRejectionReason::ALL {
+        output.push_str(&format!(
+            "auth_token_rejected_total{{reason=\"{}\"}} {}\n",
+            reason.as_str(),
+            token_rejected_count(reason)
+        ));
+    }
+
+    let detector = signature_failure_detector();
+    output.push_str("# HELP auth_signature_failures_window Ошибки подписи JWT за последнее окно (минута)\n");
+    output.push_str("# TYPE auth_signature_failures_window gauge\n");
+    output.push_str(&format!(
+        "auth_signature_failures_window {}\n",
+        detector.failures_in_window(Instant::now())
+    ));
+
+    output.push_str("# HELP auth_signature_failure_surges_total Обнаруженные всплески ошибок подписи JWT\n");
+    output.push_str("# TYPE auth_signature_failure_surges_total counter\n");
+    output.push_str(&format!("auth_signature_failure_surges_total {}\n", detector.surge_count()));
+
+    let hashing = password_hash_limiter();
+    output.push_str("# HELP password_hash_in_flight Выполняемые сейчас хеширования и проверки паролей\n");
+    output.push_str("# TYPE password_hash_in_flight gauge\n");
+    output.push_str(&format!("password_hash_in_flight {}\n", hashing.in_flight()));
+
+    output.push_str("# HELP password_hash_rejected_total Хеширования паролей, отклоненные из-за переполнения очереди (503)\n");
+    output.push_str("# TYPE password_hash_rejected_total counter\n");
+    output.push_str(&format!("password_hash_rejected_total {}\n", hashing.rejected_count()));
+
+    output
+}
+
+// Счетчики журнала аудита в текстовом формате Prometheus
+pub fn render_audit_metrics() -> String {
+    let mut output = String::new();
+
+    output.push_str("# HELP audit_events_lost_total События аудита, не записанные ни фоновой записью, ни напрямую\n");
+    output.push_str("# TYPE audit_events_lost_total counter\n");
+    output.push_str(&format!("audit_events_lost_total {}\n", audit_events_lost_count()));
+
+    output
+}
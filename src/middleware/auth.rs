@@ -1,33 +1,78 @@
 use hyper::{Body, Request, Response, header};
-use jsonwebtoken::{DecodingKey, Validation, decode, Algorithm};
+use jsonwebtoken::{DecodingKey, Validation, decode};
 use sqlx::PgPool;
 use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::OnceLock;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 use crate::errors::AppError;
-use crate::models::{Claims, UserRole};
-
-// Тип для request_id в extensions
-type RequestIdKey = &'static str;
+use crate::metrics::{self, TokenRejectionReason};
+use crate::middleware::rate_limit::check_user_rate_limit;
+use crate::config::AppConfig;
+use crate::models::{Claims, UserRole, UserRoles};
+use crate::repositories::user::{find_user_role, list_additional_roles};
+use crate::services::session::ensure_session_active;
+use crate::routes::accepts_query_token;
+use crate::utils::{client_fingerprint, current_request_id, jwt_algorithm, jwt_secret};
 
 // Используем OnceLock для загрузки JWT ключа только один раз
 static DECODING_KEY: OnceLock<DecodingKey> = OnceLock::new();
 static JWT_VALIDATION: OnceLock<Validation> = OnceLock::new();
+static LEGACY_TOKEN_DEPRECATION: OnceLock<LegacyTokenDeprecation> = OnceLock::new();
 
-// Функция для получения ключа JWT, инициализируется при первом вызове
-fn get_jwt_key() -> &'static DecodingKey {
-    DECODING_KEY.get_or_init(|| {
-        let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET должен быть задан в .env");
-        DecodingKey::from_secret(jwt_secret.as_bytes())
-    })
+// Время последнего предупреждения об устаревшем заголовке (Unix timestamp)
+static LAST_LEGACY_TOKEN_WARNING: AtomicU64 = AtomicU64::new(0);
+
+// Не чаще одного предупреждения в минуту, чтобы не засорять логи
+const LEGACY_TOKEN_WARNING_INTERVAL_SECS: u64 = 60;
+
+// Имя cookie с токеном аутентификации
+pub const AUTH_COOKIE_NAME: &str = "auth_token";
+
+// Параметр строки запроса с токеном (для маршрутов из QUERY_TOKEN_ROUTES)
+pub const QUERY_TOKEN_PARAM: &str = "access_token";
+
+// Откуда был взят токен аутентификации
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TokenSource {
+    Bearer,
+    LegacyHeader,
+    Cookie,
+    QueryParam,
+}
+
+// Настройки вывода из эксплуатации заголовка X-User-Access-Token
+struct LegacyTokenDeprecation {
+    enabled: bool,          // Добавлять ли Deprecation/Sunset и писать предупреждения
+    sunset: Option<String>, // Дата отключения в формате HTTP-date (для заголовка Sunset)
+}
+
+// Загружает ключ проверки JWT при старте сервера из секрета конфигурации, которым подписываются
+// выдаваемые токены. JWT_SECRET не перезагружается, поэтому ключи подписи и проверки не расходятся
+pub fn init_jwt_key(secret: &str) {
+    DECODING_KEY.get_or_init(|| DecodingKey::from_secret(secret.as_bytes()));
+}
+
+// Функция для получения ключа JWT. Если ключ не загружен при старте (тесты), загружается
+// при первом вызове; при отсутствии JWT_SECRET возвращает ошибку вместо паники
+fn get_jwt_key() -> Result<&'static DecodingKey, String> {
+    if let Some(key) = DECODING_KEY.get() {
+        return Ok(key);
+    }
+    let jwt_secret = jwt_secret()?;
+    Ok(DECODING_KEY.get_or_init(|| DecodingKey::from_secret(jwt_secret.as_bytes())))
 }
 
 // Функция для получения настроек валидации JWT, инициализируется при первом вызове
 fn get_jwt_validation() -> &'static Validation {
     JWT_VALIDATION.get_or_init(|| {
-        let mut validation = Validation::new(Algorithm::HS256);
+        // Принимается ровно один алгоритм: токены с alg: none, RS256 и т.п. отклоняются
+        let mut validation = Validation::new(jwt_algorithm());
+
+        // Токен без срока действия не принимается
+        validation.set_required_spec_claims(&["exp"]);
         
         // Добавляем валидацию issuer, если задан
         if let Ok(issuer) = env::var("JWT_ISSUER") {
@@ -46,48 +91,158 @@ fn get_jwt_validation() -> &'static Validation {
     })
 }
 
-// Извлекает токен из различных мест в запросе
-fn extract_token(req: &Request<Body>) -> Option<String> {
-    // 1. Пытаемся получить из заголовка Authorization
-    if let Some(auth_header) = req.headers().get(header::AUTHORIZATION) {
+// Функция для получения настроек устаревания X-User-Access-Token, инициализируется при первом вызове
+fn get_legacy_token_deprecation() -> &'static LegacyTokenDeprecation {
+    LEGACY_TOKEN_DEPRECATION.get_or_init(|| {
+        let enabled = env::var("LEGACY_TOKEN_DEPRECATION")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true);
+        let sunset = env::var("LEGACY_TOKEN_SUNSET")
+            .ok()
+            .filter(|v| header::HeaderValue::from_str(v).is_ok());
+
+        LegacyTokenDeprecation { enabled, sunset }
+    })
+}
+
+// Токен из параметра access_token, если маршрут допускает токен в строке запроса
+fn query_token(req: &Request<Body>) -> Option<String> {
+    if !accepts_query_token(req.method(), req.uri().path()) {
+        return None;
+    }
+    if !AppConfig::from_request(req).ok()?.query_token_auth {
+        return None;
+    }
+    serde_urlencoded::from_str::<Vec<(String, String)>>(req.uri().query()?)
+        .ok()?
+        .into_iter()
+        .find(|(name, value)| name == QUERY_TOKEN_PARAM && !value.is_empty())
+        .map(|(_, value)| value)
+}
+
+// Убирает access_token из URI запроса, чтобы токен не попал в логи и обработчики
+fn strip_query_token(req: &mut Request<Body>) {
+    let Some(query) = req.uri().query() else {
+        return;
+    };
+    let remaining: Vec<&str> = query
+        .split('&')
+        .filter(|pair| pair.split('=').next() != Some(QUERY_TOKEN_PARAM))
+        .collect();
+    let path_and_query = if remaining.is_empty() {
+        req.uri().path().to_string()
+    } else {
+        format!("{}?{}", req.uri().path(), remaining.join("&"))
+    };
+
+    let mut parts = req.uri().clone().into_parts();
+    parts.path_and_query = path_and_query.parse().ok();
+    if let Ok(uri) = hyper::Uri::from_parts(parts) {
+        *req.uri_mut() = uri;
+    }
+}
+
+// Пишет предупреждение об устаревшем заголовке не чаще раза в интервал
+fn warn_legacy_token_usage(remote_addr: &str, request_id: Option<&str>) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs();
+    let last = LAST_LEGACY_TOKEN_WARNING.load(Ordering::Relaxed);
+
+    if now.saturating_sub(last) >= LEGACY_TOKEN_WARNING_INTERVAL_SECS
+        && LAST_LEGACY_TOKEN_WARNING
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    {
+        log::warn!(
+            "Аутентификация через устаревший заголовок X-User-Access-Token, используйте Authorization: Bearer [ip={}] [request_id={}]",
+            remote_addr,
+            request_id.unwrap_or("unknown")
+        );
+    }
+}
+
+// Извлекает токен из различных мест в запросе вместе с его источником.
+// Возвращает ошибку, если запрос содержит несколько разных токенов в заголовках
+fn extract_token(req: &Request<Body>) -> Result<Option<(String, TokenSource)>, AppError> {
+    // 1. Пытаемся получить из заголовка Authorization (повторы заголовка должны совпадать)
+    let mut bearer_token: Option<String> = None;
+    for auth_header in req.headers().get_all(header::AUTHORIZATION) {
         if let Ok(auth_str) = auth_header.to_str() {
-            if auth_str.starts_with("Bearer ") {
-                return Some(auth_str[7..].to_string());
+            if let Some(token) = auth_str.strip_prefix("Bearer ") {
+                match &bearer_token {
+                    Some(existing) if existing != token => {
+                        return Err(AppError::BadRequest(
+                            "Запрос содержит несколько разных заголовков Authorization".to_string(),
+                        ));
+                    }
+                    _ => bearer_token = Some(token.to_string()),
+                }
             }
         }
     }
     
     // 2. Пытаемся получить из кастомного заголовка (для обратной совместимости)
-    if let Some(token_header) = req.headers().get("X-User-Access-Token") {
-        if let Ok(token) = token_header.to_str() {
-            return Some(token.to_string());
+    let legacy_token = req
+        .headers()
+        .get("X-User-Access-Token")
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+
+    match (bearer_token, legacy_token) {
+        // Разные токены в двух заголовках — неоднозначная аутентификация, не выбираем произвольно
+        (Some(bearer), Some(legacy)) if bearer != legacy => {
+            return Err(AppError::BadRequest(
+                "Заголовки Authorization и X-User-Access-Token содержат разные токены".to_string(),
+            ));
         }
+        (Some(token), _) => return Ok(Some((token, TokenSource::Bearer))),
+        (None, Some(token)) => return Ok(Some((token, TokenSource::LegacyHeader))),
+        (None, None) => {}
     }
     
     // 3. Пытаемся получить из cookie (если используется)
     if let Some(cookie_header) = req.headers().get(header::COOKIE) {
         if let Ok(cookie_str) = cookie_header.to_str() {
             for cookie in cookie_str.split(';') {
-                if let Some(token_part) = cookie.trim().strip_prefix("auth_token=") {
-                    return Some(token_part.to_string());
+                let token_part = cookie
+                    .trim()
+                    .strip_prefix(AUTH_COOKIE_NAME)
+                    .and_then(|rest| rest.strip_prefix('='));
+                if let Some(token_part) = token_part {
+                    return Ok(Some((token_part.to_string(), TokenSource::Cookie)));
                 }
             }
         }
     }
+
+    // 4. Из строки запроса — только если включено и маршрут это допускает
+    if let Some(token) = query_token(req) {
+        return Ok(Some((token, TokenSource::QueryParam)));
+    }
     
-    None
+    Ok(None)
 }
 
-// Middleware для проверки JWT-токена
-pub async fn auth_middleware<F, Fut>(
-    mut req: Request<Body>,
-    pool: PgPool,
-    handler: F,
-) -> Result<Response<Body>, hyper::Error>
-where
-    F: Fn(Request<Body>, PgPool) -> Fut + Send + Sync + 'static,
-    Fut: std::future::Future<Output = Result<Response<Body>, hyper::Error>> + Send,
-{
+// Значение Set-Cookie для cookie-сессии: недоступно JavaScript (HttpOnly),
+// передается только по https и не отправляется с запросами с чужих сайтов
+pub fn auth_cookie(token: &str, max_age: i64) -> String {
+    format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; Secure; SameSite=Strict",
+        AUTH_COOKIE_NAME, token, max_age
+    )
+}
+
+// Отпечаток клиента текущего запроса (User-Agent и необязательный X-Device-ID)
+pub fn request_fingerprint(req: &Request<Body>) -> String {
+    let header_value = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+    client_fingerprint(header_value("User-Agent"), header_value("X-Device-ID"))
+}
+
+// Проверяет JWT-токен и добавляет user_id и роль в extensions запроса.
+// При ошибке возвращает готовый ответ с кодом 4xx
+pub(crate) fn authenticate(req: &mut Request<Body>) -> Result<TokenSource, Response<Body>> {
     // Получаем IP адрес для логирования
     let remote_addr = req
         .extensions()
@@ -96,29 +251,50 @@ where
         .unwrap_or_else(|| "unknown".to_string());
         
     // Получаем ID запроса для трассировки, если есть
-    let request_id = req
-        .headers()
-        .get("X-Request-ID")
-        .and_then(|v| v.to_str().ok())
-        .map(String::from);
+    let request_id = current_request_id(req);
     
     // Извлекаем токен из запроса
-    let token = match extract_token(&req) {
-        Some(token) => token,
-        None => {
+    let (token, token_source) = match extract_token(req) {
+        Ok(Some((token, TokenSource::QueryParam))) => {
+            strip_query_token(req);
+            (token, TokenSource::QueryParam)
+        }
+        Ok(Some(extracted)) => extracted,
+        Err(e) => {
+            log::warn!(
+                "Неоднозначные заголовки аутентификации [ip={}] [request_id={}]",
+                remote_addr,
+                request_id.as_deref().unwrap_or("unknown")
+            );
+            return Err(e.into_response(request_id.as_deref()));
+        }
+        Ok(None) => {
             log::warn!(
                 "Отсутствует токен аутентификации [ip={}] [request_id={}]",
                 remote_addr,
                 request_id.as_deref().unwrap_or("unknown")
             );
-            return Ok(AppError::Unauthorized.into_response(request_id.as_deref()));
+            return Err(AppError::Unauthorized.into_response(request_id.as_deref()));
+        }
+    };
+
+    // Без ключа токен проверить нельзя: это ошибка конфигурации сервера, а не клиента
+    let jwt_key = match get_jwt_key() {
+        Ok(key) => key,
+        Err(e) => {
+            log::error!(
+                "Не удалось загрузить ключ JWT: {} [request_id={}]",
+                e,
+                request_id.as_deref().unwrap_or("unknown")
+            );
+            return Err(AppError::Internal(anyhow::anyhow!(e)).into_response(request_id.as_deref()));
         }
     };
 
     // Проверяем JWT-токен
     let token_data = match decode::<Claims>(
         &token,
-        get_jwt_key(),
+        jwt_key,
         get_jwt_validation(),
     ) {
         Ok(token_data) => token_data,
@@ -132,7 +308,8 @@ where
                         remote_addr,
                         request_id.as_deref().unwrap_or("unknown")
                     );
-                    return Ok(AppError::InvalidToken.into_response(request_id.as_deref()));
+                    metrics::record_token_rejected(TokenRejectionReason::Expired);
+                    return Err(AppError::TokenExpired.into_response(request_id.as_deref()));
                 }
                 ErrorKind::InvalidSignature => {
                     log::warn!(
@@ -140,7 +317,8 @@ where
                         remote_addr,
                         request_id.as_deref().unwrap_or("unknown")
                     );
-                    return Ok(AppError::InvalidToken.into_response(request_id.as_deref()));
+                    metrics::record_token_rejected(TokenRejectionReason::InvalidSignature);
+                    return Err(AppError::InvalidToken.into_response(request_id.as_deref()));
                 }
                 _ => {
                     log::warn!(
@@ -149,7 +327,8 @@ where
                         remote_addr,
                         request_id.as_deref().unwrap_or("unknown")
                     );
-                    return Ok(AppError::InvalidToken.into_response(request_id.as_deref()));
+                    metrics::record_token_rejected(TokenRejectionReason::Invalid);
+                    return Err(AppError::InvalidToken.into_response(request_id.as_deref()));
                 }
             }
         }
@@ -170,7 +349,22 @@ where
             request_id.as_deref().unwrap_or("unknown"),
             claims.email
         );
-        return Ok(AppError::InvalidToken.into_response(request_id.as_deref()));
+        metrics::record_token_rejected(TokenRejectionReason::Expired);
+        return Err(AppError::TokenExpired.into_response(request_id.as_deref()));
+    }
+
+    // Токен, привязанный к клиенту при входе, принимается только от того же клиента
+    if let Some(expected) = &claims.fingerprint {
+        if *expected != request_fingerprint(req) {
+            log::warn!(
+                "Отпечаток клиента не совпадает с токеном [ip={}] [request_id={}] [user_email={}]",
+                remote_addr,
+                request_id.as_deref().unwrap_or("unknown"),
+                claims.email
+            );
+            metrics::record_token_rejected(TokenRejectionReason::Invalid);
+            return Err(AppError::InvalidToken.into_response(request_id.as_deref()));
+        }
     }
 
     // Извлекаем user_id из claims
@@ -182,19 +376,18 @@ where
                 remote_addr,
                 request_id.as_deref().unwrap_or("unknown")
             );
-            return Ok(AppError::InvalidToken.into_response(request_id.as_deref()));
+            metrics::record_token_rejected(TokenRejectionReason::Invalid);
+            return Err(AppError::InvalidToken.into_response(request_id.as_deref()));
         }
     };
 
     // Добавляем информацию в extensions запроса для использования в обработчиках
     req.extensions_mut().insert(user_id);
     req.extensions_mut().insert(claims.role);
+    req.extensions_mut().insert(claims.clone());
     
-    // Сохраняем request_id с явным типом
-    if let Some(id) = request_id {
-        req.extensions_mut().insert(("request_id", id));
-        
-        // Добавляем еще и в заголовки для корреляции
+    // ID запроса обработчики берут через current_request_id; для корреляции добавляем X-User-ID
+    if request_id.is_some() {
         req.headers_mut().insert(
             "X-User-ID", 
             header::HeaderValue::from_str(&user_id.to_string())
@@ -211,37 +404,206 @@ where
         claims.role
     );
 
-    // Передаём запрос дальше в обработчик
-    handler(req, pool).await
+    // Предупреждаем об устаревшем заголовке (ответ помечается в mark_token_source_response)
+    if token_source == TokenSource::LegacyHeader && get_legacy_token_deprecation().enabled {
+        warn_legacy_token_usage(&remote_addr, request_id.as_deref());
+    }
+
+    Ok(token_source)
 }
 
-// Middleware для проверки роли пользователя (используется после auth_middleware)
-pub async fn role_middleware<F, Fut>(
-    req: Request<Body>,
+// Проверяет, что серверная сессия токена (claim sid) не отозвана и не истекла.
+// Вызывается после authenticate, которой достаточно самого токена без обращения к БД
+pub(crate) async fn check_session(req: &Request<Body>, pool: &PgPool) -> Result<(), Response<Body>> {
+    let request_id = current_request_id(req);
+    let session_id = req.extensions().get::<Claims>().and_then(|claims| claims.sid);
+
+    match ensure_session_active(session_id, pool).await {
+        Ok(()) => Ok(()),
+        Err(AppError::InvalidToken) => {
+            log::warn!(
+                "Сессия токена отозвана или истекла [request_id={}] [session_id={:?}]",
+                request_id.as_deref().unwrap_or("unknown"),
+                session_id
+            );
+            metrics::record_token_rejected(TokenRejectionReason::Invalid);
+            Err(AppError::InvalidToken.into_response(request_id.as_deref()))
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при проверке сессии [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            Err(e.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Заменяет роль из токена в extensions текущей ролью из БД. Токен хранит роль на момент входа,
+// и после ее изменения администратором прежняя роль давала бы устаревшие права до истечения токена.
+// Вызывается перед проверкой роли; удаленный пользователь получает 401.
+// При FEATURE_MULTIPLE_ROLES в extensions кладутся и дополнительные роли из user_roles
+pub(crate) async fn load_current_role(req: &mut Request<Body>, pool: &PgPool) -> Result<(), Response<Body>> {
+    let request_id = current_request_id(req);
+    let Some(user_id) = req.extensions().get::<Uuid>().copied() else {
+        log::error!("user_id отсутствует в extensions, возможный баг в коде");
+        return Err(AppError::Unauthorized.into_response(request_id.as_deref()));
+    };
+
+    let role = match find_user_role(user_id, pool).await {
+        Ok(Some(role)) => role,
+        Ok(None) => {
+            log::warn!(
+                "Пользователь токена не найден [request_id={}] [user_id={}]",
+                request_id.as_deref().unwrap_or("unknown"),
+                user_id
+            );
+            metrics::record_token_rejected(TokenRejectionReason::Invalid);
+            return Err(AppError::InvalidToken.into_response(request_id.as_deref()));
+        }
+        Err(e) => {
+            log::error!(
+                "Ошибка при получении роли пользователя [request_id={}]: {:?}",
+                request_id.as_deref().unwrap_or("unknown"),
+                e
+            );
+            return Err(e.into_response(request_id.as_deref()));
+        }
+    };
+
+    if req.extensions().get::<UserRole>() != Some(&role) {
+        log::info!(
+            "Роль в токене устарела, используется роль из БД [request_id={}] [user_id={}] [роль={:?}]",
+            request_id.as_deref().unwrap_or("unknown"),
+            user_id,
+            role
+        );
+    }
+    req.extensions_mut().insert(role);
+
+    let config = AppConfig::from_request(req).map_err(|e| e.into_response(request_id.as_deref()))?;
+    let mut roles = vec![role];
+    if config.features.multiple_roles {
+        match list_additional_roles(user_id, pool).await {
+            Ok(additional) => roles.extend(additional.into_iter().filter(|extra| *extra != role)),
+            Err(e) => {
+                log::error!(
+                    "Ошибка при получении дополнительных ролей [request_id={}]: {:?}",
+                    request_id.as_deref().unwrap_or("unknown"),
+                    e
+                );
+                return Err(e.into_response(request_id.as_deref()));
+            }
+        }
+    }
+    req.extensions_mut().insert(UserRoles(roles));
+
+    Ok(())
+}
+
+// Помечает ответ в зависимости от источника токена: Deprecation/Sunset для X-User-Access-Token,
+// Referrer-Policy: no-referrer для токена из строки запроса (чтобы URL с токеном не ушел
+// в Referer при переходе со страницы ответа)
+pub(crate) fn mark_token_source_response(token_source: TokenSource, response: &mut Response<Body>) {
+    if token_source == TokenSource::QueryParam {
+        response
+            .headers_mut()
+            .insert(header::REFERRER_POLICY, header::HeaderValue::from_static("no-referrer"));
+        return;
+    }
+
+    let deprecation = get_legacy_token_deprecation();
+    if token_source != TokenSource::LegacyHeader || !deprecation.enabled {
+        return;
+    }
+
+    let headers = response.headers_mut();
+    headers.insert("Deprecation", header::HeaderValue::from_static("true"));
+    if let Some(sunset) = &deprecation.sunset {
+        if let Ok(value) = header::HeaderValue::from_str(sunset) {
+            headers.insert("Sunset", value);
+        }
+    }
+}
+
+// Middleware для проверки JWT-токена
+pub async fn auth_middleware<F, Fut>(
+    mut req: Request<Body>,
     pool: PgPool,
-    required_role: UserRole,
     handler: F,
 ) -> Result<Response<Body>, hyper::Error>
 where
     F: Fn(Request<Body>, PgPool) -> Fut + Send + Sync + 'static,
     Fut: std::future::Future<Output = Result<Response<Body>, hyper::Error>> + Send,
 {
+    let token_source = match authenticate(&mut req) {
+        Ok(token_source) => token_source,
+        Err(response) => return Ok(response),
+    };
+    if let Err(response) = check_session(&req, &pool).await {
+        return Ok(response);
+    }
+    if let Err(response) = check_user_rate_limit(&req) {
+        return Ok(response);
+    }
+
+    // Передаём запрос дальше в обработчик
+    let mut response = handler(req, pool).await?;
+    mark_token_source_response(token_source, &mut response);
+
+    Ok(response)
+}
+
+// Проверяет доступ к /metrics: если токен метрик задан, требуется Authorization: Bearer <token>
+pub fn authorize_metrics(req: &Request<Body>, metrics_token: Option<&str>) -> Result<(), AppError> {
+    let Some(expected) = metrics_token else {
+        return Ok(());
+    };
+
+    let provided = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    // Сравнение без раннего выхода, чтобы не раскрывать токен через время ответа
+    let matches = provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0;
+
+    if matches {
+        Ok(())
+    } else {
+        Err(AppError::Unauthorized)
+    }
+}
+
+// Проверяет, что роли пользователя из extensions достаточно (администратор имеет все права).
+// Если загружены все роли пользователя (load_current_role), достаточно любой из них.
+// При нехватке прав возвращает готовый ответ с кодом 4xx
+pub(crate) fn authorize_role(req: &Request<Body>, required_role: UserRole) -> Result<(), Response<Body>> {
     // Получаем роль пользователя из extensions (добавлена auth_middleware)
     let user_role = match req.extensions().get::<UserRole>() {
         Some(role) => *role,
         None => {
             // Это не должно произойти, если auth_middleware был вызван перед этим middleware
             log::error!("Роль пользователя отсутствует в extensions, возможный баг в коде");
-            return Ok(AppError::Unauthorized.into_response(None));
+            return Err(AppError::Unauthorized.into_response(None));
         }
     };
     
     // Проверяем достаточность прав (администратор имеет все права)
-    if user_role != required_role && user_role != UserRole::Admin {
-        let request_id = req
-            .headers()
-            .get("X-Request-ID")
-            .and_then(|v| v.to_str().ok());
+    let authorized = match req.extensions().get::<UserRoles>() {
+        Some(roles) => roles.satisfies(required_role),
+        None => user_role == required_role || user_role == UserRole::Admin,
+    };
+    if !authorized {
+        let request_id = current_request_id(req);
             
         let user_id = req
             .extensions()
@@ -251,18 +613,40 @@ where
             
         log::warn!(
             "Доступ запрещен: недостаточно прав [request_id={}] [user_id={}] [роль={:?}, требуется={:?}]",
-            request_id.unwrap_or("unknown"),
+            request_id.as_deref().unwrap_or("unknown"),
             user_id,
             user_role,
             required_role
         );
         
-        return Ok(AppError::Forbidden(format!(
+        return Err(AppError::Forbidden(format!(
             "Недостаточно прав для этой операции. Требуется роль: {:?}", 
             required_role
-        )).into_response(request_id));
+        )).into_response(request_id.as_deref()));
+    }
+
+    Ok(())
+}
+
+// Middleware для проверки роли пользователя (используется после auth_middleware).
+// Роль берется из БД, а не из токена
+pub async fn role_middleware<F, Fut>(
+    mut req: Request<Body>,
+    pool: PgPool,
+    required_role: UserRole,
+    handler: F,
+) -> Result<Response<Body>, hyper::Error>
+where
+    F: Fn(Request<Body>, PgPool) -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = Result<Response<Body>, hyper::Error>> + Send,
+{
+    if let Err(response) = load_current_role(&mut req, &pool).await {
+        return Ok(response);
+    }
+    if let Err(response) = authorize_role(&req, required_role) {
+        return Ok(response);
     }
 
     // Передаём запрос дальше в обработчик
     handler(req, pool).await
-}
\ No newline at end of file
+}
@@ -0,0 +1,56 @@
+use hyper::header::{self, HeaderMap, HeaderValue};
+
+use crate::config::AppConfig;
+use crate::routes::allowed_methods;
+
+// Методы для путей, неизвестных маршрутизатору
+const DEFAULT_ALLOW_METHODS: &str = "GET, POST, PATCH, DELETE, OPTIONS";
+
+// Политика CORS для маршрута
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CorsPolicy {
+    Global,   // Действуют общие настройки CORS_ORIGINS
+    Disabled, // CORS-заголовки не добавляются: браузер не отдаст ответ другому домену
+}
+
+// Политика для пути по списку CORS_DISABLED_PATHS. Элемент, оканчивающийся на '*',
+// задает префикс пути (например, /internal/*), остальные сравниваются целиком
+pub fn route_cors_policy(path: &str, disabled_paths: &[String]) -> CorsPolicy {
+    let disabled = disabled_paths.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => path.starts_with(prefix),
+        None => path == pattern,
+    });
+
+    if disabled {
+        CorsPolicy::Disabled
+    } else {
+        CorsPolicy::Global
+    }
+}
+
+// Добавляет CORS-заголовки к ответу по политике маршрута и Origin запроса
+pub fn apply_cors_headers(headers: &mut HeaderMap, config: &AppConfig, path: &str, origin: Option<&str>) {
+    if route_cors_policy(path, &config.cors_disabled_paths) == CorsPolicy::Disabled {
+        return;
+    }
+
+    // Пустой список доменов — кросс-доменные запросы запрещены
+    if let Some(cors_value) = config.cors_allow_origin(origin) {
+        if let Ok(value) = HeaderValue::from_str(&cors_value) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+        }
+    }
+
+    // Для известного пути перечисляются только методы, которые он обрабатывает
+    let methods = allowed_methods(path)
+        .map(|methods| methods.join(", "))
+        .unwrap_or_else(|| DEFAULT_ALLOW_METHODS.to_string());
+    if let Ok(value) = HeaderValue::from_str(&methods) {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, value);
+    }
+
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static("Content-Type, Authorization, X-User-Access-Token, Accept, X-Requested-With"),
+    );
+}
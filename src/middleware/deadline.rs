@@ -0,0 +1,63 @@
+use hyper::{Body, Method, Response, StatusCode};
+use std::future::Future;
+use std::time::Duration;
+
+use crate::errors::AppError;
+
+// Мягкий бюджет времени ответа: если обработчик не вернул ответ за deadline, запрос
+// прерывается с 503, не дожидаясь жесткого таймаута (408). Ответ, который уже начал
+// отправляться (обработчик вернул Response), не прерывается. None — бюджет не ограничен
+pub async fn with_soft_deadline<Fut>(
+    handler: Fut,
+    deadline: Option<Duration>,
+    request_id: Option<String>,
+) -> Result<Response<Body>, hyper::Error>
+where
+    Fut: Future<Output = Result<Response<Body>, hyper::Error>>,
+{
+    let Some(deadline) = deadline else {
+        return handler.await;
+    };
+
+    match tokio::time::timeout(deadline, handler).await {
+        Ok(result) => result,
+        Err(_) => {
+            log::warn!(
+                "Запрос превысил мягкий бюджет времени {} мс и был прерван [request_id={}]",
+                deadline.as_millis(),
+                request_id.as_deref().unwrap_or("unknown")
+            );
+            Ok(AppError::ServiceUnavailable.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Пишет WARN о запросе, обработка которого заняла больше порога SLOW_REQUEST_MS (None — не логируется).
+// Маршрут передается шаблоном, чтобы идентификаторы из пути не попадали в лог.
+// Возвращает, был ли запрос признан медленным
+pub fn log_slow_request(
+    method: &Method,
+    route: &str,
+    status: StatusCode,
+    elapsed: Duration,
+    threshold: Option<Duration>,
+    request_id: Option<&str>,
+) -> bool {
+    let Some(threshold) = threshold else {
+        return false;
+    };
+    if elapsed <= threshold {
+        return false;
+    }
+
+    log::warn!(
+        "Медленный запрос: {} {} — статус {}, {} мс (порог {} мс) [request_id={}]",
+        method,
+        route,
+        status.as_u16(),
+        elapsed.as_millis(),
+        threshold.as_millis(),
+        request_id.unwrap_or("unknown")
+    );
+    true
+}
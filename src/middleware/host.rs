@@ -0,0 +1,111 @@
+use hyper::body::HttpBody;
+use hyper::header::{CONTENT_LENGTH, HOST, TRANSFER_ENCODING};
+use hyper::{Body, Method, Request, Response, Version};
+
+use crate::errors::AppError;
+use crate::utils::current_request_id;
+
+// Хост запроса без порта в нижнем регистре: из заголовка Host, а для HTTP/2 — из authority URI
+fn request_host(req: &Request<Body>) -> Result<Option<String>, &'static str> {
+    let mut hosts = req.headers().get_all(HOST).iter();
+    let host = match (hosts.next(), hosts.next()) {
+        (Some(_), Some(_)) => return Err("Заголовок Host указан несколько раз"),
+        (Some(value), None) => Some(value.to_str().map_err(|_| "Некорректный заголовок Host")?),
+        (None, _) => req.uri().authority().map(|authority| authority.as_str()),
+    };
+
+    let Some(host) = host else {
+        return Ok(None);
+    };
+    let authority: hyper::http::uri::Authority = host.parse().map_err(|_| "Некорректный заголовок Host")?;
+    Ok(Some(authority.host().to_ascii_lowercase()))
+}
+
+// Строгая проверка запроса до маршрутизации: поддерживаются только HTTP/1.0, HTTP/1.1 и HTTP/2,
+// для HTTP/1.1 и HTTP/2 обязателен хост, и если задан ALLOWED_HOSTS, хост должен входить
+// в этот список (защита от подмены Host в ссылках и переадресациях). Иначе — готовый ответ 400
+pub fn validate_request_target(req: &Request<Body>, allowed_hosts: &[String]) -> Result<(), Response<Body>> {
+    let request_id = current_request_id(req);
+    let reject = |reason: String| {
+        log::warn!("Отклонен некорректный запрос: {} [request_id={}]", reason, request_id.as_deref().unwrap_or("unknown"));
+        Err(AppError::BadRequest(reason).into_response(request_id.as_deref()))
+    };
+
+    let version = req.version();
+    if !matches!(version, Version::HTTP_10 | Version::HTTP_11 | Version::HTTP_2) {
+        return reject(format!("Неподдерживаемая версия протокола: {:?}", version));
+    }
+
+    let host = match request_host(req) {
+        Ok(host) => host,
+        Err(reason) => return reject(reason.to_string()),
+    };
+
+    match host {
+        None if version != Version::HTTP_10 => reject("Отсутствует заголовок Host".to_string()),
+        Some(host) if !allowed_hosts.is_empty() && !allowed_hosts.contains(&host) => {
+            reject(format!("Хост {} не разрешен", host))
+        }
+        _ => Ok(()),
+    }
+}
+
+// GET и HEAD не должны нести тело: такой запрос почти всегда ошибка клиента или попытка атаки
+// (например, рассинхронизации запросов за прокси). Тело не читается, запрос отклоняется с 400
+pub fn reject_body_on_get(req: &Request<Body>) -> Result<(), Response<Body>> {
+    if !matches!(*req.method(), Method::GET | Method::HEAD) || req.body().is_end_stream() {
+        return Ok(());
+    }
+
+    let request_id = current_request_id(req);
+    log::warn!(
+        "Отклонен запрос {} {} с телом [request_id={}]",
+        req.method(),
+        req.uri().path(),
+        request_id.as_deref().unwrap_or("unknown")
+    );
+    Err(AppError::BadRequest(format!("Запрос {} не должен содержать тело", req.method())).into_response(request_id.as_deref()))
+}
+
+// Content-Length больше лимита класса маршрута — 413 до чтения тела. Тела без Content-Length
+// (chunked) ограничиваются при чтении (см. utils::read_body_limited)
+pub fn reject_oversized_body(req: &Request<Body>, limit: usize) -> Result<(), Response<Body>> {
+    let content_length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(0);
+    if content_length <= limit as u64 {
+        return Ok(());
+    }
+
+    let request_id = current_request_id(req);
+    log::warn!(
+        "Отклонен запрос {} {} с телом {} байт при лимите {} [request_id={}]",
+        req.method(),
+        req.uri().path(),
+        content_length,
+        limit,
+        request_id.as_deref().unwrap_or("unknown")
+    );
+    Err(AppError::PayloadTooLarge(format!("Тело запроса больше {} байт", limit)).into_response(request_id.as_deref()))
+}
+
+// Запрос одновременно с Content-Length и Transfer-Encoding допускает разное понимание границ тела
+// прокси и сервером (request smuggling), поэтому по RFC 9112 (6.3) он отклоняется с 400, не читая тело
+pub fn reject_conflicting_framing(req: &Request<Body>) -> Result<(), Response<Body>> {
+    if !req.headers().contains_key(CONTENT_LENGTH) || !req.headers().contains_key(TRANSFER_ENCODING) {
+        return Ok(());
+    }
+
+    let request_id = current_request_id(req);
+    log::warn!(
+        "Отклонен запрос {} {} с Content-Length и Transfer-Encoding одновременно [request_id={}]",
+        req.method(),
+        req.uri().path(),
+        request_id.as_deref().unwrap_or("unknown")
+    );
+    Err(AppError::BadRequest("Заголовки Content-Length и Transfer-Encoding не могут быть указаны вместе".to_string())
+        .into_response(request_id.as_deref()))
+}
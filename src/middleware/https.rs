@@ -0,0 +1,33 @@
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Request, Response, StatusCode};
+use std::net::IpAddr;
+
+use crate::middleware::proxy::{forwarded_header, request_scheme};
+
+// Пробы балансировщика и сбор метрик обычно идут по http напрямую к экземпляру
+// и не следуют переадресации, поэтому FORCE_HTTPS их не затрагивает
+const HTTPS_EXEMPT_PATHS: &[&str] = &["/health", "/metrics"];
+
+// Ответ 308 с переадресацией на https, если исходный запрос пришел по http.
+// Используется при FORCE_HTTPS; 308 (а не 301) сохраняет метод и тело запроса
+pub fn https_redirect(req: &Request<Body>, trusted_proxies: &[IpAddr]) -> Option<Response<Body>> {
+    if HTTPS_EXEMPT_PATHS.contains(&req.uri().path()) || request_scheme(req, trusted_proxies) != "http" {
+        return None;
+    }
+
+    // Без Host адрес переадресации не построить — обрабатываем запрос как обычно
+    let host = forwarded_header(req, "X-Forwarded-Host", trusted_proxies)
+        .or_else(|| req.headers().get(header::HOST).and_then(|v| v.to_str().ok()))?;
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+
+    let location = HeaderValue::from_str(&format!("https://{}{}", host, path_and_query)).ok()?;
+
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::PERMANENT_REDIRECT;
+    response.headers_mut().insert(header::LOCATION, location);
+    Some(response)
+}
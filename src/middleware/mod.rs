@@ -3,3 +3,129 @@
 // Объявляем подмодуль auth, содержащий middleware для проверки JWT-токенов
 pub mod auth;
 
+// Объявляем подмодуль security, добавляющий защитные HTTP-заголовки к ответам
+pub mod security;
+
+// Объявляем подмодуль panic, превращающий панику обработчика в ответ 500
+pub mod panic;
+
+// Объявляем подмодуль proxy для чтения X-Forwarded-* только от доверенных прокси
+pub mod proxy;
+
+// Объявляем подмодуль https, переадресующий http-запросы на https (FORCE_HTTPS)
+pub mod https;
+
+// Объявляем подмодуль negotiation для согласования версии API по заголовку Accept
+pub mod negotiation;
+
+// Объявляем подмодуль rate_limit, ограничивающий число запросов с одного IP клиента и пользователя
+pub mod rate_limit;
+
+// Объявляем подмодуль deadline, прерывающий запросы сверх мягкого бюджета времени ответа
+pub mod deadline;
+
+// Объявляем подмодуль cors, добавляющий CORS-заголовки с учетом политики маршрута
+pub mod cors;
+
+// Объявляем подмодуль response_limit, ограничивающий размер буферизованного тела ответа
+pub mod response_limit;
+
+// Объявляем подмодуль host, отклоняющий запросы с неподдерживаемой версией HTTP или чужим Host
+pub mod host;
+
+use hyper::{Body, Request, Response};
+use sqlx::PgPool;
+use std::future::Future;
+
+use crate::models::UserRole;
+
+// Цепочка проверок для защищенных маршрутов:
+// chain().auth().role(UserRole::Admin).handle(req, pool, handler)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chain {
+    auth: bool,                 // Требуется JWT-аутентификация
+    current_role: bool,         // Роль из токена заменяется текущей ролью из БД (подразумевает аутентификацию)
+    role: Option<UserRole>,     // Требуемая роль (подразумевает загрузку текущей роли)
+}
+
+// Создает пустую цепочку (без проверок запрос сразу передается обработчику)
+pub fn chain() -> Chain {
+    Chain::default()
+}
+
+impl Chain {
+    // Добавляет проверку JWT-токена
+    pub fn auth(mut self) -> Self {
+        self.auth = true;
+        self
+    }
+
+    // Загружает текущую роль из БД без требования конкретной роли: для обработчиков,
+    // которые сами решают по роли, что доступно пользователю
+    pub fn current_role(mut self) -> Self {
+        self.auth = true;
+        self.current_role = true;
+        self
+    }
+
+    // Добавляет проверку роли (администратор проходит любую проверку роли)
+    pub fn role(mut self, required_role: UserRole) -> Self {
+        self.auth = true;
+        self.current_role = true;
+        self.role = Some(required_role);
+        self
+    }
+
+    // Выполняет проверки по порядку и передает запрос обработчику.
+    // Первая неудачная проверка возвращает свой ответ, обработчик не вызывается
+    pub async fn handle<F, Fut>(
+        self,
+        mut req: Request<Body>,
+        pool: PgPool,
+        handler: F,
+    ) -> Result<Response<Body>, hyper::Error>
+    where
+        F: FnOnce(Request<Body>, PgPool) -> Fut,
+        Fut: Future<Output = Result<Response<Body>, hyper::Error>>,
+    {
+        let token_source = if self.auth {
+            match auth::authenticate(&mut req) {
+                Ok(token_source) => {
+                    if let Err(response) = auth::check_session(&req, &pool).await {
+                        return Ok(response);
+                    }
+                    Some(token_source)
+                }
+                Err(response) => return Ok(response),
+            }
+        } else {
+            None
+        };
+
+        // Роль из токена могла устареть: проверяется текущая роль из БД
+        if self.current_role {
+            if let Err(response) = auth::load_current_role(&mut req, &pool).await {
+                return Ok(response);
+            }
+        }
+        if let Some(required_role) = self.role {
+            if let Err(response) = auth::authorize_role(&req, required_role) {
+                return Ok(response);
+            }
+        }
+
+        // Лимит пользователя зависит от роли, поэтому проверяется после загрузки текущей роли
+        if self.auth {
+            if let Err(response) = rate_limit::check_user_rate_limit(&req) {
+                return Ok(response);
+            }
+        }
+
+        let mut response = handler(req, pool).await?;
+        if let Some(token_source) = token_source {
+            auth::mark_token_source_response(token_source, &mut response);
+        }
+
+        Ok(response)
+    }
+}
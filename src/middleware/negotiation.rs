@@ -0,0 +1,53 @@
+use hyper::header::ACCEPT;
+use hyper::{Body, Request, Response};
+
+use crate::errors::AppError;
+use crate::utils::current_request_id;
+
+// Номер версии API из пути вида "/api/v2/..." (None — путь не относится к версионированному API)
+pub fn api_version(path: &str) -> Option<u32> {
+    let rest = path.strip_prefix("/api/v")?;
+    let version = rest.split('/').next()?;
+    version.parse().ok()
+}
+
+// Тип содержимого, который клиент указывает в Accept для выбранной версии API
+pub fn version_media_type(version: u32) -> String {
+    format!("application/vnd.webapi.v{}+json", version)
+}
+
+// Перечислен ли тип в заголовке Accept (параметры вроде ";q=0.9" не учитываются)
+fn accepts_media_type(req: &Request<Body>, media_type: &str) -> bool {
+    req.headers()
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| item.split(';').next())
+        .any(|item| item.trim().eq_ignore_ascii_case(media_type))
+}
+
+// Согласование версии по заголовку Accept: маршруты /api/v2 и старше требуют
+// Accept: application/vnd.webapi.vN+json, v1 принимает запросы без него.
+// При несовпадении возвращает готовый ответ 406
+pub fn negotiate_api_version(req: &Request<Body>) -> Result<(), Response<Body>> {
+    let version = match api_version(req.uri().path()) {
+        Some(version) if version >= 2 => version,
+        _ => return Ok(()),
+    };
+
+    let media_type = version_media_type(version);
+    if accepts_media_type(req, &media_type) {
+        return Ok(());
+    }
+
+    let request_id = current_request_id(req);
+    log::debug!(
+        "Отклонен запрос к API v{} без Accept: {} [request_id={}]",
+        version,
+        media_type,
+        request_id.as_deref().unwrap_or("unknown")
+    );
+    Err(AppError::NotAcceptable(format!("Для этой версии API требуется заголовок Accept: {}", media_type))
+        .into_response(request_id.as_deref()))
+}
@@ -0,0 +1,40 @@
+use futures_util::FutureExt;
+use hyper::{Body, Response};
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+
+use crate::errors::AppError;
+
+// Граница паники: паника в обработчике превращается в ответ 500 вместо обрыва соединения.
+// Работает только при panic = "unwind" (значение по умолчанию для профилей сборки)
+pub async fn catch_panic<Fut>(
+    handler: Fut,
+    request_id: Option<String>,
+) -> Result<Response<Body>, hyper::Error>
+where
+    Fut: Future<Output = Result<Response<Body>, hyper::Error>>,
+{
+    match AssertUnwindSafe(handler).catch_unwind().await {
+        Ok(result) => result,
+        Err(panic) => {
+            // Internal логирует ошибку вместе с trace_id ответа
+            let error = AppError::Internal(anyhow::anyhow!(
+                "Паника в обработчике запроса: {}",
+                panic_message(panic.as_ref())
+            ));
+            Ok(error.into_response(request_id.as_deref()))
+        }
+    }
+}
+
+// Извлекает текст паники (panic! принимает &str или String)
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "неизвестная причина"
+    }
+}
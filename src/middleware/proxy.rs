@@ -0,0 +1,73 @@
+use hyper::{Body, Request};
+use std::net::{IpAddr, SocketAddr};
+
+// Запрос пришел от доверенного прокси (адрес соединения указан в TRUSTED_PROXIES).
+// Только таким прокси можно верить в заголовках X-Forwarded-*
+pub fn is_trusted_proxy(req: &Request<Body>, trusted_proxies: &[IpAddr]) -> bool {
+    req.extensions()
+        .get::<SocketAddr>()
+        .map(|addr| trusted_proxies.contains(&addr.ip()))
+        .unwrap_or(false)
+}
+
+// Значения заголовка X-Forwarded-* по порядку добавления (все строки заголовка, слева направо)
+fn forwarded_values<'a>(req: &'a Request<Body>, name: &str) -> Vec<&'a str> {
+    req.headers()
+        .get_all(name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+// Адрес клиента из X-Forwarded-For и его позиция, считая справа. Каждый прокси дописывает адрес
+// отправителя в конец, поэтому цепочка проверяется справа налево: первый адрес не из
+// TRUSTED_PROXIES записан доверенным прокси и принадлежит клиенту. Записи левее прислал
+// сам клиент, им верить нельзя. Нечитаемая запись обрывает цепочку (None)
+fn forwarded_client(req: &Request<Body>, trusted_proxies: &[IpAddr]) -> Option<(usize, IpAddr)> {
+    if !is_trusted_proxy(req, trusted_proxies) {
+        return None;
+    }
+
+    let mut client = None;
+    for (hop, value) in forwarded_values(req, "X-Forwarded-For").into_iter().rev().enumerate() {
+        let ip = value.parse::<IpAddr>().ok()?;
+        client = Some((hop, ip));
+        if !trusted_proxies.contains(&ip) {
+            break;
+        }
+    }
+    client
+}
+
+// Значение заголовка X-Forwarded-* от доверенного прокси, добавленное тем же прокси, что
+// и адрес клиента в X-Forwarded-For (на той же позиции справа). Если прокси заменяют
+// заголовок, а не дописывают, берется самое левое из имеющихся значений
+pub fn forwarded_header<'a>(req: &'a Request<Body>, name: &str, trusted_proxies: &[IpAddr]) -> Option<&'a str> {
+    if !is_trusted_proxy(req, trusted_proxies) {
+        return None;
+    }
+
+    let values = forwarded_values(req, name);
+    let hop = forwarded_client(req, trusted_proxies).map_or(0, |(hop, _)| hop);
+    values.iter().rev().nth(hop).or(values.first()).copied()
+}
+
+// Схема исходного запроса клиента. Сервер сам принимает только HTTP, поэтому без
+// доверенного X-Forwarded-Proto считаем схему http
+pub fn request_scheme(req: &Request<Body>, trusted_proxies: &[IpAddr]) -> String {
+    forwarded_header(req, "X-Forwarded-Proto", trusted_proxies)
+        .map(|proto| proto.to_ascii_lowercase())
+        .unwrap_or_else(|| "http".to_string())
+}
+
+// IP-адрес клиента: от доверенного прокси — крайний справа адрес из X-Forwarded-For, не
+// принадлежащий доверенному прокси, иначе — адрес соединения (заголовок от недоверенного
+// адреса мог быть подделан)
+pub fn client_ip(req: &Request<Body>, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    forwarded_client(req, trusted_proxies)
+        .map(|(_, ip)| ip)
+        .or_else(|| req.extensions().get::<SocketAddr>().map(|addr| addr.ip()))
+}
@@ -0,0 +1,285 @@
+use hyper::header::{HeaderValue, RETRY_AFTER};
+use hyper::{Body, Request, Response};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::config::{AppConfig, LoginThrottleLimits, RoleRateLimits};
+use crate::errors::AppError;
+use crate::metrics::LoginFailureReason;
+use crate::middleware::proxy::client_ip;
+use crate::models::UserRole;
+use crate::utils::current_request_id;
+
+// Число клиентов, после которого из таблицы удаляются окна с истекшим сроком
+const PRUNE_THRESHOLD: usize = 10_000;
+
+// Счетчики запросов в фиксированных окнах для произвольного ключа (IP или ID пользователя)
+struct FixedWindows<K> {
+    window: Duration,                           // Длина окна
+    buckets: Mutex<HashMap<K, (Instant, u32)>>, // Начало окна и число запросов для каждого ключа
+}
+
+impl<K: Hash + Eq> FixedWindows<K> {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Учитывает запрос по ключу. При превышении лимита возвращает число секунд до нового окна
+    fn hit(&self, key: K, limit: u32) -> Result<(), u64> {
+        if limit == 0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, (started, _)| now.duration_since(*started) < self.window);
+        }
+
+        let (started, count) = buckets.entry(key).or_insert((now, 0));
+        if now.duration_since(*started) >= self.window {
+            *started = now;
+            *count = 0;
+        }
+
+        if *count >= limit {
+            let retry_after = self.window.saturating_sub(now.duration_since(*started));
+            return Err(retry_after.as_secs().max(1));
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    // Проверяет, исчерпан ли лимит по ключу, не учитывая запрос.
+    // При исчерпании возвращает число секунд до нового окна
+    fn peek(&self, key: &K, limit: u32) -> Result<(), u64> {
+        if limit == 0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match buckets.get(key) {
+            Some((started, count)) if now.duration_since(*started) < self.window && *count >= limit => {
+                let retry_after = self.window.saturating_sub(now.duration_since(*started));
+                Err(retry_after.as_secs().max(1))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Ответ 429 с Retry-After
+fn too_many_requests(request_id: Option<&str>, retry_after: u64) -> Response<Body> {
+    let mut response = AppError::RateLimited.into_response(request_id);
+    response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+// Ограничение числа запросов с одного IP в фиксированном окне.
+// Ключ — IP клиента с учетом доверенных прокси, поэтому пользователи за общим прокси
+// учитываются раздельно
+pub struct RateLimiter {
+    limit: AtomicU32,               // Запросов за окно (0 — ограничение выключено)
+    windows: FixedWindows<IpAddr>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit: AtomicU32::new(limit),
+            windows: FixedWindows::new(window),
+        }
+    }
+
+    // Меняет лимит (перезагрузка конфигурации); уже открытые окна сохраняются
+    pub fn set_limit(&self, limit: u32) {
+        self.limit.store(limit, Ordering::Relaxed);
+    }
+
+    // Учитывает запрос клиента. При превышении лимита возвращает число секунд до нового окна
+    pub fn check(&self, ip: IpAddr) -> Result<(), u64> {
+        self.windows.hit(ip, self.limit.load(Ordering::Relaxed))
+    }
+
+    // Проверяет запрос по IP клиента. При превышении лимита возвращает готовый ответ 429
+    pub fn check_request(&self, req: &Request<Body>, trusted_proxies: &[IpAddr]) -> Result<(), Response<Body>> {
+        // Без адреса клиента (например, в тестах без соединения) ограничение не применяется
+        let Some(ip) = client_ip(req, trusted_proxies) else {
+            return Ok(());
+        };
+
+        self.check(ip).map_err(|retry_after| {
+            let request_id = current_request_id(req);
+            log::warn!(
+                "Превышен лимит запросов [ip={}] [request_id={}]",
+                ip,
+                request_id.as_deref().unwrap_or("unknown")
+            );
+            too_many_requests(request_id.as_deref(), retry_after)
+        })
+    }
+}
+
+// Ограничение числа запросов аутентифицированного пользователя: ключ — ID пользователя,
+// лимит зависит от роли (например, администраторам больше или без ограничения)
+pub struct UserRateLimiter {
+    policy: RwLock<RoleRateLimits>,
+    windows: FixedWindows<Uuid>,
+}
+
+impl UserRateLimiter {
+    pub fn new(policy: RoleRateLimits, window: Duration) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            windows: FixedWindows::new(window),
+        }
+    }
+
+    // Меняет лимиты ролей (перезагрузка конфигурации); уже открытые окна сохраняются
+    pub fn set_policy(&self, policy: RoleRateLimits) {
+        *self.policy.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+    }
+
+    // Учитывает запрос пользователя с данной ролью. При превышении — секунды до нового окна
+    pub fn check(&self, user_id: Uuid, role: UserRole) -> Result<(), u64> {
+        let limit = self.policy.read().unwrap_or_else(|poisoned| poisoned.into_inner()).for_role(role);
+        self.windows.hit(user_id, limit)
+    }
+
+    // Проверяет запрос по пользователю и роли из extensions (после аутентификации).
+    // При превышении лимита возвращает готовый ответ 429
+    pub fn check_request(&self, req: &Request<Body>) -> Result<(), Response<Body>> {
+        let (Some(user_id), Some(role)) = (req.extensions().get::<Uuid>(), req.extensions().get::<UserRole>()) else {
+            return Ok(());
+        };
+
+        self.check(*user_id, *role).map_err(|retry_after| {
+            let request_id = current_request_id(req);
+            log::warn!(
+                "Превышен лимит запросов пользователя [user_id={}] [роль={:?}] [request_id={}]",
+                user_id,
+                role,
+                request_id.as_deref().unwrap_or("unknown")
+            );
+            too_many_requests(request_id.as_deref(), retry_after)
+        })
+    }
+}
+
+// Ограничители запросов сервера. Создаются при запуске из AppConfig, хранятся в AppState
+// и передаются запросам через extensions
+pub struct RateLimiters {
+    pub ip: RateLimiter,       // По IP клиента, RATE_LIMIT_PER_MINUTE
+    pub user: UserRateLimiter, // По пользователю и роли, RATE_LIMIT_*_PER_MINUTE
+    pub login: LoginThrottle,  // Неудачные входы по IP клиента, LOGIN_THROTTLE_*_PER_MINUTE
+}
+
+impl RateLimiters {
+    // Ограничители с окном в минуту
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            ip: RateLimiter::new(config.rate_limit_per_minute, Duration::from_secs(60)),
+            user: UserRateLimiter::new(config.role_rate_limits, Duration::from_secs(60)),
+            login: LoginThrottle::new(config.login_throttle, Duration::from_secs(60)),
+        }
+    }
+
+    // Применяет лимиты из перезагруженной конфигурации; уже открытые окна сохраняются
+    pub fn apply(&self, config: &AppConfig) {
+        self.ip.set_limit(config.rate_limit_per_minute);
+        self.user.set_policy(config.role_rate_limits);
+        self.login.set_limits(config.login_throttle);
+    }
+
+    // Ограничители из extensions запроса (None — запрос не прошел через сервер, например в тестах)
+    pub fn from_request<B>(req: &Request<B>) -> Option<Arc<Self>> {
+        req.extensions().get::<Arc<RateLimiters>>().cloned()
+    }
+}
+
+// Проверяет лимит аутентифицированного пользователя. Запрос без ограничителей в extensions
+// не ограничивается
+pub fn check_user_rate_limit(req: &Request<Body>) -> Result<(), Response<Body>> {
+    match RateLimiters::from_request(req) {
+        Some(limiters) => limiters.user.check_request(req),
+        None => Ok(()),
+    }
+}
+
+// Ограничение неудачных попыток входа с одного IP с раздельными лимитами для несуществующего
+// email и неверного пароля. Когда исчерпан любой из лимитов, вход с этого IP отклоняется
+// до конца окна
+pub struct LoginThrottle {
+    limits: RwLock<LoginThrottleLimits>,
+    windows: FixedWindows<(IpAddr, LoginFailureReason)>,
+}
+
+impl LoginThrottle {
+    pub fn new(limits: LoginThrottleLimits, window: Duration) -> Self {
+        Self {
+            limits: RwLock::new(limits),
+            windows: FixedWindows::new(window),
+        }
+    }
+
+    // Лимит для категории неудачного входа; остальные категории (блокировка, неактивный
+    // аккаунт) не учитываются
+    fn limit(&self, reason: LoginFailureReason) -> u32 {
+        let limits = *self.limits.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match reason {
+            LoginFailureReason::NotFound => limits.unknown_email,
+            LoginFailureReason::BadPassword => limits.wrong_password,
+            LoginFailureReason::Locked | LoginFailureReason::Inactive => 0,
+        }
+    }
+
+    // Меняет лимиты (перезагрузка конфигурации); уже открытые окна сохраняются
+    pub fn set_limits(&self, limits: LoginThrottleLimits) {
+        *self.limits.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = limits;
+    }
+
+    // Проверяет, можно ли принять попытку входа с IP. Иначе — секунды до нового окна
+    pub fn check(&self, ip: IpAddr) -> Result<(), u64> {
+        for reason in [LoginFailureReason::NotFound, LoginFailureReason::BadPassword] {
+            self.windows.peek(&(ip, reason), self.limit(reason))?;
+        }
+        Ok(())
+    }
+
+    // Учитывает неудачный вход с IP в лимите его категории
+    pub fn record_failure(&self, ip: IpAddr, reason: LoginFailureReason) {
+        let limit = self.limit(reason);
+        if limit > 0 {
+            // Попытка уже состоялась, поэтому учитывается даже сверх лимита
+            let _ = self.windows.hit((ip, reason), u32::MAX);
+        }
+    }
+
+    // Проверяет попытку входа по IP клиента (с учетом доверенных прокси, как и общий лимит по IP).
+    // При исчерпании лимита возвращает готовый ответ 429
+    pub fn check_request(&self, req: &Request<Body>, trusted_proxies: &[IpAddr]) -> Result<(), Response<Body>> {
+        // Без адреса клиента (например, в тестах без соединения) ограничение не применяется
+        let Some(ip) = client_ip(req, trusted_proxies) else {
+            return Ok(());
+        };
+
+        self.check(ip).map_err(|retry_after| {
+            let request_id = current_request_id(req);
+            log::warn!(
+                "Превышен лимит неудачных попыток входа [ip={}] [request_id={}]",
+                ip,
+                request_id.as_deref().unwrap_or("unknown")
+            );
+            too_many_requests(request_id.as_deref(), retry_after)
+        })
+    }
+}
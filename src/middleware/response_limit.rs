@@ -0,0 +1,35 @@
+use hyper::body::HttpBody;
+use hyper::{Body, Response};
+
+use crate::errors::AppError;
+
+// Ограничение размера буферизованного тела ответа: если тело заранее известной длины
+// превышает max_bytes, вместо него возвращается 500, а в лог пишется предупреждение.
+// Потоковые ответы (экспорт NDJSON и т.п.) размер заранее не знают и не ограничиваются.
+// None — ограничение отключено
+pub fn enforce_response_size_limit(
+    response: Response<Body>,
+    max_bytes: Option<u64>,
+    request_id: Option<&str>,
+) -> Response<Body> {
+    let Some(max_bytes) = max_bytes else {
+        return response;
+    };
+
+    match response.body().size_hint().exact() {
+        Some(size) if size > max_bytes => {
+            log::warn!(
+                "Тело ответа {} байт превышает лимит {} байт, ответ заменен ошибкой [request_id={}]",
+                size,
+                max_bytes,
+                request_id.unwrap_or("unknown")
+            );
+            AppError::Internal(anyhow::anyhow!(
+                "Тело ответа превышает лимит {} байт",
+                max_bytes
+            ))
+            .into_response(request_id)
+        }
+        _ => response,
+    }
+}
@@ -0,0 +1,33 @@
+use hyper::header::{self, HeaderMap, HeaderValue};
+
+// Политика безопасности контента для JSON API: загрузка ресурсов и встраивание во фреймы запрещены
+const CONTENT_SECURITY_POLICY: &str = "default-src 'none'; frame-ancestors 'none'";
+
+// Добавляет защитные заголовки ко всем ответам.
+// Strict-Transport-Security добавляется только если задан max-age (сервер работает за TLS)
+pub fn apply_security_headers(headers: &mut HeaderMap, hsts_max_age: Option<u64>) {
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+    headers.insert(
+        header::REFERRER_POLICY,
+        HeaderValue::from_static("no-referrer"),
+    );
+
+    // CSP не перезаписываем, если обработчик задал собственную политику
+    if !headers.contains_key(header::CONTENT_SECURITY_POLICY) {
+        headers.insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static(CONTENT_SECURITY_POLICY),
+        );
+    }
+
+    if let Some(max_age) = hsts_max_age {
+        let value = format!("max-age={}; includeSubDomains", max_age);
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(header::STRICT_TRANSPORT_SECURITY, value);
+        }
+    }
+}
@@ -1,19 +1,14 @@
 use chrono::{DateTime, Utc};
+use serde::de::{self, Deserializer, Visitor};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use std::collections::BTreeMap;
+use std::env;
+use std::sync::OnceLock;
 use validator::Validate;  // Удален неиспользуемый импорт ValidateArgs
-
-// Добавьте в начало файла models.rs
-#[derive(Debug)]
-pub struct AppConfig {
-    pub database_url: String,
-    pub server_host: String,
-    pub server_port: u16,
-    pub jwt_secret: String,
-    pub jwt_expiration: u64,
-    pub cors_origins: String,
-}
+use webauthn_rs::prelude::{Base64UrlSafeData, RegisterPublicKeyCredential};
+use webauthn_rs_proto::AuthenticatorAttestationResponseRaw;
 
 // Структура для пользователя в базе данных
 #[derive(Debug, Serialize, FromRow)]
@@ -28,59 +23,351 @@ pub struct User {
     pub created_at: DateTime<Utc>, // Время создания аккаунта
     pub updated_at: DateTime<Utc>, // Время последнего обновления
     pub is_active: bool,          // Активен ли аккаунт
+    pub name_updated_at: Option<DateTime<Utc>>, // Время последнего изменения имени
+    pub age_updated_at: Option<DateTime<Utc>>,  // Время последнего изменения возраста
+    pub failed_login_attempts: i32,             // Неудачные попытки входа подряд
+    pub locked_until: Option<DateTime<Utc>>,    // Окончание временной блокировки входа
+    pub pending_email: Option<String>,          // Новый email, ожидающий подтверждения
+    pub must_change_password: bool,             // Требуется смена пароля при следующем входе
+    pub timezone: Option<String>,               // Часовой пояс IANA для отображения времени
+    pub login_count: i32,                       // Количество успешных входов
+    pub last_login_at: Option<DateTime<Utc>>,   // Время последнего успешного входа
 }
 
 // Перечисление для ролей пользователя
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, sqlx::Type)]
-#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+#[derive(Debug, Serialize, PartialEq, Clone, Copy)]
 pub enum UserRole {
     User,
     Admin,
     Moderator,
 }
 
+impl UserRole {
+    // Все роли (для сводок с нулевыми значениями по отсутствующим ролям)
+    pub const ALL: [UserRole; 3] = [UserRole::User, UserRole::Moderator, UserRole::Admin];
+
+    // Каноническое имя роли (совпадает со значениями enum user_role в миграциях)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::User => "User",
+            UserRole::Admin => "Admin",
+            UserRole::Moderator => "Moderator",
+        }
+    }
+}
+
+// Все роли пользователя в extensions: основная (users.role) и дополнительные (user_roles)
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserRoles(pub Vec<UserRole>);
+
+impl UserRoles {
+    // Достаточно ли ролей для требуемой (администратор имеет все права)
+    pub fn satisfies(&self, required: UserRole) -> bool {
+        self.0.iter().any(|role| *role == required || *role == UserRole::Admin)
+    }
+}
+
+// Разбор роли без учета регистра: исторические данные хранят и "User", и "user"
+impl std::str::FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "user" => Ok(UserRole::User),
+            "admin" => Ok(UserRole::Admin),
+            "moderator" => Ok(UserRole::Moderator),
+            _ => Err(format!("Неизвестная роль пользователя: {}", s)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for UserRole {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// Роль передается в БД как текст: подходит и для колонки TEXT, и для enum user_role
+impl sqlx::Type<sqlx::Postgres> for UserRole {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <&str as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        use sqlx::TypeInfo;
+        ty.name() == "user_role" || <&str as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for UserRole {
+    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+        <&str as sqlx::Encode<sqlx::Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+impl sqlx::Decode<'_, sqlx::Postgres> for UserRole {
+    fn decode(value: sqlx::postgres::PgValueRef<'_>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        Ok(value.parse()?)
+    }
+}
+
+// Предельная длина строковых полей по умолчанию (заведомо больше ограничений валидации)
+const DEFAULT_MAX_STRING_FIELD_LENGTH: usize = 1024;
+
+// Строковые поля, длина которых ограничивается уже при разборе тела запроса,
+// чтобы заведомо огромные значения отклонялись до валидации и дальнейшей обработки
+#[derive(Debug, Clone, Copy)]
+enum BoundedField {
+    Name,
+    Email,
+}
+
+impl BoundedField {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BoundedField::Name => "name",
+            BoundedField::Email => "email",
+        }
+    }
+
+    // Предельная длина из MAX_NAME_FIELD_LENGTH / MAX_EMAIL_FIELD_LENGTH
+    fn max_length(&self) -> usize {
+        let variable = match self {
+            BoundedField::Name => "MAX_NAME_FIELD_LENGTH",
+            BoundedField::Email => "MAX_EMAIL_FIELD_LENGTH",
+        };
+        env::var(variable)
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(DEFAULT_MAX_STRING_FIELD_LENGTH)
+    }
+}
+
+// Проверяет длину строки до копирования ее в String
+struct BoundedStringVisitor(BoundedField);
+
+impl<'de> Visitor<'de> for BoundedStringVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "строка не длиннее {} символов", self.0.max_length())
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<String, E> {
+        let max_length = self.0.max_length();
+        if value.chars().count() > max_length {
+            return Err(E::custom(format!(
+                "поле {} длиннее {} символов",
+                self.0.as_str(),
+                max_length
+            )));
+        }
+        Ok(value.to_owned())
+    }
+}
+
+// То же для необязательных полей (null и отсутствие поля — None)
+struct OptionalBoundedStringVisitor(BoundedField);
+
+impl<'de> Visitor<'de> for OptionalBoundedStringVisitor {
+    type Value = Option<String>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "строка не длиннее {} символов или null", self.0.max_length())
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(BoundedStringVisitor(self.0)).map(Some)
+    }
+}
+
+fn deserialize_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    deserializer.deserialize_str(BoundedStringVisitor(BoundedField::Name))
+}
+
+fn deserialize_email<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    deserializer.deserialize_str(BoundedStringVisitor(BoundedField::Email))
+}
+
+fn deserialize_optional_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    deserializer.deserialize_option(OptionalBoundedStringVisitor(BoundedField::Name))
+}
+
+fn deserialize_optional_email<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    deserializer.deserialize_option(OptionalBoundedStringVisitor(BoundedField::Email))
+}
+
+// Поле, которое можно сбросить: отсутствие поля — None (не менять), null — Some(None) (сбросить)
+fn deserialize_nullable<'de, D: Deserializer<'de>, T: Deserialize<'de>>(deserializer: D) -> Result<Option<Option<T>>, D::Error> {
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 // Структура для запроса на создание пользователя
 #[derive(Debug, Deserialize, Validate)]
 pub struct UserRequest {
     #[validate(length(min = 2, max = 100, message = "Имя должно содержать от 2 до 100 символов"))]
+    #[serde(deserialize_with = "deserialize_name")]
     pub name: String,             // Имя пользователя
     
     #[validate(email(message = "Некорректный формат email"))]
+    #[serde(deserialize_with = "deserialize_email")]
     pub email: String,            // Логин (почтовый адрес)
     
     #[validate(length(min = 8, message = "Пароль должен быть не менее 8 символов"))]
-    #[validate(regex(path = "PASSWORD_REGEX", message = "Пароль должен содержать цифры, строчные и заглавные буквы"))]
+    #[validate(custom(function = "validate_password_complexity", message = "Пароль должен содержать цифры, строчные и заглавные буквы"))]
     pub password: String,         // Пароль (нехешированный, для создания)
     
     #[validate(range(min = 13, max = 120, message = "Возраст должен быть от 13 до 120 лет"))]
     pub age: i32,                 // Возраст пользователя (изменен тип с u16 на i32)
 }
 
-// Регулярное выражение для проверки сложности пароля
-lazy_static::lazy_static! {
-    static ref PASSWORD_REGEX: regex::Regex = regex::Regex::new(
-        r"^(?=.*[a-z])(?=.*[A-Z])(?=.*\d).{8,}$"
-    ).unwrap();
+// Политика сложности пароля: не менее 8 символов, есть цифры, строчные и заглавные буквы.
+// PASSWORD_PATTERN задает дополнительное регулярное выражение, которому пароль должен соответствовать
+#[derive(Debug, Default)]
+pub struct PasswordPolicy {
+    pattern: Option<regex::Regex>,
+}
+
+impl PasswordPolicy {
+    // Читает политику из окружения. Некорректное выражение — ошибка с описанием, а не паника
+    pub fn from_env() -> Result<Self, String> {
+        let pattern = match env::var("PASSWORD_PATTERN") {
+            Ok(pattern) if !pattern.trim().is_empty() => Some(
+                regex::Regex::new(&pattern)
+                    .map_err(|e| format!("Некорректное регулярное выражение PASSWORD_PATTERN: {}", e))?,
+            ),
+            _ => None,
+        };
+        Ok(Self { pattern })
+    }
+
+    // Удовлетворяет ли пароль политике
+    pub fn is_satisfied_by(&self, password: &str) -> bool {
+        password.chars().count() >= 8
+            && password.chars().any(|c| c.is_ascii_digit())
+            && password.chars().any(char::is_lowercase)
+            && password.chars().any(char::is_uppercase)
+            && self.pattern.as_ref().is_none_or(|pattern| pattern.is_match(password))
+    }
+}
+
+// Политика загружается один раз: при старте сервера через init_password_policy
+static PASSWORD_POLICY: OnceLock<PasswordPolicy> = OnceLock::new();
+
+// Компилирует политику паролей при старте. Ошибку вызывающий считает фатальной, поэтому
+// некорректный PASSWORD_PATTERN обнаруживается до приема запросов, а не при первой регистрации
+pub fn init_password_policy() -> Result<(), String> {
+    let policy = PasswordPolicy::from_env()?;
+    let _ = PASSWORD_POLICY.set(policy);
+    Ok(())
+}
+
+// Текущая политика. Без init_password_policy (например, при использовании как библиотеки)
+// загружается при первом обращении; некорректное выражение тогда пропускается с ошибкой в логе
+fn password_policy() -> &'static PasswordPolicy {
+    PASSWORD_POLICY.get_or_init(|| {
+        PasswordPolicy::from_env().unwrap_or_else(|e| {
+            log::error!("{}; дополнительное правило для паролей не применяется", e);
+            PasswordPolicy::default()
+        })
+    })
+}
+
+// Проверка сложности пароля по политике
+fn validate_password_complexity(value: &str) -> Result<(), validator::ValidationError> {
+    if password_policy().is_satisfied_by(value) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("password_complexity"))
+    }
+}
+
+// Проверка часового пояса по базе IANA (например, Europe/Moscow)
+fn validate_timezone(value: &str) -> Result<(), validator::ValidationError> {
+    value
+        .parse::<chrono_tz::Tz>()
+        .map(|_| ())
+        .map_err(|_| validator::ValidationError::new("timezone"))
 }
 
 // Структура для запроса на авторизацию
 #[derive(Debug, Deserialize, Validate, Clone)]  // Добавлен Clone
 pub struct LoginRequest {
     #[validate(email(message = "Некорректный формат email"))]
+    #[serde(deserialize_with = "deserialize_email")]
     pub email: String,            // Логин (почтовый адрес)
     
     #[validate(length(min = 1, message = "Пароль не может быть пустым"))]
     pub password: String,         // Пароль (нехешированный, для проверки)
+
+    #[serde(default)]
+    pub remember_me: bool,        // Запомнить вход (токен с увеличенным сроком жизни)
+
+    #[serde(default)]
+    pub reactivate: bool,         // Восстановить самостоятельно деактивированный аккаунт
+}
+
+// Структура для запроса сброса пароля по email
+#[derive(Debug, Deserialize, Validate)]
+pub struct PasswordResetRequest {
+    #[validate(email(message = "Некорректный формат email"))]
+    #[serde(deserialize_with = "deserialize_email")]
+    pub email: String,            // Email аккаунта, для которого запрошен сброс
 }
 
 // Структура для запроса на обновление пользователя
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateUserRequest {
     #[validate(length(min = 2, max = 100, message = "Имя должно содержать от 2 до 100 символов"))]
+    #[serde(default, deserialize_with = "deserialize_optional_name")]
     pub name: Option<String>,     // Новое имя (опционально)
     
     #[validate(range(min = 13, max = 120, message = "Возраст должен быть от 13 до 120 лет"))]
     pub age: Option<i32>,         // Новый возраст (изменен тип с u16 на i32)
+
+    #[validate(email(message = "Некорректный формат email"))]
+    #[serde(default, deserialize_with = "deserialize_optional_email")]
+    pub email: Option<String>,    // Новый email (применяется после подтверждения)
+
+    #[validate(custom(function = "validate_timezone", message = "Неизвестный часовой пояс"))]
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub timezone: Option<Option<String>>, // Часовой пояс IANA, например Europe/Moscow; null сбрасывает его
+}
+
+impl UpdateUserRequest {
+    // Проверяет, изменяет ли запрос хотя бы одно поле относительно текущих данных
+    pub fn has_changes(&self, user: &User) -> bool {
+        self.has_profile_changes(user) || self.email_change(user).is_some()
+    }
+
+    // Проверяет, меняются ли поля, применяемые сразу (имя, возраст и часовой пояс)
+    pub fn has_profile_changes(&self, user: &User) -> bool {
+        let name_changed = self.name.as_ref().is_some_and(|name| *name != user.name);
+        let age_changed = self.age.is_some_and(|age| age != user.age);
+        let timezone_changed = self
+            .timezone
+            .as_ref()
+            .is_some_and(|timezone| timezone.as_ref() != user.timezone.as_ref());
+        name_changed || age_changed || timezone_changed
+    }
+
+    // Новый email, если он отличается и от текущего, и от уже ожидающего подтверждения
+    pub fn email_change(&self, user: &User) -> Option<&str> {
+        self.email
+            .as_deref()
+            .filter(|email| *email != user.email && Some(*email) != user.pending_email.as_deref())
+    }
 }
 
 // Структура для запроса на смену пароля
@@ -89,20 +376,191 @@ pub struct ChangePasswordRequest {
     #[validate(length(min = 1, message = "Текущий пароль не может быть пустым"))]
     pub current_password: String,
     
-    #[validate(regex(path = "PASSWORD_REGEX", message = "Новый пароль должен содержать минимум 8 символов, включая цифры, строчные и заглавные буквы"))]
+    #[validate(custom(function = "validate_password_complexity", message = "Новый пароль должен содержать минимум 8 символов, включая цифры, строчные и заглавные буквы"))]
     pub new_password: String,
     
     #[validate(must_match(other = "new_password", message = "Пароли должны совпадать"))]
     pub confirm_password: String,
 }
 
+// Структура для запроса на сброс пароля администратором (текущий пароль не требуется)
+#[derive(Debug, Deserialize, Validate)]
+pub struct AdminResetPasswordRequest {
+    #[validate(custom(function = "validate_password_complexity", message = "Новый пароль должен содержать минимум 8 символов, включая цифры, строчные и заглавные буквы"))]
+    pub new_password: String,
+
+    #[serde(default)]
+    pub must_change_password: bool, // Потребовать смену пароля при следующем входе
+}
+
+// Действие, записываемое в журнал аудита
+#[derive(Debug, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    PasswordReset,
+    SessionsRevoked,
+    AccountUnlocked,
+    RolesChanged,
+    AccountDeactivated,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::PasswordReset => "password_reset",
+            AuditAction::SessionsRevoked => "sessions_revoked",
+            AuditAction::AccountUnlocked => "account_unlocked",
+            AuditAction::RolesChanged => "roles_changed",
+            AuditAction::AccountDeactivated => "account_deactivated",
+        }
+    }
+}
+
+// Событие аудита до записи в журнал: время фиксируется при создании, а не при вставке,
+// чтобы отложенная запись не сдвигала порядок событий
+#[derive(Debug, Clone)]
+pub struct NewAuditEvent {
+    pub actor_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub action: AuditAction,
+    pub details: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl NewAuditEvent {
+    pub fn new(actor_id: Option<Uuid>, user_id: Option<Uuid>, action: AuditAction, details: Option<serde_json::Value>) -> Self {
+        Self { actor_id, user_id, action, details, created_at: Utc::now() }
+    }
+}
+
+// Структура для запроса на массовое изменение статуса пользователей (для модераторов)
+#[derive(Debug, Deserialize, Validate)]
+pub struct BulkStatusRequest {
+    #[validate(length(min = 1, max = 1000, message = "Список должен содержать от 1 до 1000 ID"))]
+    pub ids: Vec<Uuid>,           // ID пользователей
+    pub is_active: bool,          // Новый статус активации
+    #[serde(default)]
+    pub force: bool,              // Разрешить деактивацию администраторов (только для админов)
+}
+
+// Результат изменения статуса для одного пользователя
+#[derive(Debug, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkStatusOutcome {
+    Updated,                      // Статус изменен
+    NotFound,                     // Пользователь не найден
+    SkippedAdmin,                 // Администратор пропущен (без force)
+}
+
+// Структура для результата по одному ID
+#[derive(Debug, Serialize)]
+pub struct BulkStatusResult {
+    pub id: Uuid,
+    pub result: BulkStatusOutcome,
+}
+
+// Структура для ответа на массовое изменение статуса
+#[derive(Debug, Serialize)]
+pub struct BulkStatusResponse {
+    pub results: Vec<BulkStatusResult>,
+}
+
+// Структура для ответа со страницей списка пользователей
+#[derive(Debug, Serialize)]
+pub struct UserListResponse {
+    pub items: Vec<AdminUserResponse>, // Пользователи на странице
+    pub total: Option<i64>,       // Общее количество пользователей (null, если не запрошено)
+    pub offset: i64,              // Смещение страницы
+    pub limit: i64,               // Размер страницы
+}
+
+// Структура для ответа со страницей пользователей (GET /api/v1/users?page=&per_page=)
+#[derive(Debug, Serialize)]
+pub struct UserPageResponse {
+    pub data: Vec<UserResponse>, // Пользователи на странице
+    pub total: i64,              // Общее количество пользователей
+    pub page: i64,               // Номер страницы (с 1)
+    pub per_page: i64,           // Размер страницы
+}
+
 // Структура для ответа с токеном
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub token: String,            // JWT токен
+    pub expires_in: i64,          // Срок жизни токена в секундах
+    pub refresh_token: String,    // Refresh-токен для получения нового JWT (одноразовый)
     pub user: UserResponse,       // Информация о пользователе
 }
 
+// Структура для запроса на обновление токена
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefreshTokenRequest {
+    #[validate(length(min = 1, max = 256, message = "Некорректный refresh-токен"))]
+    pub refresh_token: String,
+}
+
+// Структура для ответа на обновление токена (прежний refresh-токен больше не действует)
+#[derive(Debug, Serialize)]
+pub struct TokenRefreshResponse {
+    pub token: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
+}
+
+// Структура для ответа с новыми кодами восстановления 2FA (показываются один раз)
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub codes: Vec<String>,
+}
+
+// Структура для запроса на завершение регистрации ключа (результат PublicKeyCredential.toJSON()).
+// Повторяет RegisterPublicKeyCredential из webauthn-rs, но принимает и ключи, переименованные
+// в режиме JSON_CASE=camel (clientDataJSON -> client_data_j_s_o_n)
+#[derive(Debug, Deserialize)]
+pub struct WebauthnRegisterFinishRequest {
+    pub id: String,               // ID ключа в base64url
+    #[serde(rename = "rawId", alias = "raw_id")]
+    pub raw_id: Base64UrlSafeData,
+    pub response: WebauthnAttestationResponse,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+// Ответ аутентификатора на создание ключа
+#[derive(Debug, Deserialize)]
+pub struct WebauthnAttestationResponse {
+    // client_data_j_s_o_n — так ключ выглядит после преобразования ключей в режиме JSON_CASE=camel
+    #[serde(rename = "clientDataJSON", alias = "client_data_json", alias = "client_data_j_s_o_n")]
+    pub client_data_json: Base64UrlSafeData,
+    #[serde(rename = "attestationObject", alias = "attestation_object")]
+    pub attestation_object: Base64UrlSafeData,
+}
+
+impl From<WebauthnRegisterFinishRequest> for RegisterPublicKeyCredential {
+    fn from(request: WebauthnRegisterFinishRequest) -> Self {
+        RegisterPublicKeyCredential {
+            id: request.id,
+            raw_id: request.raw_id,
+            response: AuthenticatorAttestationResponseRaw {
+                attestation_object: request.response.attestation_object,
+                client_data_json: request.response.client_data_json,
+                transports: None,
+            },
+            type_: request.kind,
+            extensions: Default::default(),
+        }
+    }
+}
+
+// Зарегистрированный ключ доступа (сам ключ в формате webauthn-rs хранится в БД и не отдается)
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebauthnCredential {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub credential_id: String,    // ID ключа в base64url
+    pub created_at: DateTime<Utc>,
+}
+
 // Структура для ответа с данными пользователя (без чувствительных полей)
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
@@ -112,16 +570,215 @@ pub struct UserResponse {
     pub age: i32,                 // Изменен тип с u16 на i32
     pub role: UserRole,
     pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_updated_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_updated_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_email: Option<String>,
+    pub must_change_password: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    pub login_count: i32,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+impl UserResponse {
+    // Имена полей ответа, допустимые в параметре частичного ответа ?fields=
+    pub const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "email",
+        "age",
+        "role",
+        "created_at",
+        "name_updated_at",
+        "age_updated_at",
+        "pending_email",
+        "must_change_password",
+        "timezone",
+        "login_count",
+        "last_login_at",
+    ];
+}
+
+// Представление пользователя для администраторских эндпоинтов: публичные поля
+// плюс состояние учетной записи, которое не показывается в обычных ответах
+#[derive(Debug, Serialize)]
+pub struct AdminUserResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub is_active: bool,
+    pub updated_at: DateTime<Utc>,
 }
 
 // Структура для JWT claims
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,              // Идентификатор пользователя (UUID)
     pub exp: i64,                 // Время истечения токена (Unix timestamp)
     pub iat: i64,                 // Время выдачи токена (Unix timestamp)
     pub role: UserRole,           // Роль пользователя
     pub email: String,            // Email пользователя
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>, // Отпечаток клиента (SHA-256 от User-Agent и ID устройства)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,           // ID серверной сессии, созданной при входе
+}
+
+// Серверная сессия пользователя (создается при входе, может быть отозвана)
+#[derive(Debug, Serialize, FromRow)]
+pub struct Session {
+    pub id: Uuid,                 // ID сессии (claim sid в токене)
+    pub user_id: Uuid,            // Владелец сессии
+    pub created_at: DateTime<Utc>, // Время входа
+    pub expires_at: DateTime<Utc>, // Время истечения (совпадает со сроком последнего выданного токена)
+    #[serde(skip_serializing)]
+    pub revoked_at: Option<DateTime<Utc>>, // Время отзыва (в список попадают только активные)
+}
+
+// Refresh-токен в базе (сам токен не хранится, только его хеш)
+#[derive(Debug, FromRow)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,                   // Цепочка ротации (общая для всех токенов одного входа)
+    pub session_id: Uuid,                  // Сессия, в которой выдаются новые JWT
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,    // Время замены новым токеном
+    pub revoked_at: Option<DateTime<Utc>>, // Время отзыва цепочки
+}
+
+// Событие журнала аудита
+#[derive(Debug, Serialize, FromRow)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,   // Кто выполнил действие (None — система)
+    pub user_id: Option<Uuid>,    // Над каким пользователем выполнено действие
+    pub action: String,           // Действие (значение AuditAction::as_str)
+    pub details: Option<serde_json::Value>, // Подробности действия
+    pub created_at: DateTime<Utc>,
+}
+
+// Структура для запроса на замену дополнительных ролей пользователя (основная роль не меняется)
+#[derive(Debug, Deserialize)]
+pub struct UserRolesRequest {
+    pub roles: Vec<UserRole>,     // Дополнительные роли; пустой список снимает все
+}
+
+// Структура для ответа с ролями пользователя
+#[derive(Debug, Serialize)]
+pub struct UserRolesResponse {
+    pub user_id: Uuid,
+    pub role: UserRole,           // Основная роль (users.role)
+    pub roles: Vec<UserRole>,     // Все роли: основная первой, затем дополнительные
+}
+
+// Структура для ответа с журналом аудита пользователя (с пагинацией)
+#[derive(Debug, Serialize)]
+pub struct AuditEventListResponse {
+    pub items: Vec<AuditEvent>,   // События на странице, новые первыми
+    pub total: i64,               // Общее количество событий пользователя
+    pub offset: i64,              // Смещение страницы
+    pub limit: i64,               // Размер страницы
+}
+
+// Структура для ответа со списком сессий пользователя
+#[derive(Debug, Serialize)]
+pub struct SessionListResponse {
+    pub items: Vec<Session>,      // Активные сессии, новые первыми
+}
+
+// Структура для ответа на отзыв сессий
+#[derive(Debug, Serialize)]
+pub struct RevokedSessionsResponse {
+    pub revoked: u64,             // Количество отозванных сессий
+}
+
+// Структура для ответа на перезагрузку конфигурации
+#[derive(Debug, Serialize)]
+pub struct ConfigReloadResponse {
+    pub changed: Vec<&'static str>, // Измененные перезагружаемые настройки (пусто — изменений нет)
+}
+
+// Структура для ответа со сводкой активных сессий (планирование нагрузки)
+#[derive(Debug, Serialize)]
+pub struct ActiveSessionStats {
+    pub total: i64,                        // Активные сессии во всей системе
+    pub by_role: BTreeMap<String, i64>,    // Активные сессии по ролям владельцев (все роли, в том числе с 0)
+}
+
+// Ответ на самостоятельную деактивацию аккаунта
+#[derive(Debug, Serialize)]
+pub struct DeactivationResponse {
+    pub deactivated_at: DateTime<Utc>,             // Время деактивации
+    pub reactivate_before: Option<DateTime<Utc>>,  // До какого момента аккаунт можно восстановить входом
+}
+
+// Сводка безопасности аккаунта. Поля функций, данные которых недоступны, равны null
+#[derive(Debug, Serialize)]
+pub struct SecurityStatusResponse {
+    pub failed_login_attempts: i32,               // Неудачные попытки входа подряд
+    pub locked_until: Option<DateTime<Utc>>,      // Окончание блокировки входа, если она действует
+    pub login_count: i32,                         // Количество успешных входов
+    pub last_login_at: Option<DateTime<Utc>>,     // Время последнего успешного входа
+    pub active_sessions: Option<i64>,             // Количество активных сессий
+    pub recovery_codes_remaining: Option<i64>,    // Неиспользованные коды восстановления 2FA
+    pub password_changed_at: DateTime<Utc>,       // Время последней смены пароля
+    pub password_age_days: i64,                   // Возраст пароля в днях
+}
+
+// Структура для ответа с версией API (публичные данные, кешируются)
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+// Структура для ответа с текущим временем сервера (по нему клиенты оценивают расхождение часов)
+#[derive(Debug, Serialize)]
+pub struct ServerTimeResponse {
+    pub timestamp: i64,       // Unix-время в секундах, как в exp/iat токенов
+    pub timestamp_ms: i64,    // Unix-время в миллисекундах
+    pub time: DateTime<Utc>,  // То же время в ISO 8601
+}
+
+// Структура для ответа на GET / — краткое описание API со ссылками
+#[derive(Debug, Serialize)]
+pub struct ApiRootResponse {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub description: &'static str,
+    pub docs: String,             // Документация (API_DOCS_URL, по умолчанию — репозиторий)
+    pub health: &'static str,     // Проверка состояния сервиса
+}
+
+// Структура для ответа на обмен токена на cookie-сессию (сам токен передается только в cookie)
+#[derive(Debug, Serialize)]
+pub struct CookieSessionResponse {
+    pub expires_in: i64,          // Срок жизни cookie-сессии в секундах
+    pub bearer_revoked: bool,     // Отозвана ли сессия исходного токена
+}
+
+// Структура для ответа проверки токена (данные берутся из claims без обращения к БД)
+#[derive(Debug, Serialize)]
+pub struct TokenInfoResponse {
+    pub id: String,
+    pub email: String,
+    pub role: UserRole,
+    pub expires_at: i64,          // Время истечения токена (Unix timestamp)
+}
+
+impl From<&Claims> for TokenInfoResponse {
+    fn from(claims: &Claims) -> Self {
+        Self {
+            id: claims.sub.clone(),
+            email: claims.email.clone(),
+            role: claims.role,
+            expires_at: claims.exp,
+        }
+    }
 }
 
 impl From<&User> for UserResponse {
@@ -133,6 +790,23 @@ impl From<&User> for UserResponse {
             age: user.age,
             role: user.role,
             created_at: user.created_at,
+            name_updated_at: user.name_updated_at,
+            age_updated_at: user.age_updated_at,
+            pending_email: user.pending_email.clone(),
+            must_change_password: user.must_change_password,
+            timezone: user.timezone.clone(),
+            login_count: user.login_count,
+            last_login_at: user.last_login_at,
+        }
+    }
+}
+
+impl From<&User> for AdminUserResponse {
+    fn from(user: &User) -> Self {
+        Self {
+            user: UserResponse::from(user),
+            is_active: user.is_active,
+            updated_at: user.updated_at,
         }
     }
 }
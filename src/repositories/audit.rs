@@ -0,0 +1,103 @@
+use log::debug;
+use sqlx::{PgExecutor, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{AuditEvent, NewAuditEvent};
+
+// Записывает событие в журнал аудита. Синхронно вызывается в транзакции самого действия
+// (services::audit::commit_with_audit), чтобы событие не потерялось и не появилось без изменения;
+// при фоновой записи (FEATURE_ASYNC_AUDIT) — отдельно, уже после фиксации действия
+pub async fn insert_audit_event<'e, E: PgExecutor<'e>>(event: &NewAuditEvent, executor: E) -> Result<(), AppError> {
+    debug!(
+        "Событие аудита: action={}, actor={:?}, user={:?}",
+        event.action.as_str(),
+        event.actor_id,
+        event.user_id
+    );
+
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log (id, actor_id, user_id, action, details, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(event.actor_id)
+    .bind(event.user_id)
+    .bind(event.action.as_str())
+    .bind(&event.details)
+    .bind(event.created_at)
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при записи события аудита: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(())
+}
+
+// Записывает пачку событий аудита одним запросом (фоновая запись, см. services::audit::AuditWriter)
+pub async fn insert_audit_events(events: &[NewAuditEvent], pool: &PgPool) -> Result<(), AppError> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    debug!("Запись пачки событий аудита: {}", events.len());
+
+    let mut query = QueryBuilder::<Postgres>::new("INSERT INTO audit_log (id, actor_id, user_id, action, details, created_at) ");
+    query.push_values(events, |mut row, event| {
+        row.push_bind(Uuid::new_v4())
+            .push_bind(event.actor_id)
+            .push_bind(event.user_id)
+            .push_bind(event.action.as_str())
+            .push_bind(event.details.clone())
+            .push_bind(event.created_at);
+    });
+
+    query.build().execute(pool).await.map_err(|err| {
+        debug!("Ошибка при записи пачки событий аудита: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(())
+}
+
+// Возвращает страницу событий аудита пользователя, новые первыми
+pub async fn list_user_audit_events(
+    user_id: Uuid,
+    offset: i64,
+    limit: i64,
+    pool: &PgPool,
+) -> Result<Vec<AuditEvent>, AppError> {
+    sqlx::query_as::<_, AuditEvent>(
+        r#"
+        SELECT id, actor_id, user_id, action, details, created_at
+        FROM audit_log
+        WHERE user_id = $1
+        ORDER BY created_at DESC, id
+        OFFSET $2 LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(offset)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при получении журнала аудита пользователя: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Считает события аудита пользователя
+pub async fn count_user_audit_events(user_id: Uuid, pool: &PgPool) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM audit_log WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при подсчете событий аудита пользователя: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })
+}
@@ -0,0 +1,67 @@
+use log::debug;
+use sqlx::pool::PoolConnection;
+use sqlx::{PgConnection, PgPool, Postgres};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::errors::AppError;
+
+// Соединение с БД, общее для всех запросов к БД в рамках одного HTTP-запроса. Берется из пула
+// при первом обращении (запросы без БД пул не трогают) и возвращается в пул, когда запрос
+// завершается и последняя копия RequestConnection удаляется вместе с extensions
+#[derive(Clone)]
+pub struct RequestConnection {
+    pool: PgPool,
+    connection: Arc<Mutex<Option<PoolConnection<Postgres>>>>,
+}
+
+impl RequestConnection {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            connection: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    // Соединение текущего запроса из extensions. Если запрос не проходил через сервер (тесты),
+    // создается отдельное соединение на время обработчика
+    pub fn from_request<B>(req: &hyper::Request<B>, pool: &PgPool) -> Self {
+        req.extensions()
+            .get::<RequestConnection>()
+            .cloned()
+            .unwrap_or_else(|| Self::new(pool.clone()))
+    }
+
+    // Доступ к соединению запроса. Пока guard жив, остальные обращения того же запроса ждут,
+    // поэтому guard не нужно держать дольше одной операции. Транзакция открывается через
+    // sqlx::Connection::begin на guard и тоже выполняется на этом соединении
+    pub async fn acquire(&self) -> Result<RequestConnectionGuard, AppError> {
+        let mut slot = Arc::clone(&self.connection).lock_owned().await;
+        if slot.is_none() {
+            let connection = self.pool.acquire().await.map_err(|err| {
+                debug!("Ошибка при получении соединения для запроса: {:?}", err);
+                AppError::from(err)  // Явно указываем преобразование в AppError
+            })?;
+            *slot = Some(connection);
+        }
+        Ok(RequestConnectionGuard(slot))
+    }
+}
+
+// Монопольный доступ к соединению запроса; передается в репозитории как &mut PgConnection
+pub struct RequestConnectionGuard(OwnedMutexGuard<Option<PoolConnection<Postgres>>>);
+
+impl Deref for RequestConnectionGuard {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &PgConnection {
+        self.0.as_ref().expect("соединение запроса получено в acquire")
+    }
+}
+
+impl DerefMut for RequestConnectionGuard {
+    fn deref_mut(&mut self) -> &mut PgConnection {
+        self.0.as_mut().expect("соединение запроса получено в acquire")
+    }
+}
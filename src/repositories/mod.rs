@@ -2,3 +2,73 @@
 
 // Объявляем подмодуль user, содержащий репозиторий для работы с пользователями
 pub mod user;
+
+// Объявляем подмодуль recovery_code для кодов восстановления двухфакторной аутентификации
+pub mod recovery_code;
+
+// Объявляем подмодуль audit для журнала аудита действий с аккаунтами
+pub mod audit;
+
+// Объявляем подмодуль session для серверных сессий, создаваемых при входе
+pub mod session;
+
+// Объявляем подмодуль refresh_token для refresh-токенов и их цепочек ротации
+pub mod refresh_token;
+
+// Объявляем подмодуль password_reset для токенов самостоятельного сброса пароля
+pub mod password_reset;
+
+// Объявляем подмодуль webauthn для challenge и ключей доступа WebAuthn (passkey)
+pub mod webauthn;
+
+// Объявляем подмодуль connection с общим соединением к БД на время одного HTTP-запроса
+pub mod connection;
+
+use std::env;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::errors::{is_serialization_failure, is_transient_db_error};
+
+// Пауза перед повтором чтения после временной ошибки соединения
+const READ_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+// Выполняет идемпотентное чтение с одним повтором после временной ошибки соединения.
+// Для записей не используется: при обрыве соединения изменение могло уже примениться
+pub async fn retry_read<T, F, Fut>(mut query: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    match query().await {
+        Err(err) if is_transient_db_error(&err) => {
+            log::warn!("Временная ошибка БД, повторяем чтение: {:?}", err);
+            tokio::time::sleep(READ_RETRY_BACKOFF).await;
+            query().await
+        }
+        result => result,
+    }
+}
+
+// Повторять ли запись после конфликта сериализации (DB_RETRY_SERIALIZATION_FAILURES, по умолчанию да)
+fn retry_serialization_failures_enabled() -> bool {
+    env::var("DB_RETRY_SERIALIZATION_FAILURES")
+        .map(|v| !matches!(v.to_ascii_lowercase().as_str(), "false" | "0"))
+        .unwrap_or(true)
+}
+
+// Выполняет запись с одним повтором после конфликта сериализации (40001). Подходит только для
+// операций, которые целиком откатываются при ошибке (один оператор или вся транзакция в query)
+pub async fn retry_serialization_failure<T, F, Fut>(mut query: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    match query().await {
+        Err(err) if is_serialization_failure(&err) && retry_serialization_failures_enabled() => {
+            log::warn!("Конфликт сериализации, повторяем запись: {:?}", err);
+            query().await
+        }
+        result => result,
+    }
+}
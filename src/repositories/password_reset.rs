@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use log::debug;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+// Блокирует строку пользователя до конца транзакции, чтобы параллельные запросы сброса
+// одного пользователя проверяли лимиты по очереди и не выпускали токены сверх них
+pub async fn lock_user_for_password_reset<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<(), AppError> {
+    sqlx::query("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при блокировке пользователя для сброса пароля: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })?;
+
+    Ok(())
+}
+
+// Возвращает количество токенов сброса, выпущенных пользователю начиная с since
+// (включая использованные и отозванные: лимит ограничивает частоту запросов, а не остаток)
+pub async fn count_password_reset_tokens_since<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    since: DateTime<Utc>,
+    executor: E,
+) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM password_reset_tokens WHERE user_id = $1 AND created_at >= $2",
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_one(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при подсчете токенов сброса пароля: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Отзывает самые старые действующие токены пользователя так, чтобы осталось не больше keep.
+// Возвращает количество отозванных токенов
+pub async fn invalidate_old_password_reset_tokens<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    keep: i64,
+    executor: E,
+) -> Result<u64, AppError> {
+    debug!("Отзыв старых токенов сброса пароля: user_id={}, keep={}", user_id, keep);
+
+    let now = Utc::now();
+    let result = sqlx::query(
+        r#"
+        UPDATE password_reset_tokens
+        SET invalidated_at = $3
+        WHERE id IN (
+            SELECT id FROM password_reset_tokens
+            WHERE user_id = $1 AND used_at IS NULL AND invalidated_at IS NULL AND expires_at > $3
+            ORDER BY created_at DESC
+            OFFSET $2
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(keep)
+    .bind(now)
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при отзыве токенов сброса пароля: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(result.rows_affected())
+}
+
+// Сохраняет хеш нового токена сброса пароля
+pub async fn insert_password_reset_token<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+    executor: E,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO password_reset_tokens (id, user_id, token_hash, created_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(Utc::now())
+    .bind(expires_at)
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при сохранении токена сброса пароля: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(())
+}
+
+// Возвращает количество действующих (не истекших, не использованных и не отозванных) токенов пользователя
+pub async fn count_active_password_reset_tokens<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) FROM password_reset_tokens
+        WHERE user_id = $1 AND used_at IS NULL AND invalidated_at IS NULL AND expires_at > $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(Utc::now())
+    .fetch_one(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при подсчете действующих токенов сброса пароля: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
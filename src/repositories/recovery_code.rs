@@ -0,0 +1,91 @@
+use chrono::Utc;
+use log::debug;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+// Удаляет все коды восстановления пользователя (перед выпуском нового набора)
+pub async fn delete_recovery_codes<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    executor: E,
+) -> Result<(), AppError> {
+    debug!("Удаление кодов восстановления: user_id={}", user_id);
+
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при удалении кодов восстановления: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })?;
+
+    Ok(())
+}
+
+// Сохраняет хеш нового кода восстановления
+pub async fn insert_recovery_code<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    code_hash: &str,
+    executor: E,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_recovery_codes (id, user_id, code_hash, created_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(code_hash)
+    .bind(Utc::now())
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при сохранении кода восстановления: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(())
+}
+
+// Помечает код использованным. Возвращает false, если действующего кода с таким хешем нет.
+// Проверка и отметка выполняются одним запросом, поэтому код нельзя использовать дважды
+pub async fn use_recovery_code(user_id: Uuid, code_hash: &str, pool: &PgPool) -> Result<bool, AppError> {
+    debug!("Использование кода восстановления: user_id={}", user_id);
+
+    let used = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        UPDATE user_recovery_codes
+        SET used_at = $3
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(code_hash)
+    .bind(Utc::now())
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при использовании кода восстановления: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(used.is_some())
+}
+
+// Возвращает количество неиспользованных кодов восстановления пользователя
+pub async fn count_unused_recovery_codes<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM user_recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_one(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при подсчете кодов восстановления: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use log::debug;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::RefreshToken;
+
+// Сохраняет хеш нового refresh-токена цепочки ротации
+pub async fn insert_refresh_token<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    family_id: Uuid,
+    session_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+    executor: E,
+) -> Result<(), AppError> {
+    debug!("Создание refresh-токена: user_id={}, family_id={}", user_id, family_id);
+
+    sqlx::query(
+        r#"
+        INSERT INTO refresh_tokens (id, user_id, family_id, session_id, token_hash, created_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(family_id)
+    .bind(session_id)
+    .bind(token_hash)
+    .bind(Utc::now())
+    .bind(expires_at)
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при создании refresh-токена: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(())
+}
+
+// Находит refresh-токен по хешу и блокирует строку до конца транзакции, чтобы
+// параллельные обновления одним токеном выполнялись по очереди
+pub async fn find_refresh_token_for_update<'e, E: PgExecutor<'e>>(
+    token_hash: &str,
+    executor: E,
+) -> Result<Option<RefreshToken>, AppError> {
+    sqlx::query_as::<_, RefreshToken>(
+        r#"
+        SELECT id, user_id, family_id, session_id, created_at, expires_at, used_at, revoked_at
+        FROM refresh_tokens
+        WHERE token_hash = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при поиске refresh-токена: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Отмечает токен использованным (замененным при ротации)
+pub async fn mark_refresh_token_used<'e, E: PgExecutor<'e>>(token_id: Uuid, executor: E) -> Result<(), AppError> {
+    sqlx::query("UPDATE refresh_tokens SET used_at = $2 WHERE id = $1 AND used_at IS NULL")
+        .bind(token_id)
+        .bind(Utc::now())
+        .execute(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при ротации refresh-токена: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })?;
+
+    Ok(())
+}
+
+// Отзывает все токены цепочки ротации. Возвращает количество отозванных токенов
+pub async fn revoke_refresh_token_family<'e, E: PgExecutor<'e>>(family_id: Uuid, executor: E) -> Result<u64, AppError> {
+    debug!("Отзыв цепочки refresh-токенов: family_id={}", family_id);
+
+    let result = sqlx::query("UPDATE refresh_tokens SET revoked_at = $2 WHERE family_id = $1 AND revoked_at IS NULL")
+        .bind(family_id)
+        .bind(Utc::now())
+        .execute(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при отзыве цепочки refresh-токенов: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,197 @@
+use chrono::{DateTime, Utc};
+use log::debug;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{Session, UserRole};
+
+// Создает сессию пользователя при входе
+pub async fn insert_session<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+    executor: E,
+) -> Result<Session, AppError> {
+    debug!("Создание сессии: user_id={}", user_id);
+
+    sqlx::query_as::<_, Session>(
+        r#"
+        INSERT INTO user_sessions (id, user_id, created_at, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, user_id, created_at, expires_at, revoked_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(Utc::now())
+    .bind(expires_at)
+    .fetch_one(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при создании сессии: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Возвращает активные (не отозванные и не истекшие) сессии пользователя, новые первыми
+pub async fn list_active_sessions(user_id: Uuid, pool: &PgPool) -> Result<Vec<Session>, AppError> {
+    sqlx::query_as::<_, Session>(
+        r#"
+        SELECT id, user_id, created_at, expires_at, revoked_at
+        FROM user_sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $2
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(Utc::now())
+    .fetch_all(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при получении сессий пользователя: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Проверяет, что сессия не отозвана и не истекла
+pub async fn is_session_active(session_id: Uuid, pool: &PgPool) -> Result<bool, AppError> {
+    let active = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM user_sessions
+            WHERE id = $1 AND revoked_at IS NULL AND expires_at > $2
+        )
+        "#,
+    )
+    .bind(session_id)
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при проверке сессии: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(active)
+}
+
+// Отзывает все активные сессии пользователя. Возвращает количество отозванных сессий
+pub async fn revoke_user_sessions<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<u64, AppError> {
+    debug!("Отзыв сессий: user_id={}", user_id);
+
+    let result = sqlx::query(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = $2
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(Utc::now())
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при отзыве сессий: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(result.rows_affected())
+}
+
+// Отзывает одну сессию. Возвращает false, если сессия уже отозвана или не найдена
+pub async fn revoke_session<'e, E: PgExecutor<'e>>(session_id: Uuid, executor: E) -> Result<bool, AppError> {
+    debug!("Отзыв сессии: id={}", session_id);
+
+    let result = sqlx::query("UPDATE user_sessions SET revoked_at = $2 WHERE id = $1 AND revoked_at IS NULL")
+        .bind(session_id)
+        .bind(Utc::now())
+        .execute(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при отзыве сессии: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Продлевает неотозванную сессию при обновлении токена (в том числе уже истекшую).
+// Возвращает false, если сессия отозвана или не найдена
+pub async fn extend_session<'e, E: PgExecutor<'e>>(
+    session_id: Uuid,
+    expires_at: DateTime<Utc>,
+    executor: E,
+) -> Result<bool, AppError> {
+    debug!("Продление сессии: id={}", session_id);
+
+    let result = sqlx::query("UPDATE user_sessions SET expires_at = $2 WHERE id = $1 AND revoked_at IS NULL")
+        .bind(session_id)
+        .bind(expires_at)
+        .execute(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при продлении сессии: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Возвращает количество активных сессий пользователя и время последнего входа
+pub async fn session_activity<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    executor: E,
+) -> Result<(i64, Option<DateTime<Utc>>), AppError> {
+    sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE revoked_at IS NULL AND expires_at > $2),
+            MAX(created_at)
+        FROM user_sessions
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(Utc::now())
+    .fetch_one(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при получении активности сессий: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Считает активные (не отозванные и не истекшие) сессии во всей системе
+pub async fn count_active_sessions(pool: &PgPool) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM user_sessions WHERE revoked_at IS NULL AND expires_at > $1",
+    )
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при подсчете активных сессий: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Считает активные сессии по ролям владельцев. Роль в БД может храниться в разном регистре,
+// поэтому одна роль может встретиться в результате несколько раз
+pub async fn count_active_sessions_by_role(pool: &PgPool) -> Result<Vec<(UserRole, i64)>, AppError> {
+    sqlx::query_as::<_, (UserRole, i64)>(
+        r#"
+        SELECT u.role, COUNT(*)
+        FROM user_sessions s
+        JOIN users u ON u.id = s.user_id
+        WHERE s.revoked_at IS NULL AND s.expires_at > $1
+        GROUP BY u.role
+        "#,
+    )
+    .bind(Utc::now())
+    .fetch_all(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при подсчете активных сессий по ролям: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
@@ -1,10 +1,12 @@
-use chrono::Utc;  // Удален неиспользуемый импорт DateTime
-use sqlx::PgPool;  // Удален неиспользуемый импорт postgres::PgQueryResult
+use chrono::{DateTime, Utc};
+use futures_util::stream::{BoxStream, StreamExt};
+use sqlx::{PgExecutor, PgPool};  // Удален неиспользуемый импорт postgres::PgQueryResult
 use uuid::Uuid;
 use log::debug;
 
 use crate::errors::AppError;
 use crate::models::{UpdateUserRequest, User, UserRole};
+use crate::repositories::{retry_read, retry_serialization_failure};
 
 // Создаёт пользователя в базе данных
 pub async fn create_user(user: &User, pool: &PgPool) -> Result<User, AppError> {
@@ -14,10 +16,12 @@ pub async fn create_user(user: &User, pool: &PgPool) -> Result<User, AppError> {
         r#"
         INSERT INTO users (id, name, email, password_hash, age, role, created_at, updated_at, is_active)
         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active
+        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+                  name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+                  must_change_password, timezone, login_count, last_login_at
         "#,
     )
-    .bind(&user.id)
+    .bind(user.id)
     .bind(&user.name)
     .bind(&user.email)
     .bind(&user.password_hash)
@@ -32,10 +36,9 @@ pub async fn create_user(user: &User, pool: &PgPool) -> Result<User, AppError> {
         // Проверяем ошибки нарушения ограничений
         if let sqlx::Error::Database(ref db_err) = err {
             if let Some(constraint) = db_err.constraint() {
-                if constraint == "users_email_key" {
-                    return AppError::Conflict(format!(
-                        "Пользователь с email '{}' уже существует", user.email
-                    ));
+                if constraint == "users_email_key" || constraint == "users_email_lower_key" {
+                    debug!("Email '{}' уже занят", user.email);
+                    return AppError::email_taken();
                 }
             }
         }
@@ -47,19 +50,39 @@ pub async fn create_user(user: &User, pool: &PgPool) -> Result<User, AppError> {
     Ok(result)
 }
 
-// Находит пользователя по email
+// Находит активного пользователя по email без учета регистра
 pub async fn find_user_by_email(email: &str, pool: &PgPool) -> Result<User, AppError> {
+    let user = find_user_by_email_any_status(email, pool).await?;
+
+    // Проверяем активность пользователя
+    if !user.is_active {
+        return Err(AppError::Forbidden(
+            "Аккаунт пользователя деактивирован".to_string()
+        ));
+    }
+
+    Ok(user)
+}
+
+// Находит пользователя по email без учета регистра и независимо от статуса активации
+// (для входа, где неактивный аккаунт обрабатывается только после проверки пароля).
+// Условие совпадает с функциональным индексом users_email_lower_key
+pub async fn find_user_by_email_any_status(email: &str, pool: &PgPool) -> Result<User, AppError> {
     debug!("Поиск пользователя по email: {}", email);
     
-    let user = sqlx::query_as::<_, User>(
-        r#"
-        SELECT id, name, email, password_hash, age, role, created_at, updated_at, is_active
-        FROM users
-        WHERE email = $1
-        "#,
-    )
-    .bind(email)
-    .fetch_one(pool)
+    let user = retry_read(|| {
+        sqlx::query_as::<_, User>(
+            r#"
+            SELECT id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+                   name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+                   must_change_password, timezone, login_count, last_login_at
+            FROM users
+            WHERE LOWER(email) = LOWER($1)
+            "#,
+        )
+        .bind(email)
+        .fetch_one(pool)
+    })
     .await
     .map_err(|err| {
         if let sqlx::Error::RowNotFound = err {
@@ -71,79 +94,258 @@ pub async fn find_user_by_email(email: &str, pool: &PgPool) -> Result<User, AppE
         }
     })?;
 
-    // Проверяем активность пользователя
-    if !user.is_active {
-        return Err(AppError::Forbidden(
-            "Аккаунт пользователя деактивирован".to_string()
-        ));
-    }
-
     debug!("Пользователь найден: id={}", user.id);
     Ok(user)
 }
 
-// Находит пользователя по ID
+// Проверяет, занят ли email (без учета регистра и статуса активации), не читая строку пользователя.
+// Для проверок, где важен только факт существования
+pub async fn email_exists(email: &str, pool: &PgPool) -> Result<bool, AppError> {
+    retry_read(|| {
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = LOWER($1))")
+            .bind(email)
+            .fetch_one(pool)
+    })
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при проверке существования email: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Проверяет, что users.role хранится как TEXT: роль передается в запросы текстовым параметром,
+// и на колонке с enum user_role (до миграции 0004) запись роли завершается ошибкой
+pub async fn check_role_column_type(pool: &PgPool) -> Result<(), String> {
+    let udt_name = sqlx::query_scalar::<_, String>(
+        "SELECT udt_name::text FROM information_schema.columns \
+         WHERE table_schema = current_schema() AND table_name = 'users' AND column_name = 'role'",
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Не удалось определить тип колонки users.role: {}", e))?;
+
+    match udt_name.as_deref() {
+        Some("text") | Some("varchar") => Ok(()),
+        Some(other) => Err(format!(
+            "Колонка users.role имеет тип {}, ожидается text; примените миграцию 0004_user_role_to_text.sql",
+            other
+        )),
+        None => Err("В таблице users нет колонки role; примените миграции".to_string()),
+    }
+}
+
+// Находит пользователя по ID. При временной ошибке соединения запрос повторяется один раз
 pub async fn find_user_by_id(id: Uuid, pool: &PgPool) -> Result<User, AppError> {
     debug!("Поиск пользователя по ID: {}", id);
-    
+
+    retry_read(|| fetch_user_by_id(id, pool))
+        .await
+        .map_err(|err| user_by_id_error(id, err))
+}
+
+// Находит пользователя по ID внутри транзакции (без повтора: после обрыва соединения транзакция потеряна)
+pub async fn find_user_by_id_with<'e, E: PgExecutor<'e>>(id: Uuid, executor: E) -> Result<User, AppError> {
+    debug!("Поиск пользователя по ID: {}", id);
+
+    fetch_user_by_id(id, executor)
+        .await
+        .map_err(|err| user_by_id_error(id, err))
+}
+
+// Запрос пользователя по ID без преобразования ошибки (используется обеими версиями поиска)
+async fn fetch_user_by_id<'e, E: PgExecutor<'e>>(id: Uuid, executor: E) -> Result<User, sqlx::Error> {
     let user = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, name, email, password_hash, age, role, created_at, updated_at, is_active
+        SELECT id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+               name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+               must_change_password, timezone, login_count, last_login_at
         FROM users
         WHERE id = $1
         "#,
     )
     .bind(id)
-    .fetch_one(pool)
+    .fetch_one(executor)
+    .await?;
+
+    debug!("Пользователь найден: id={}", id);
+    Ok(user)
+}
+
+// Преобразует ошибку поиска по ID: отсутствие строки — NotFound, остальное — по общим правилам
+fn user_by_id_error(id: Uuid, err: sqlx::Error) -> AppError {
+    if let sqlx::Error::RowNotFound = err {
+        debug!("Пользователь с ID '{}' не найден", id);
+        AppError::NotFound(format!("Пользователь с ID '{}' не найден", id))
+    } else {
+        debug!("Ошибка при поиске пользователя по ID: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    }
+}
+
+// Текущая роль пользователя (None — пользователь не найден). Используется при проверке прав,
+// поэтому читает только одну колонку
+pub async fn find_user_role(id: Uuid, pool: &PgPool) -> Result<Option<UserRole>, AppError> {
+    retry_read(|| {
+        sqlx::query_scalar::<_, UserRole>("SELECT role FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+    })
     .await
     .map_err(|err| {
-        if let sqlx::Error::RowNotFound = err {
-            debug!("Пользователь с ID '{}' не найден", id);
-            AppError::NotFound(format!("Пользователь с ID '{}' не найден", id))
-        } else {
-            debug!("Ошибка при поиске пользователя по ID: {:?}", err);
+        debug!("Ошибка при получении роли пользователя: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Дополнительные роли пользователя из user_roles (основная роль хранится в users.role)
+pub async fn list_additional_roles<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<Vec<UserRole>, AppError> {
+    sqlx::query_scalar::<_, UserRole>("SELECT role FROM user_roles WHERE user_id = $1 ORDER BY granted_at, role")
+        .bind(user_id)
+        .fetch_all(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при получении дополнительных ролей: {:?}", err);
             AppError::from(err)  // Явно указываем преобразование в AppError
-        }
-    })?;
+        })
+}
 
-    debug!("Пользователь найден: id={}", id);
-    Ok(user)
+// Удаляет все дополнительные роли пользователя
+pub async fn delete_additional_roles<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+        .bind(user_id)
+        .execute(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при удалении дополнительных ролей: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })?;
+    Ok(())
+}
+
+// Добавляет пользователю дополнительную роль (повторное добавление ничего не меняет)
+pub async fn insert_additional_role<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    role: UserRole,
+    executor: E,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_roles (user_id, role, granted_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, role) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(role)
+    .bind(Utc::now())
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при добавлении роли пользователю: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+    Ok(())
 }
 
 // Обновляет данные пользователя
-pub async fn update_user(
+pub async fn update_user<'e, E: PgExecutor<'e>>(
     user_id: Uuid,
     update_request: UpdateUserRequest,
-    pool: &PgPool,
+    executor: E,
 ) -> Result<User, AppError> {
     debug!("Обновление пользователя: id={}", user_id);
     
-    // Проверяем существование пользователя
-    let _current_user = find_user_by_id(user_id, pool).await?;
-    
     // Формируем SQL запрос с использованием COALESCE для обновления только заданных полей
+    // (часовой пояс, в отличие от остальных, можно сбросить в NULL: для него передается признак $6).
+    // Временные метки (общая и по полям) сдвигаются только при фактическом изменении значения,
+    // поэтому PATCH с теми же значениями не меняет updated_at
     let result = sqlx::query_as::<_, User>(
         r#"
-        UPDATE users 
-        SET 
+        UPDATE users
+        SET
             name = COALESCE($1, name),
             age = COALESCE($2, age),
-            updated_at = $3
+            timezone = CASE WHEN $6 THEN $5 ELSE timezone END,
+            name_updated_at = CASE
+                WHEN $1 IS NOT NULL AND $1 IS DISTINCT FROM name THEN $3
+                ELSE name_updated_at
+            END,
+            age_updated_at = CASE
+                WHEN $2 IS NOT NULL AND $2 IS DISTINCT FROM age THEN $3
+                ELSE age_updated_at
+            END,
+            updated_at = CASE
+                WHEN ($1 IS NOT NULL AND $1 IS DISTINCT FROM name)
+                  OR ($2 IS NOT NULL AND $2 IS DISTINCT FROM age)
+                  OR ($6 AND $5 IS DISTINCT FROM timezone) THEN $3
+                ELSE updated_at
+            END
         WHERE id = $4
-        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active
+        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+                  name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+                  must_change_password, timezone, login_count, last_login_at
         "#,
     )
     .bind(update_request.name.as_ref())
     .bind(update_request.age)  // i32 вместо u16
     .bind(Utc::now())
     .bind(user_id)
-    .fetch_one(pool)
-    .await?;  // Здесь ? автоматически преобразует sqlx::Error в AppError
+    .bind(update_request.timezone.as_ref().and_then(Option::as_ref))
+    .bind(update_request.timezone.is_some())  // null в запросе сбрасывает часовой пояс
+    .fetch_one(executor)
+    .await
+    .map_err(|err| {
+        if let sqlx::Error::RowNotFound = err {
+            debug!("Пользователь с ID '{}' не найден при обновлении", user_id);
+            AppError::NotFound(format!("Пользователь с ID '{}' не найден", user_id))
+        } else {
+            AppError::from(err)  // CHECK и прочие ошибки БД преобразуются в AppError
+        }
+    })?;
 
     debug!("Пользователь успешно обновлен: id={}", user_id);
     Ok(result)
 }
 
+// Сохраняет новый email, ожидающий подтверждения (текущий email не меняется)
+pub async fn set_pending_email<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    pending_email: &str,
+    executor: E,
+) -> Result<User, AppError> {
+    debug!("Запрос смены email: id={}", user_id);
+
+    let result = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET
+            pending_email = $1,
+            updated_at = $2
+        WHERE id = $3
+        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+                  name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+                  must_change_password, timezone, login_count, last_login_at
+        "#,
+    )
+    .bind(pending_email)
+    .bind(Utc::now())
+    .bind(user_id)
+    .fetch_one(executor)
+    .await
+    .map_err(|err| {
+        if let sqlx::Error::RowNotFound = err {
+            debug!("Пользователь с ID '{}' не найден при смене email", user_id);
+            AppError::NotFound(format!("Пользователь с ID '{}' не найден", user_id))
+        } else {
+            debug!("Ошибка при сохранении нового email: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        }
+    })?;
+
+    debug!("Новый email ожидает подтверждения: id={}", user_id);
+    Ok(result)
+}
+
 // Изменяет роль пользователя (для админов)
 pub async fn update_user_role(
     user_id: Uuid,
@@ -159,7 +361,9 @@ pub async fn update_user_role(
             role = $1,
             updated_at = $2
         WHERE id = $3
-        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active
+        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+                  name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+                  must_change_password, timezone, login_count, last_login_at
         "#,
     )
     .bind(new_role)
@@ -181,11 +385,12 @@ pub async fn update_user_role(
     Ok(result)
 }
 
-// Изменяет статус активации пользователя (для админов)
-pub async fn update_user_status(
+// Изменяет статус активации пользователя (для админов, принимает пул или транзакцию).
+// Сбрасывает deactivated_at: аккаунт, деактивированный администратором, нельзя восстановить входом
+pub async fn update_user_status<'e, E: PgExecutor<'e>>(
     user_id: Uuid,
     is_active: bool,
-    pool: &PgPool,
+    executor: E,
 ) -> Result<User, AppError> {
     debug!("Изменение статуса активации пользователя: id={}, active={}", user_id, is_active);
     
@@ -194,15 +399,18 @@ pub async fn update_user_status(
         UPDATE users 
         SET 
             is_active = $1,
+            deactivated_at = NULL,
             updated_at = $2
         WHERE id = $3
-        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active
+        RETURNING id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+                  name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+                  must_change_password, timezone, login_count, last_login_at
         "#,
     )
     .bind(is_active)
     .bind(Utc::now())
     .bind(user_id)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
     .map_err(|err| {
         if let sqlx::Error::RowNotFound = err {
@@ -218,11 +426,13 @@ pub async fn update_user_status(
     Ok(result)
 }
 
-// Изменяет пароль пользователя
-pub async fn update_user_password(
+// Изменяет пароль пользователя и устанавливает требование сменить его при следующем входе
+// (при обычной смене пароля must_change_password = false снимает требование)
+pub async fn update_user_password<'e, E: PgExecutor<'e>>(
     user_id: Uuid,
     password_hash: &str,
-    pool: &PgPool,
+    must_change_password: bool,
+    executor: E,
 ) -> Result<(), AppError> {
     debug!("Изменение пароля пользователя: id={}", user_id);
     
@@ -231,14 +441,17 @@ pub async fn update_user_password(
         UPDATE users 
         SET 
             password_hash = $1,
-            updated_at = $2
-        WHERE id = $3
+            must_change_password = $2,
+            password_changed_at = $3,
+            updated_at = $3
+        WHERE id = $4
         "#,
     )
     .bind(password_hash)
+    .bind(must_change_password)
     .bind(Utc::now())
     .bind(user_id)
-    .execute(pool)
+    .execute(executor)
     .await
     .map_err(|err| {
         debug!("Ошибка при обновлении пароля пользователя: {:?}", err);
@@ -254,6 +467,98 @@ pub async fn update_user_password(
     Ok(())
 }
 
+// Регистрирует неудачную попытку входа и блокирует аккаунт при превышении лимита.
+// Возвращает время окончания блокировки, если она была установлена
+pub async fn register_failed_login(
+    user_id: Uuid,
+    max_attempts: i32,
+    lockout_until: DateTime<Utc>,
+    pool: &PgPool,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+    debug!("Регистрация неудачной попытки входа: id={}", user_id);
+
+    // Инкремент и проверка лимита выполняются одним запросом, чтобы не терять попытки
+    // при параллельных входах
+    let locked_until: (Option<DateTime<Utc>>,) = sqlx::query_as(
+        r#"
+        UPDATE users
+        SET
+            failed_login_attempts = failed_login_attempts + 1,
+            locked_until = CASE
+                WHEN failed_login_attempts + 1 >= $2 THEN $3
+                ELSE locked_until
+            END
+        WHERE id = $1
+        RETURNING locked_until
+        "#,
+    )
+    .bind(user_id)
+    .bind(max_attempts)
+    .bind(lockout_until)
+    .fetch_one(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при регистрации неудачной попытки входа: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(locked_until.0)
+}
+
+// Сбрасывает счетчик неудачных попыток входа и снимает блокировку.
+// Возвращает true, если счетчик или блокировка действительно были сброшены
+pub async fn reset_failed_logins<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<bool, AppError> {
+    debug!("Сброс счетчика неудачных попыток входа: id={}", user_id);
+
+    let result = sqlx::query(
+        r#"
+        UPDATE users
+        SET
+            failed_login_attempts = 0,
+            locked_until = NULL
+        WHERE id = $1 AND (failed_login_attempts <> 0 OR locked_until IS NOT NULL)
+        "#,
+    )
+    .bind(user_id)
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при сбросе счетчика неудачных попыток входа: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Фиксирует успешный вход: увеличивает счетчик входов и обновляет время последнего входа.
+// Инкремент выполняется одним запросом, поэтому параллельные входы не теряют обновления
+pub async fn record_successful_login(user_id: Uuid, pool: &PgPool) -> Result<(i32, DateTime<Utc>), AppError> {
+    debug!("Регистрация успешного входа: id={}", user_id);
+
+    // При параллельных входах под сериализуемой изоляцией обновление может получить 40001:
+    // оператор откатывается целиком, поэтому его безопасно повторить
+    retry_serialization_failure(|| {
+        sqlx::query_as::<_, (i32, DateTime<Utc>)>(
+            r#"
+            UPDATE users
+            SET
+                login_count = login_count + 1,
+                last_login_at = GREATEST(COALESCE(last_login_at, $2), $2)
+            WHERE id = $1
+            RETURNING login_count, last_login_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_one(pool)
+    })
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при регистрации успешного входа: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
 // Удаляет пользователя (мягкое удаление путём деактивации)
 pub async fn soft_delete_user(user_id: Uuid, pool: &PgPool) -> Result<(), AppError> {
     debug!("Мягкое удаление пользователя: id={}", user_id);
@@ -263,6 +568,7 @@ pub async fn soft_delete_user(user_id: Uuid, pool: &PgPool) -> Result<(), AppErr
         UPDATE users 
         SET 
             is_active = false,
+            deactivated_at = NULL,
             updated_at = $1
         WHERE id = $2
         "#,
@@ -285,24 +591,116 @@ pub async fn soft_delete_user(user_id: Uuid, pool: &PgPool) -> Result<(), AppErr
     Ok(())
 }
 
-// Список пользователей с пагинацией (для админов)
+// Деактивирует аккаунт по запросу самого пользователя и запоминает время деактивации.
+// Возвращает время деактивации
+pub async fn deactivate_user<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<DateTime<Utc>, AppError> {
+    debug!("Самостоятельная деактивация пользователя: id={}", user_id);
+
+    sqlx::query_scalar::<_, DateTime<Utc>>(
+        r#"
+        UPDATE users
+        SET
+            is_active = false,
+            deactivated_at = $2,
+            updated_at = $2
+        WHERE id = $1
+        RETURNING deactivated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(Utc::now())
+    .fetch_optional(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при деактивации пользователя: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?
+    .ok_or_else(|| AppError::NotFound(format!("Пользователь с ID '{}' не найден", user_id)))
+}
+
+// Время самостоятельной деактивации аккаунта (None — аккаунт не деактивирован пользователем)
+pub async fn find_deactivated_at<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    executor: E,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+    sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT deactivated_at FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(|err| {
+            debug!("Ошибка при получении времени деактивации: {:?}", err);
+            AppError::from(err)  // Явно указываем преобразование в AppError
+        })
+        .map(Option::flatten)
+}
+
+// Восстанавливает самостоятельно деактивированный аккаунт. Возвращает false, если аккаунт
+// уже активен или деактивирован администратором
+pub async fn reactivate_user<'e, E: PgExecutor<'e>>(user_id: Uuid, executor: E) -> Result<bool, AppError> {
+    debug!("Восстановление аккаунта: id={}", user_id);
+
+    let result = sqlx::query(
+        r#"
+        UPDATE users
+        SET
+            is_active = true,
+            deactivated_at = NULL,
+            updated_at = $2
+        WHERE id = $1 AND deactivated_at IS NOT NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(Utc::now())
+    .execute(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при восстановлении аккаунта: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Экранирует спецсимволы LIKE (%, _ и \), чтобы строка поиска сравнивалась буквально
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Шаблон ILIKE для поиска подстроки (None — без фильтра)
+fn search_pattern(search: Option<&str>) -> Option<String> {
+    search.map(|q| format!("%{}%", escape_like(q)))
+}
+
+// Список пользователей с пагинацией и поиском по подстроке имени или email (для админов)
 pub async fn list_users(
     offset: i64,
     limit: i64,
+    search: Option<&str>,
     pool: &PgPool,
 ) -> Result<Vec<User>, AppError> {
-    debug!("Получение списка пользователей: offset={}, limit={}", offset, limit);
+    debug!("Получение списка пользователей: offset={}, limit={}, search={:?}", offset, limit, search);
     
     let users = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, name, email, password_hash, age, role, created_at, updated_at, is_active
+        SELECT id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+               name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+               must_change_password, timezone, login_count, last_login_at
         FROM users
+        WHERE $3::TEXT IS NULL OR name ILIKE $3 ESCAPE '\' OR email ILIKE $3 ESCAPE '\'
         ORDER BY created_at DESC
         LIMIT $1 OFFSET $2
         "#,
     )
     .bind(limit)
     .bind(offset)
+    .bind(search_pattern(search))
     .fetch_all(pool)
     .await
     .map_err(|err| {
@@ -314,18 +712,60 @@ pub async fn list_users(
     Ok(users)
 }
 
-// Подсчет общего количества пользователей
-pub async fn count_users(pool: &PgPool) -> Result<i64, AppError> {
-    debug!("Подсчет общего количества пользователей");
+// Потоковое чтение всех пользователей через курсор (для экспорта без загрузки таблицы в память)
+pub fn stream_users(pool: &PgPool) -> BoxStream<'_, Result<User, AppError>> {
+    debug!("Потоковое чтение списка пользователей");
+
+    sqlx::query_as::<_, User>(
+        r#"
+        SELECT id, name, email, password_hash, age, role, created_at, updated_at, is_active,
+               name_updated_at, age_updated_at, failed_login_attempts, locked_until, pending_email,
+               must_change_password, timezone, login_count, last_login_at
+        FROM users
+        ORDER BY created_at
+        "#,
+    )
+    .fetch(pool)
+    .map(|row| row.map_err(AppError::from))
+    .boxed()
+}
+
+// Подсчет количества пользователей (с учетом поиска, если задан)
+pub async fn count_users(search: Option<&str>, pool: &PgPool) -> Result<i64, AppError> {
+    debug!("Подсчет количества пользователей: search={:?}", search);
     
-    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
-        .fetch_one(pool)
-        .await
-        .map_err(|err| {
-            debug!("Ошибка при подсчете пользователей: {:?}", err);
-            AppError::from(err)  // Явно указываем преобразование в AppError
-        })?;
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM users
+        WHERE $1::TEXT IS NULL OR name ILIKE $1 ESCAPE '\' OR email ILIKE $1 ESCAPE '\'
+        "#,
+    )
+    .bind(search_pattern(search))
+    .fetch_one(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при подсчете пользователей: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
 
-    debug!("Общее количество пользователей: {}", count.0);
+    debug!("Количество пользователей: {}", count.0);
     Ok(count.0)
-}
\ No newline at end of file
+}
+
+// Возвращает время последней смены пароля (для паролей, не менявшихся с регистрации, — время создания)
+pub async fn find_password_changed_at<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    executor: E,
+) -> Result<DateTime<Utc>, AppError> {
+    sqlx::query_scalar::<_, DateTime<Utc>>(
+        "SELECT COALESCE(password_changed_at, created_at) FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при получении времени смены пароля: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?
+    .ok_or_else(|| AppError::NotFound(format!("Пользователь с ID '{}' не найден", user_id)))
+}
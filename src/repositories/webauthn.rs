@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use log::debug;
+use sqlx::types::Json;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+use webauthn_rs::prelude::{Passkey, PasskeyRegistration};
+
+use crate::errors::AppError;
+use crate::models::WebauthnCredential;
+
+// Сохраняет состояние регистрации ключа (challenge и параметры проверки ответа). Незавершенные
+// регистрации пользователя при этом отменяются: действительна только последняя начатая
+pub async fn replace_webauthn_registration(
+    user_id: Uuid,
+    challenge: &str,
+    registration: &PasskeyRegistration,
+    expires_at: DateTime<Utc>,
+    pool: &PgPool,
+) -> Result<(), AppError> {
+    debug!("Создание challenge WebAuthn: user_id={}", user_id);
+
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    sqlx::query("DELETE FROM webauthn_challenges WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+    sqlx::query(
+        r#"
+        INSERT INTO webauthn_challenges (id, user_id, challenge, registration_state, created_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(challenge)
+    .bind(Json(registration))
+    .bind(Utc::now())
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при создании challenge WebAuthn: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+    tx.commit().await.map_err(AppError::from)?;
+
+    Ok(())
+}
+
+// Погашает действующую регистрацию пользователя и возвращает ее состояние. None — регистрация
+// не начиналась, уже использована или истекла
+pub async fn consume_webauthn_registration<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    executor: E,
+) -> Result<Option<PasskeyRegistration>, AppError> {
+    let registration = sqlx::query_scalar::<_, Json<PasskeyRegistration>>(
+        r#"
+        DELETE FROM webauthn_challenges
+        WHERE user_id = $1 AND expires_at > $2
+        RETURNING registration_state
+        "#,
+    )
+    .bind(user_id)
+    .bind(Utc::now())
+    .fetch_optional(executor)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при погашении challenge WebAuthn: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(registration.map(|Json(registration)| registration))
+}
+
+// Сохраняет зарегистрированный ключ доступа. Ключ с тем же ID — Conflict
+pub async fn insert_webauthn_credential<'e, E: PgExecutor<'e>>(
+    user_id: Uuid,
+    credential_id: &str,
+    passkey: &Passkey,
+    executor: E,
+) -> Result<WebauthnCredential, AppError> {
+    debug!("Сохранение ключа WebAuthn: user_id={}", user_id);
+
+    sqlx::query_as::<_, WebauthnCredential>(
+        r#"
+        INSERT INTO webauthn_credentials (id, user_id, credential_id, passkey, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, user_id, credential_id, created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(credential_id)
+    .bind(Json(passkey))
+    .bind(Utc::now())
+    .fetch_one(executor)
+    .await
+    .map_err(|err| {
+        // Проверяем ошибки нарушения ограничений
+        if let sqlx::Error::Database(ref db_err) = err {
+            if db_err.constraint() == Some("webauthn_credentials_credential_id_key") {
+                return AppError::Conflict("Ключ доступа уже зарегистрирован".to_string());
+            }
+        }
+        debug!("Ошибка при сохранении ключа WebAuthn: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })
+}
+
+// Зарегистрированные ключи пользователя (для excludeCredentials)
+pub async fn list_webauthn_passkeys(user_id: Uuid, pool: &PgPool) -> Result<Vec<Passkey>, AppError> {
+    let passkeys = sqlx::query_scalar::<_, Json<Passkey>>(
+        "SELECT passkey FROM webauthn_credentials WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|err| {
+        debug!("Ошибка при получении ключей WebAuthn: {:?}", err);
+        AppError::from(err)  // Явно указываем преобразование в AppError
+    })?;
+
+    Ok(passkeys.into_iter().map(|Json(passkey)| passkey).collect())
+}
@@ -0,0 +1,181 @@
+use crate::controllers::admin::{
+    ADMIN_RESET_PASSWORD_PATH, ADMIN_UNLOCK_USER_PATH, ADMIN_USER_AUDIT_PATH, ADMIN_USER_ROLES_PATH,
+    ADMIN_USER_SESSIONS_PATH,
+};
+use crate::controllers::user::USER_BY_ID_PATH;
+use hyper::header::{HeaderMap, HeaderValue};
+use hyper::Method;
+
+use crate::utils::path_matches;
+
+// Заголовок ответа с шаблоном маршрута, обработавшего запрос (EXPOSE_MATCHED_ROUTE)
+pub const MATCHED_ROUTE_HEADER: &str = "X-Matched-Route";
+
+// GET-маршруты, для которых токен можно передать в ?access_token= (QUERY_TOKEN_AUTH): выгрузки
+// и потоки событий, которые браузер открывает без заголовка Authorization (ссылка на скачивание,
+// EventSource). Список намеренно короткий: токен в URL может попасть в историю и логи прокси
+pub const QUERY_TOKEN_ROUTES: &[&str] = &["/api/v1/admin/users/export"];
+
+// Класс маршрута по допустимому размеру тела запроса
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BodySizeClass {
+    Auth,     // Вход, регистрация, обмен токенов
+    Standard, // Остальные маршруты
+    Bulk,     // Массовые операции
+}
+
+// Обработчик, к которому ведет маршрут. Вызов обработчика для каждого варианта — в server::handle_request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Route {
+    CreateUser,
+    Login,
+    RefreshToken,
+    RequestPasswordReset,
+    Version,
+    Features,
+    ServerTime,
+    Ping,
+    CurrentUser,
+    UpdateCurrentUser,
+    ChangePassword,
+    SecurityStatus,
+    GenerateRecoveryCodes,
+    DeactivateCurrentUser,
+    VerifyToken,
+    TokenToCookie,
+    WebauthnRegisterStart,
+    WebauthnRegisterFinish,
+    GetUser,
+    ListUsersPage,
+    AdminListUsers,
+    ExportUsers,
+    BulkUpdateStatus,
+    SessionStats,
+    ReloadConfig,
+    ResetUserPassword,
+    UnlockUser,
+    ListUserSessions,
+    RevokeUserSessions,
+    ListUserAudit,
+    GetUserRoles,
+    SetUserRoles,
+    Root,
+    Health,
+    Metrics,
+}
+
+// Строка таблицы маршрутов: метод, шаблон пути, обработчик и класс размера тела
+#[derive(Debug, Clone, Copy)]
+pub struct RouteDef {
+    pub method: &'static str,
+    pub pattern: &'static str,
+    pub route: Route,
+    pub body: BodySizeClass,
+}
+
+impl RouteDef {
+    const fn new(method: &'static str, pattern: &'static str, route: Route) -> Self {
+        RouteDef { method, pattern, route, body: BodySizeClass::Standard }
+    }
+
+    // Вход, регистрация и обмен токенов: тела крошечные, поэтому лимит строгий (MAX_BODY_AUTH_BYTES)
+    const fn auth_body(self) -> Self {
+        RouteDef { body: BodySizeClass::Auth, ..self }
+    }
+
+    // Массовые операции администратора: допускаются большие тела (MAX_BODY_BULK_BYTES)
+    const fn bulk_body(self) -> Self {
+        RouteDef { body: BodySizeClass::Bulk, ..self }
+    }
+}
+
+// Единственный список маршрутов: по нему handle_request выбирает обработчик, CORS preflight
+// перечисляет допустимые методы, X-Matched-Route получает шаблон, а лимит тела — класс.
+// Шаблоны проверяются по порядку, поэтому точные пути идут раньше шаблонов с параметрами
+// (/api/v1/users/me раньше /api/v1/users/:id). Класс тела берется из первой строки пути
+pub const ROUTES: &[RouteDef] = &[
+    // Публичные маршруты (без JWT)
+    RouteDef::new("POST", "/api/v1/users", Route::CreateUser).auth_body(),
+    RouteDef::new("POST", "/api/v1/login", Route::Login).auth_body(),
+    RouteDef::new("POST", "/api/v1/auth/refresh", Route::RefreshToken).auth_body(),
+    RouteDef::new("POST", "/api/v1/auth/password-reset", Route::RequestPasswordReset).auth_body(),
+    RouteDef::new("GET", "/api/v1/version", Route::Version),
+    RouteDef::new("GET", "/api/v1/features", Route::Features),
+    RouteDef::new("GET", "/api/v1/time", Route::ServerTime),
+    RouteDef::new("GET", "/api/v1/ping", Route::Ping),
+    // Защищенные маршруты (требуют JWT)
+    RouteDef::new("GET", "/api/v1/users/me", Route::CurrentUser),
+    RouteDef::new("PATCH", "/api/v1/users/me", Route::UpdateCurrentUser),
+    RouteDef::new("POST", "/api/v1/users/me/change-password", Route::ChangePassword),
+    RouteDef::new("GET", "/api/v1/users/me/security", Route::SecurityStatus),
+    RouteDef::new("POST", "/api/v1/users/me/2fa/recovery-codes", Route::GenerateRecoveryCodes),
+    RouteDef::new("POST", "/api/v1/users/me/deactivate", Route::DeactivateCurrentUser),
+    RouteDef::new("GET", "/api/v1/auth/verify", Route::VerifyToken),
+    RouteDef::new("POST", "/api/v1/auth/to-cookie", Route::TokenToCookie).auth_body(),
+    RouteDef::new("POST", "/api/v1/auth/webauthn/register/start", Route::WebauthnRegisterStart),
+    RouteDef::new("POST", "/api/v1/auth/webauthn/register/finish", Route::WebauthnRegisterFinish),
+    RouteDef::new("GET", USER_BY_ID_PATH, Route::GetUser),
+    // Маршруты модерации и администрирования
+    RouteDef::new("GET", "/api/v1/users", Route::ListUsersPage),
+    RouteDef::new("GET", "/api/v1/admin/users", Route::AdminListUsers),
+    RouteDef::new("GET", "/api/v1/admin/users/export", Route::ExportUsers),
+    RouteDef::new("POST", "/api/v1/admin/users/status", Route::BulkUpdateStatus).bulk_body(),
+    RouteDef::new("GET", "/api/v1/admin/sessions/stats", Route::SessionStats),
+    RouteDef::new("POST", "/api/v1/admin/broadcast", Route::ReloadConfig),
+    RouteDef::new("POST", ADMIN_RESET_PASSWORD_PATH, Route::ResetUserPassword),
+    RouteDef::new("POST", ADMIN_UNLOCK_USER_PATH, Route::UnlockUser),
+    RouteDef::new("GET", ADMIN_USER_SESSIONS_PATH, Route::ListUserSessions),
+    RouteDef::new("DELETE", ADMIN_USER_SESSIONS_PATH, Route::RevokeUserSessions),
+    RouteDef::new("GET", ADMIN_USER_AUDIT_PATH, Route::ListUserAudit),
+    RouteDef::new("GET", ADMIN_USER_ROLES_PATH, Route::GetUserRoles),
+    RouteDef::new("PUT", ADMIN_USER_ROLES_PATH, Route::SetUserRoles),
+    // Описание API, мониторинг и диагностика
+    RouteDef::new("GET", "/", Route::Root),
+    RouteDef::new("GET", "/health", Route::Health),
+    RouteDef::new("GET", "/metrics", Route::Metrics),
+    // Старый API без версии
+    RouteDef::new("POST", "/api/users", Route::CreateUser).auth_body(),
+    RouteDef::new("POST", "/api/login", Route::Login).auth_body(),
+    RouteDef::new("PATCH", "/api/users/me", Route::UpdateCurrentUser),
+];
+
+// Первая строка таблицы, шаблону которой соответствует путь (без учета метода)
+fn first_route_for_path(path: &str) -> Option<&'static RouteDef> {
+    ROUTES.iter().find(|def| path_matches(def.pattern, path))
+}
+
+// Строка таблицы, которая обрабатывает метод и путь. None — маршрутизатор ответит 404
+pub fn resolve_route(method: &Method, path: &str) -> Option<&'static RouteDef> {
+    ROUTES
+        .iter()
+        .find(|def| def.method == method.as_str() && path_matches(def.pattern, path))
+}
+
+// Класс маршрута по размеру тела для пути
+pub fn body_size_class(path: &str) -> BodySizeClass {
+    first_route_for_path(path).map(|def| def.body).unwrap_or(BodySizeClass::Standard)
+}
+
+// Методы, разрешенные для пути. None — путь не известен маршрутизатору
+pub fn allowed_methods(path: &str) -> Option<Vec<&'static str>> {
+    let pattern = first_route_for_path(path)?.pattern;
+    Some(ROUTES.iter().filter(|def| def.pattern == pattern).map(|def| def.method).collect())
+}
+
+// Шаблон маршрута (например, /api/v1/users/:id), который обрабатывает метод и путь.
+// None — маршрутизатор ответит 404
+pub fn matched_route(method: &Method, path: &str) -> Option<&'static str> {
+    resolve_route(method, path).map(|def| def.pattern)
+}
+
+// Добавляет к ответу X-Matched-Route, если запрос попал в известный маршрут
+pub fn apply_matched_route_header(headers: &mut HeaderMap, method: &Method, path: &str) {
+    if let Some(route) = matched_route(method, path) {
+        headers.insert(MATCHED_ROUTE_HEADER, HeaderValue::from_static(route));
+    }
+}
+
+// Допускает ли маршрут токен в строке запроса (только GET и только пути из QUERY_TOKEN_ROUTES)
+pub fn accepts_query_token(method: &Method, path: &str) -> bool {
+    method == Method::GET && QUERY_TOKEN_ROUTES.iter().any(|pattern| path_matches(pattern, path))
+}
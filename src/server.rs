@@ -0,0 +1,594 @@
+use futures_util::FutureExt;
+use hyper::body::Body;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Method, Request, Response, StatusCode};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::signal::ctrl_c;
+
+use crate::controllers::admin::{
+    bulk_update_status, export_users, get_user_roles, list_user_audit, session_stats, list_user_sessions, list_users, list_users_page,
+    reload_config, reset_user_password, revoke_user_sessions, set_user_roles, unlock_user,
+};
+use crate::controllers::user::{
+    change_password, create_user, deactivate_current_user, exchange_token_for_cookie, generate_recovery_codes, get_current_user,
+    get_security_status, get_user, login, refresh_token, request_password_reset, update_user, verify_token,
+};
+use crate::controllers::meta::{features, ping, root, server_time, version};
+use crate::controllers::webauthn::{webauthn_register_finish, webauthn_register_start};
+use crate::errors::{redact_server_error, AppError};
+use crate::metrics;
+use crate::middleware::auth::{auth_middleware, authorize_metrics, init_jwt_key};
+use crate::middleware::chain;
+use crate::middleware::host::{reject_body_on_get, reject_conflicting_framing, reject_oversized_body, validate_request_target};
+use crate::middleware::https::https_redirect;
+use crate::middleware::negotiation::negotiate_api_version;
+use crate::models::{init_password_policy, UserRole};
+use crate::repositories::connection::RequestConnection;
+use crate::services::audit::AuditWriter;
+use crate::routes::{apply_matched_route_header, body_size_class, matched_route, resolve_route, Route};
+use crate::services::session::active_session_stats_service;
+use crate::middleware::panic::catch_panic;
+use crate::middleware::rate_limit::RateLimiters;
+use crate::middleware::cors::apply_cors_headers;
+use crate::middleware::security::apply_security_headers;
+use crate::middleware::deadline::{log_slow_request, with_soft_deadline};
+use crate::middleware::response_limit::enforce_response_size_limit;
+use crate::config::{AppConfig, ConfigStore, REQUEST_TIMEOUT_MS};
+use crate::utils::{assign_request_id, current_request_id, RequestBodyLimit, REQUEST_ID_HEADER};
+
+// Структура с настройками и глобальными переменными приложения
+struct AppState {
+    db_pool: sqlx::PgPool,
+    start_time: std::time::Instant,
+    request_count: std::sync::atomic::AtomicUsize,
+    config: Arc<ConfigStore>, // Действующая конфигурация; перезагружается по SIGHUP и POST /api/v1/admin/broadcast
+    audit_writer: AuditWriter, // Фоновая запись аудита; передается запросам, пока включен FEATURE_ASYNC_AUDIT
+    rate_limiters: Arc<RateLimiters>, // Ограничители запросов с лимитами из конфигурации
+}
+
+// Запускает сервер и инициализирует маршрутизацию
+pub async fn run() {
+    // Логирование и переменные из .env инициализируются в main до запуска рантайма
+    log::info!("Запуск сервера WebAPI v1.0.0...");
+
+    // Получаем конфигурацию из переменных окружения с дефолтными значениями
+    // (часть настроек перезагружается без перезапуска через POST /api/v1/admin/broadcast)
+    let config_store = match AppConfig::from_env() {
+        Ok(config) => Arc::new(ConfigStore::new(config)),
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let config = config_store.load();
+
+    // Ключ проверки JWT загружается до приема запросов из уже проверенного секрета конфигурации
+    init_jwt_key(&config.jwt_secret);
+
+    let db_pool_size = config.db_pool_size;
+    let server_host = config.server_host.clone();
+    let server_port = config.server_port;
+
+    // Правила паролей компилируются до приема запросов: ошибка в PASSWORD_PATTERN фатальна
+    if let Err(e) = init_password_policy() {
+        log::error!("{}", e);
+        std::process::exit(1);
+    }
+
+    // Выводим сводку эффективной конфигурации одной строкой (секреты замаскированы)
+    log::info!("Эффективная конфигурация: {}", config.redacted_summary());
+    for warning in config.startup_warnings() {
+        log::warn!("{}", warning);
+    }
+
+    // Настройки WebAuthn проверяются при запуске: RP ID должен быть доменом WEBAUTHN_ORIGIN
+    match &config.webauthn {
+        Some(settings) => {
+            if let Err(e) = crate::services::webauthn::relying_party(settings) {
+                log::error!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        None => log::info!("WEBAUTHN_RP_ID и WEBAUTHN_ORIGIN не заданы: регистрация ключей доступа выключена"),
+    }
+
+    // Инициализируем пул соединений с PostgreSQL
+    let pool = match config
+        .pool_options()
+        .connect(&config.database_url)
+        .await
+    {
+        Ok(pool) => {
+            log::info!(
+                "Успешное подключение к базе данных (пул соединений: {})",
+                db_pool_size
+            );
+            pool
+        }
+        Err(e) => {
+            log::error!("Не удалось подключиться к PostgreSQL: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Проверяем соединение с базой данных
+    if let Err(e) = sqlx::query("SELECT 1").execute(&pool).await {
+        log::error!("Не удалось выполнить тестовый запрос к базе данных: {}", e);
+        std::process::exit(1);
+    }
+
+    // Проверяем, что схема совпадает с тем, как код пишет роли
+    if let Err(e) = crate::repositories::user::check_role_column_type(&pool).await {
+        log::error!("{}", e);
+        std::process::exit(1);
+    }
+
+    // Создаем состояние приложения
+    let app_state = Arc::new(AppState {
+        db_pool: pool.clone(),
+        start_time: std::time::Instant::now(),
+        request_count: std::sync::atomic::AtomicUsize::new(0),
+        config: Arc::clone(&config_store),
+        audit_writer: AuditWriter::from_settings(pool.clone(), &config.audit_queue),
+        rate_limiters: Arc::new(RateLimiters::from_config(&config)),
+    });
+
+    // SIGHUP перечитывает .env и применяет перезагружаемые настройки, как POST /api/v1/admin/broadcast
+    #[cfg(unix)]
+    {
+        let limiters = Arc::clone(&app_state.rate_limiters);
+        if let Err(e) = crate::config::spawn_sighup_reload(config_store, None, move |config| limiters.apply(config)) {
+            log::warn!("Не удалось подписаться на SIGHUP, перезагрузка конфигурации по сигналу недоступна: {}", e);
+        }
+    }
+
+    // Настраиваем адрес сервера
+    let addr: SocketAddr = format!("{}:{}", server_host, server_port)
+        .parse()
+        .expect("Неверный формат адреса сервера");
+
+    log::info!("Настройка сервера на адресе: {}", addr);
+
+    // Очередь аудита дописывается после остановки сервера, когда состояние уже отдано сервису
+    let audit_writer = app_state.audit_writer.clone();
+
+    // Создаём сервис Hyper с маршрутизацией
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let app_state = Arc::clone(&app_state);
+        // Адрес соединения нужен для логов и проверки доверенных прокси
+        let remote_addr = conn.remote_addr();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |mut req: Request<Body>| {
+                req.extensions_mut().insert(remote_addr);
+
+                // Увеличиваем счетчик запросов
+                app_state
+                    .request_count
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+                // ID запроса доступен обработчикам и передается в исходящие запросы;
+                // повторно присланный клиентом ID получает суффикс, чтобы внутренние логи не смешивались.
+                // Тот же ID попадает в ответы 500 (паника) и 503 (мягкий бюджет), чтобы их можно было найти в логах
+                let request_id = Some(assign_request_id(&mut req));
+
+                // Ограничиваем время выполнения запроса: сверх мягкого бюджета — 503,
+                // сверх жесткого таймаута — 408
+                let app_state = Arc::clone(&app_state);
+                // Конфигурация читается один раз: перезагрузка во время обработки запроса на него не влияет.
+                // Она же действует для сериализации ответов и ошибок (AppConfig::current)
+                let config = app_state.config.load();
+                let soft_deadline = config.soft_deadline();
+                let fut = AppConfig::scope(
+                    Arc::clone(&config),
+                    catch_panic(
+                        with_soft_deadline(handle_request(req, app_state, config), soft_deadline, request_id.clone()),
+                        request_id,
+                    ),
+                );
+                tokio::time::timeout(Duration::from_millis(REQUEST_TIMEOUT_MS), fut).map(|result| match result {
+                    Ok(response) => response,
+                    Err(_) => {
+                        log::error!("Запрос выполнялся слишком долго и был отменен");
+                        let mut response = Response::new(Body::from(
+                            r#"{"error":"Request Timeout","status":408}"#,
+                        ));
+                        *response.status_mut() = StatusCode::REQUEST_TIMEOUT;
+                        response.headers_mut().insert(
+                            hyper::header::CONTENT_TYPE,
+                            hyper::header::HeaderValue::from_static("application/json"),
+                        );
+                        Ok(response)
+                    }
+                })
+            }))
+        }
+    });
+
+    // Создаем экземпляр сервера
+    let server = hyper::Server::bind(&addr).serve(make_service);
+
+    // Настраиваем graceful shutdown
+    let server_with_shutdown = server.with_graceful_shutdown(shutdown_signal());
+
+    log::info!("Сервер успешно запущен на {}", addr);
+
+    if let Err(e) = server_with_shutdown.await {
+        log::error!("Ошибка сервера: {}", e);
+        std::process::exit(1);
+    }
+
+    // Дописываем события аудита, оставшиеся в очереди
+    audit_writer.flush().await;
+
+    log::info!("Сервер успешно завершил работу");
+}
+
+// Функция для отслеживания сигнала завершения
+async fn shutdown_signal() {
+    if let Err(e) = ctrl_c().await {
+        log::error!("Ошибка при ожидании сигнала завершения: {}", e);
+    }
+    log::info!("Получен сигнал завершения, начинаем graceful shutdown");
+}
+
+// Обрабатывает входящие запросы и маршрутизирует их
+async fn handle_request(
+    mut req: Request<Body>,
+    app_state: Arc<AppState>,
+    config: Arc<AppConfig>,
+) -> Result<Response<Body>, hyper::Error> {
+    // Проба задержки отвечает до остальной обработки (лог, CORS, проверка размера тела)
+    if resolve_route(req.method(), req.uri().path()).map(|def| def.route) == Some(Route::Ping) {
+        return Ok(ping());
+    }
+
+    // Время обработки для лога медленных запросов (SLOW_REQUEST_MS)
+    let started_at = Instant::now();
+
+    // Логируем входящий запрос (кроме проб и сбора метрик из LOG_EXCLUDE_PATHS)
+    let log_request = config.logs_request(req.uri().path());
+    if log_request {
+        log::debug!(
+            "Входящий запрос: {} {} от {}",
+            req.method(),
+            req.uri().path(),
+            req.headers()
+                .get("user-agent")
+                .and_then(|ua| ua.to_str().ok())
+                .unwrap_or("Неизвестный клиент")
+        );
+    }
+
+    // Неподдерживаемая версия HTTP, отсутствующий или не разрешенный в ALLOWED_HOSTS Host — 400
+    if let Err(response) = validate_request_target(&req, &config.allowed_hosts) {
+        return Ok(response);
+    }
+
+    // Content-Length вместе с Transfer-Encoding — 400 (защита от request smuggling)
+    if let Err(response) = reject_conflicting_framing(&req) {
+        return Ok(response);
+    }
+
+    // GET и HEAD с телом — 400 (REJECT_GET_WITH_BODY)
+    if config.reject_get_with_body {
+        if let Err(response) = reject_body_on_get(&req) {
+            return Ok(response);
+        }
+    }
+
+    // При FORCE_HTTPS запросы, пришедшие по http, переадресуются на https
+    if config.force_https {
+        if let Some(response) = https_redirect(&req, &config.trusted_proxies) {
+            log::debug!("Переадресация на https: {}", req.uri().path());
+            return Ok(response);
+        }
+    }
+
+    // Для /api/v2 и старше версия согласуется по заголовку Accept (v1 его не требует)
+    if let Err(response) = negotiate_api_version(&req) {
+        return Ok(response);
+    }
+
+    // Лимит запросов применяется к API; пробы и метрики не ограничиваются
+    if req.uri().path().starts_with("/api/") {
+        if let Err(response) = app_state
+            .rate_limiters
+            .ip
+            .check_request(&req, &config.trusted_proxies)
+        {
+            return Ok(response);
+        }
+    }
+
+    // Размер тела ограничивается по классу маршрута (MAX_BODY_AUTH_BYTES, MAX_BODY_BYTES, MAX_BODY_BULK_BYTES):
+    // по Content-Length — сразу, а тела без него (chunked) — при чтении в parse_body, которое
+    // прекращается на первом куске сверх лимита. Тот же лимит ограничивает распакованное gzip-тело
+    let body_limit = config.body_limits.for_class(body_size_class(req.uri().path()));
+    if let Err(response) = reject_oversized_body(&req, body_limit) {
+        return Ok(response);
+    }
+    req.extensions_mut().insert(RequestBodyLimit(body_limit));
+
+    // Origin и путь нужны для CORS-заголовков после обработки запроса
+    let request_origin = req
+        .headers()
+        .get(hyper::header::ORIGIN)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let request_path = req.uri().path().to_string();
+    let request_method = req.method().clone();
+    let request_id = current_request_id(&req);
+    // В ответах клиенту возвращается его собственный X-Request-ID, даже если внутренний ID получил суффикс
+    let client_request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+        .or_else(|| request_id.clone());
+
+    // Одно соединение с БД на весь запрос: берется из пула при первом обращении
+    let pool = app_state.db_pool.clone();
+    req.extensions_mut().insert(RequestConnection::new(pool.clone()));
+
+    // Обработчики и middleware читают тот же снимок конфигурации, что и сервер
+    req.extensions_mut().insert(Arc::clone(&config));
+
+    // Лимит пользователя проверяется после аутентификации, а перезагрузка конфигурации меняет лимиты
+    req.extensions_mut().insert(Arc::clone(&app_state.rate_limiters));
+
+    // С FEATURE_ASYNC_AUDIT сервисы ставят события аудита в очередь фоновой записи
+    if config.features.async_audit {
+        req.extensions_mut().insert(app_state.audit_writer.clone());
+    }
+
+    let path = req.uri().path().to_string();
+    let method = req.method().clone();
+
+    // Маршрутизация запросов по таблице routes::ROUTES (по ней же CORS preflight, X-Matched-Route
+    // и лимит тела); маршрут добавляется строкой в таблицу и вариантом Route здесь
+    let response = match resolve_route(&method, &path).map(|def| def.route) {
+        // Публичные маршруты (без JWT)
+        Some(Route::CreateUser) => create_user(req, pool).await?,
+        Some(Route::Login) => login(req, pool).await?,
+        Some(Route::RefreshToken) => refresh_token(req, pool).await?,
+        Some(Route::RequestPasswordReset) => request_password_reset(req, pool).await?,
+        Some(Route::Version) => version(req, pool).await?,
+        Some(Route::Features) => features(req, pool).await?,
+        Some(Route::ServerTime) => server_time(req, pool).await?,
+        Some(Route::Ping) => ping(),
+
+        // Защищенные маршруты (требуют JWT)
+        Some(Route::CurrentUser) => auth_middleware(req, pool.clone(), get_current_user).await?,
+        Some(Route::UpdateCurrentUser) => auth_middleware(req, pool.clone(), update_user).await?,
+        Some(Route::ChangePassword) => auth_middleware(req, pool.clone(), change_password).await?,
+        Some(Route::SecurityStatus) => auth_middleware(req, pool.clone(), get_security_status).await?,
+        Some(Route::GenerateRecoveryCodes) => auth_middleware(req, pool.clone(), generate_recovery_codes).await?,
+        Some(Route::DeactivateCurrentUser) => auth_middleware(req, pool.clone(), deactivate_current_user).await?,
+        Some(Route::VerifyToken) => auth_middleware(req, pool.clone(), verify_token).await?,
+        Some(Route::TokenToCookie) => auth_middleware(req, pool.clone(), exchange_token_for_cookie).await?,
+        Some(Route::WebauthnRegisterStart) => auth_middleware(req, pool.clone(), webauthn_register_start).await?,
+        Some(Route::WebauthnRegisterFinish) => auth_middleware(req, pool.clone(), webauthn_register_finish).await?,
+        // Маршруты с параметрами пути (ID разбирается и проверяется в обработчике).
+        // Доступ к чужому профилю зависит от роли, поэтому роль берется из БД, а не из токена
+        Some(Route::GetUser) => chain().current_role().handle(req, pool.clone(), get_user).await?,
+
+        // Маршруты администрирования (требуют JWT и роль администратора; массовая смена
+        // статуса доступна и модератору)
+        Some(Route::ListUsersPage) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), list_users_page)
+                .await?
+        }
+        Some(Route::AdminListUsers) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), list_users)
+                .await?
+        }
+        Some(Route::ExportUsers) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), export_users)
+                .await?
+        }
+        Some(Route::SessionStats) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), session_stats)
+                .await?
+        }
+        Some(Route::ReloadConfig) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), |req, pool| {
+                    reload_config(req, pool, Arc::clone(&app_state.config), Arc::clone(&app_state.rate_limiters))
+                })
+                .await?
+        }
+        Some(Route::BulkUpdateStatus) => {
+            chain()
+                .role(UserRole::Moderator)
+                .handle(req, pool.clone(), bulk_update_status)
+                .await?
+        }
+        Some(Route::ResetUserPassword) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), reset_user_password)
+                .await?
+        }
+        Some(Route::UnlockUser) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), unlock_user)
+                .await?
+        }
+        Some(Route::ListUserSessions) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), list_user_sessions)
+                .await?
+        }
+        Some(Route::ListUserAudit) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), list_user_audit)
+                .await?
+        }
+        Some(Route::GetUserRoles) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), get_user_roles)
+                .await?
+        }
+        Some(Route::SetUserRoles) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), set_user_roles)
+                .await?
+        }
+        Some(Route::RevokeUserSessions) => {
+            chain()
+                .role(UserRole::Admin)
+                .handle(req, pool.clone(), revoke_user_sessions)
+                .await?
+        }
+
+        // Описание API в корне сервиса
+        Some(Route::Root) => root(req, pool).await?,
+
+        // Пути для мониторинга и диагностики
+        Some(Route::Health) => {
+            let uptime = app_state.start_time.elapsed().as_secs();
+            let requests = app_state
+                .request_count
+                .load(std::sync::atomic::Ordering::SeqCst);
+            
+            let body = format!(
+                r#"{{"status":"OK","version":"1.0.0","uptime":{},"requests":{}}}"#,
+                uptime, requests
+            );
+            
+            let mut response = Response::new(Body::from(body));
+            response.headers_mut().insert(
+                hyper::header::CONTENT_TYPE,
+                hyper::header::HeaderValue::from_static("application/json"),
+            );
+            response
+        }
+        // Если задан METRICS_TOKEN, метрики доступны только с этим токеном
+        Some(Route::Metrics) if authorize_metrics(&req, config.metrics_token.as_deref()).is_err() => {
+            log::warn!("Отклонен запрос к /metrics без корректного токена");
+            AppError::Unauthorized.into_response(None)
+        }
+        Some(Route::Metrics) => {
+            // Простые метрики для Prometheus
+            let uptime = app_state.start_time.elapsed().as_secs();
+            let requests = app_state
+                .request_count
+                .load(std::sync::atomic::Ordering::SeqCst);
+            
+            // Недоступность БД не должна ломать сбор остальных метрик: сессии просто пропускаются
+            let session_metrics = match active_session_stats_service(&pool).await {
+                Ok(stats) => metrics::render_session_metrics(&stats),
+                Err(e) => {
+                    log::warn!("Не удалось посчитать активные сессии для метрик: {:?}", e);
+                    String::new()
+                }
+            };
+
+            let metrics = format!(
+                "# HELP api_uptime_seconds Время работы сервера в секундах\n\
+                 # TYPE api_uptime_seconds counter\n\
+                 api_uptime_seconds {}\n\
+                 # HELP api_requests_total Общее число запросов\n\
+                 # TYPE api_requests_total counter\n\
+                 api_requests_total {}\n\
+                 {}{}{}",
+                uptime, requests, metrics::render_auth_metrics(), metrics::render_audit_metrics(), session_metrics
+            );
+            
+            let mut response = Response::new(Body::from(metrics));
+            response.headers_mut().insert(
+                hyper::header::CONTENT_TYPE,
+                hyper::header::HeaderValue::from_static("text/plain"),
+            );
+            response
+        }
+
+        // OPTIONS - для поддержки CORS preflight запросов
+        None if method == Method::OPTIONS => {
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::NO_CONTENT;
+            response
+        }
+
+        // Обработка неподдерживаемых маршрутов
+        None => {
+            log::warn!("Запрос к несуществующему маршруту: {} {}", method, path);
+            let mut response = Response::new(Body::from(r#"{"error":"Not Found","code":"NOT_FOUND","status":404}"#));
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            response.headers_mut().insert(
+                hyper::header::CONTENT_TYPE,
+                hyper::header::HeaderValue::from_static("application/json"),
+            );
+            response
+        }
+    };
+
+    // Слишком большое буферизованное тело заменяется ошибкой (потоковые ответы не ограничиваются)
+    let response = enforce_response_size_limit(
+        response,
+        config.max_response_body_bytes,
+        client_request_id.as_deref(),
+    );
+
+    // Ответы 5xx не раскрывают внутренних подробностей, если ERROR_DETAIL не равен full
+    let mut response = redact_server_error(response, config.error_detail);
+
+    // Добавляем CORS заголовки по политике маршрута (служебные пути их не получают)
+    let headers = response.headers_mut();
+    apply_cors_headers(headers, &config, &request_path, request_origin.as_deref());
+
+    // Шаблон маршрута для отладки маршрутизации (EXPOSE_MATCHED_ROUTE)
+    if config.expose_matched_route {
+        apply_matched_route_header(headers, &request_method, &request_path);
+    }
+
+    // Добавляем защитные заголовки (nosniff, DENY, Referrer-Policy, CSP, опционально HSTS)
+    apply_security_headers(headers, config.hsts_max_age);
+
+    // Добавляем заголовок Content-Type, если его еще нет
+    if !headers.contains_key(hyper::header::CONTENT_TYPE) {
+        headers.insert(
+            hyper::header::CONTENT_TYPE,
+            hyper::header::HeaderValue::from_static("application/json"),
+        );
+    }
+
+    // Логируем исходящий ответ; ошибки сервера логируются и для исключенных путей
+    if log_request || response.status().is_server_error() {
+        log::debug!(
+            "Исходящий ответ: статус {} для запроса {} {}",
+            response.status(),
+            method,
+            path
+        );
+    }
+
+    log_slow_request(
+        &request_method,
+        matched_route(&request_method, &request_path).unwrap_or("unknown"),
+        response.status(),
+        started_at.elapsed(),
+        config.slow_request_threshold(),
+        request_id.as_deref(),
+    );
+
+    Ok(response)
+}
\ No newline at end of file
@@ -0,0 +1,168 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::Duration;
+use tokio::sync::mpsc::error::SendTimeoutError;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use crate::config::AuditQueueSettings;
+use crate::errors::AppError;
+use crate::metrics;
+use crate::models::{AuditEvent, NewAuditEvent};
+use crate::repositories::audit::{
+    count_user_audit_events, insert_audit_event, insert_audit_events, list_user_audit_events,
+};
+use crate::repositories::user::find_user_by_id;
+
+// Сообщение фоновому писателю: событие или запрос дописать все, что уже в очереди
+enum AuditMessage {
+    Event(NewAuditEvent),
+    Flush(oneshot::Sender<()>),
+}
+
+// Фоновая запись журнала аудита (FEATURE_ASYNC_AUDIT): сервисы кладут события в ограниченную
+// очередь, а задача run_audit_writer пишет их пачками, так что запрос не ждет INSERT.
+// Если очередь заполнена дольше enqueue_timeout или задача остановлена, событие пишется
+// напрямую — медленнее, но без потерь. Копии писателя отправляют события в одну очередь
+#[derive(Clone)]
+pub struct AuditWriter {
+    sender: mpsc::Sender<AuditMessage>,
+    enqueue_timeout: Duration,
+}
+
+// Принимающая сторона очереди для run_audit_writer
+pub struct AuditReceiver(mpsc::Receiver<AuditMessage>);
+
+impl AuditWriter {
+    // Очередь на capacity событий без запуска задачи записи
+    pub fn new(capacity: usize, enqueue_timeout: Duration) -> (Self, AuditReceiver) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        (Self { sender, enqueue_timeout }, AuditReceiver(receiver))
+    }
+
+    // Очередь и фоновая задача записи пачками до batch_size событий
+    pub fn spawn(pool: PgPool, capacity: usize, batch_size: usize, enqueue_timeout: Duration) -> Self {
+        let (writer, receiver) = Self::new(capacity, enqueue_timeout);
+        tokio::spawn(run_audit_writer(receiver, pool, batch_size));
+        writer
+    }
+
+    // Очередь и фоновая задача с настройками AUDIT_QUEUE_CAPACITY, AUDIT_BATCH_SIZE
+    // и AUDIT_ENQUEUE_TIMEOUT_MS
+    pub fn from_settings(pool: PgPool, settings: &AuditQueueSettings) -> Self {
+        Self::spawn(pool, settings.capacity, settings.batch_size, settings.enqueue_timeout())
+    }
+
+    // Писатель из extensions запроса. Сервер кладет его туда, только пока включен
+    // FEATURE_ASYNC_AUDIT, поэтому None означает запись в транзакции действия
+    pub fn from_request<B>(req: &hyper::Request<B>) -> Option<Self> {
+        req.extensions().get::<AuditWriter>().cloned()
+    }
+
+    // Ставит событие в очередь. При переполненной или закрытой очереди пишет его сразу
+    pub async fn enqueue(&self, event: NewAuditEvent, pool: &PgPool) -> Result<(), AppError> {
+        let Err(err) = self.sender.send_timeout(AuditMessage::Event(event), self.enqueue_timeout).await else {
+            return Ok(());
+        };
+        let reason = match err {
+            SendTimeoutError::Timeout(_) => "очередь аудита заполнена",
+            SendTimeoutError::Closed(_) => "фоновая запись аудита остановлена",
+        };
+        // Отправлялось событие, поэтому обратно возвращается оно же
+        let AuditMessage::Event(event) = err.into_inner() else {
+            return Ok(());
+        };
+        log::warn!("Событие аудита {} записывается напрямую: {}", event.action.as_str(), reason);
+        insert_audit_event(&event, pool).await
+    }
+
+    // Ждет, пока будут записаны все события, поставленные в очередь до вызова
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(AuditMessage::Flush(done)).await.is_ok() {
+            let _ = wait.await;
+        }
+    }
+}
+
+// Фоновая задача: забирает из очереди все готовые события (не больше batch_size) и пишет их
+// одним INSERT. Если пачка не записалась, события пишутся по одному, чтобы одно ошибочное
+// не потянуло за собой остальные
+pub async fn run_audit_writer(receiver: AuditReceiver, pool: PgPool, batch_size: usize) {
+    let AuditReceiver(mut receiver) = receiver;
+    let batch_size = batch_size.max(1);
+    let mut batch = Vec::with_capacity(batch_size);
+
+    while let Some(message) = receiver.recv().await {
+        let mut flushes = Vec::new();
+        let mut next = Some(message);
+        while let Some(message) = next.take() {
+            match message {
+                AuditMessage::Event(event) => batch.push(event),
+                AuditMessage::Flush(done) => flushes.push(done),
+            }
+            if batch.len() < batch_size {
+                next = receiver.try_recv().ok();
+            }
+        }
+
+        if let Err(e) = insert_audit_events(&batch, &pool).await {
+            log::warn!("Не удалось записать пачку событий аудита ({}): {:?}", batch.len(), e);
+            for event in &batch {
+                if let Err(e) = insert_audit_event(event, &pool).await {
+                    log::error!("Событие аудита {} потеряно: {:?}", event.action.as_str(), e);
+                    metrics::record_audit_event_lost();
+                }
+            }
+        }
+        batch.clear();
+
+        for done in flushes {
+            let _ = done.send(());
+        }
+    }
+
+    log::debug!("Фоновая запись аудита завершена");
+}
+
+// Фиксирует транзакцию действия вместе с событием аудита. С писателем (FEATURE_ASYNC_AUDIT)
+// событие ставится в очередь после фиксации: запись откладывается, но не появляется без изменения.
+// Действие к этому моменту уже выполнено, поэтому событие, которое не удалось ни поставить
+// в очередь, ни записать напрямую, логируется и учитывается в audit_events_lost_total, а не
+// превращается в ответ 500. Без писателя событие пишется в той же транзакции
+pub async fn commit_with_audit(
+    mut tx: Transaction<'_, Postgres>,
+    event: NewAuditEvent,
+    audit: Option<&AuditWriter>,
+    pool: &PgPool,
+) -> Result<(), AppError> {
+    match audit {
+        Some(writer) => {
+            tx.commit().await.map_err(AppError::from)?;
+            let action = event.action;
+            if let Err(e) = writer.enqueue(event, pool).await {
+                log::error!("Событие аудита {} потеряно после фиксации действия: {:?}", action.as_str(), e);
+                metrics::record_audit_event_lost();
+            }
+            Ok(())
+        }
+        None => {
+            insert_audit_event(&event, &mut *tx).await?;
+            tx.commit().await.map_err(AppError::from)
+        }
+    }
+}
+
+// Возвращает страницу журнала аудита одного пользователя и общее число его событий.
+// Несуществующий пользователь — NotFound, а не пустой список
+pub async fn list_user_audit_service(
+    user_id: Uuid,
+    offset: i64,
+    limit: i64,
+    pool: &PgPool,
+) -> Result<(Vec<AuditEvent>, i64), AppError> {
+    find_user_by_id(user_id, pool).await?;
+
+    let events = list_user_audit_events(user_id, offset, limit, pool).await?;
+    let total = count_user_audit_events(user_id, pool).await?;
+    Ok((events, total))
+}
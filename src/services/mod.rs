@@ -3,3 +3,18 @@
 // Объявляем подмодуль user, содержащий сервис для работы с пользователями
 pub mod user;
 
+
+// Объявляем подмодуль recovery_code, содержащий сервис кодов восстановления 2FA
+pub mod recovery_code;
+
+// Объявляем подмодуль session, содержащий сервис серверных сессий (просмотр и отзыв)
+pub mod session;
+
+// Объявляем подмодуль audit, содержащий сервис просмотра журнала аудита
+pub mod audit;
+
+// Объявляем подмодуль webauthn, содержащий регистрацию ключей доступа (passkey)
+pub mod webauthn;
+
+// Объявляем подмодуль password_reset, содержащий запрос самостоятельного сброса пароля
+pub mod password_reset;
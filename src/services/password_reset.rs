@@ -0,0 +1,71 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::config::PasswordResetLimits;
+use crate::errors::AppError;
+use crate::repositories::password_reset::{
+    count_password_reset_tokens_since, insert_password_reset_token, invalidate_old_password_reset_tokens,
+    lock_user_for_password_reset,
+};
+use crate::repositories::user::find_user_by_email;
+
+// Хеш токена сброса для хранения. Токен случайный и длинный, поэтому достаточно SHA-256
+fn hash_password_reset_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Обрабатывает запрос сброса пароля по email (POST /api/v1/auth/password-reset при
+// FEATURE_PASSWORD_RESET=true). Возвращает новый токен (32 случайных байта в hex) для отправки
+// пользователю или None, если токен не выпущен: пользователь не найден, неактивен или исчерпал
+// лимит за час. Вызывающий код отвечает одинаково во всех случаях, чтобы по ответу нельзя было
+// узнать, зарегистрирован ли email
+pub async fn request_password_reset_service(
+    email: &str,
+    limits: PasswordResetLimits,
+    pool: &PgPool,
+) -> Result<Option<String>, AppError> {
+    let user = match find_user_by_email(email, pool).await {
+        Ok(user) => user,
+        Err(AppError::NotFound(_)) | Err(AppError::Forbidden(_)) => {
+            log::info!("Сброс пароля для неизвестного или неактивного аккаунта не выполняется");
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let now = Utc::now();
+
+    // Проверка лимитов и выпуск токена выполняются под блокировкой строки пользователя
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    lock_user_for_password_reset(user.id, &mut *tx).await?;
+
+    let issued_last_hour =
+        count_password_reset_tokens_since(user.id, now - chrono::Duration::hours(1), &mut *tx).await?;
+    if issued_last_hour >= limits.max_per_hour {
+        log::warn!(
+            "Превышен лимит запросов сброса пароля для пользователя {} ({} за час)",
+            user.id,
+            issued_last_hour
+        );
+        return Ok(None);
+    }
+
+    // Новый токен занимает одно место, поэтому действующими остаются не больше max_active - 1 старых
+    let invalidated = invalidate_old_password_reset_tokens(user.id, limits.max_active - 1, &mut *tx).await?;
+
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+    let expires_at = now + chrono::Duration::minutes(limits.token_ttl_minutes);
+    insert_password_reset_token(user.id, &hash_password_reset_token(&token), expires_at, &mut *tx).await?;
+    tx.commit().await.map_err(AppError::from)?;
+
+    log::info!(
+        "Выпущен токен сброса пароля для пользователя {} (отозвано старых: {})",
+        user.id,
+        invalidated
+    );
+    Ok(Some(token))
+}
@@ -0,0 +1,86 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::repositories::recovery_code::{delete_recovery_codes, insert_recovery_code, use_recovery_code};
+use crate::repositories::user::find_user_by_id;
+
+// Количество кодов восстановления по умолчанию
+const DEFAULT_RECOVERY_CODES_COUNT: usize = 10;
+
+// Алфавит кодов без похожих символов (0/O, 1/I); 32 символа — выбор без смещения
+const RECOVERY_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+// Длина половины кода (код выдается в виде XXXXX-XXXXX)
+const RECOVERY_CODE_HALF_LENGTH: usize = 5;
+
+// Количество кодов в наборе (RECOVERY_CODES_COUNT, от 1 до 50)
+fn recovery_codes_count() -> usize {
+    env::var("RECOVERY_CODES_COUNT")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| (1..=50).contains(v))
+        .unwrap_or(DEFAULT_RECOVERY_CODES_COUNT)
+}
+
+// Генерирует случайный код вида XXXXX-XXXXX
+fn generate_recovery_code() -> String {
+    let mut code = String::with_capacity(RECOVERY_CODE_HALF_LENGTH * 2 + 1);
+    for i in 0..RECOVERY_CODE_HALF_LENGTH * 2 {
+        if i == RECOVERY_CODE_HALF_LENGTH {
+            code.push('-');
+        }
+        let index = OsRng.next_u32() as usize % RECOVERY_CODE_ALPHABET.len();
+        code.push(RECOVERY_CODE_ALPHABET[index] as char);
+    }
+    code
+}
+
+// Хеш кода для хранения. Коды случайные и длинные, поэтому достаточно SHA-256
+// (в отличие от паролей, перебор по словарю к ним неприменим).
+// Регистр, дефисы и пробелы при вводе не учитываются
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+// Выпускает новый набор кодов восстановления. Предыдущие коды перестают действовать.
+// Коды возвращаются в открытом виде только здесь — в базе хранятся лишь хеши.
+// Маршрута для выпуска нет, пока при входе нет шага 2FA: выданные коды нечем было бы погасить.
+// Выпуск и погашение подключаются к API вместе с этим шагом
+pub async fn generate_recovery_codes_service(user_id: Uuid, pool: &PgPool) -> Result<Vec<String>, AppError> {
+    // Убеждаемся, что пользователь существует
+    find_user_by_id(user_id, pool).await?;
+
+    let codes: Vec<String> = (0..recovery_codes_count()).map(|_| generate_recovery_code()).collect();
+
+    // Замена набора выполняется в транзакции, чтобы не остаться без кодов при ошибке
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    delete_recovery_codes(user_id, &mut *tx).await?;
+    for code in &codes {
+        insert_recovery_code(user_id, &hash_recovery_code(code), &mut *tx).await?;
+    }
+    tx.commit().await.map_err(AppError::from)?;
+
+    log::info!("Выпущены новые коды восстановления для пользователя {} ({} шт.)", user_id, codes.len());
+    Ok(codes)
+}
+
+// Проверяет и погашает код восстановления на шаге 2FA при входе.
+// Неверный или уже использованный код — Unauthorized
+pub async fn consume_recovery_code_service(user_id: Uuid, code: &str, pool: &PgPool) -> Result<(), AppError> {
+    if !use_recovery_code(user_id, &hash_recovery_code(code), pool).await? {
+        log::warn!("Неверный или использованный код восстановления для пользователя {}", user_id);
+        return Err(AppError::Unauthorized);
+    }
+
+    log::info!("Использован код восстановления для пользователя {}", user_id);
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{ActiveSessionStats, AuditAction, NewAuditEvent, Session, UserRole};
+use crate::repositories::session::{
+    count_active_sessions, count_active_sessions_by_role, is_session_active, list_active_sessions,
+    revoke_user_sessions,
+};
+use crate::repositories::user::find_user_by_id;
+use crate::services::audit::{commit_with_audit, AuditWriter};
+
+// Возвращает активные сессии пользователя (NotFound, если пользователя нет)
+pub async fn list_user_sessions_service(user_id: Uuid, pool: &PgPool) -> Result<Vec<Session>, AppError> {
+    find_user_by_id(user_id, pool).await?;
+    list_active_sessions(user_id, pool).await
+}
+
+// Отзывает все активные сессии пользователя от имени администратора.
+// Отзыв и запись в журнал аудита выполняются в одной транзакции
+pub async fn revoke_user_sessions_service(
+    actor_id: Uuid,
+    user_id: Uuid,
+    audit: Option<&AuditWriter>,
+    pool: &PgPool,
+) -> Result<u64, AppError> {
+    find_user_by_id(user_id, pool).await?;
+
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    let revoked = revoke_user_sessions(user_id, &mut *tx).await?;
+    let event = NewAuditEvent::new(
+        Some(actor_id),
+        Some(user_id),
+        AuditAction::SessionsRevoked,
+        Some(serde_json::json!({ "revoked": revoked })),
+    );
+    commit_with_audit(tx, event, audit, pool).await?;
+
+    log::info!("Администратор {} отозвал {} сессий пользователя {}", actor_id, revoked, user_id);
+    Ok(revoked)
+}
+
+// Проверяет сессию токена. Токены без sid (выданные до появления сессий) не проверяются
+pub async fn ensure_session_active(session_id: Option<Uuid>, pool: &PgPool) -> Result<(), AppError> {
+    let Some(session_id) = session_id else {
+        return Ok(());
+    };
+
+    if is_session_active(session_id, pool).await? {
+        Ok(())
+    } else {
+        Err(AppError::InvalidToken)
+    }
+}
+
+// Сводка активных сессий: всего и по ролям (роли без сессий включаются с нулем)
+pub async fn active_session_stats_service(pool: &PgPool) -> Result<ActiveSessionStats, AppError> {
+    let total = count_active_sessions(pool).await?;
+
+    let mut by_role: std::collections::BTreeMap<String, i64> =
+        UserRole::ALL.iter().map(|role| (role.as_str().to_string(), 0)).collect();
+    for (role, count) in count_active_sessions_by_role(pool).await? {
+        *by_role.entry(role.as_str().to_string()).or_default() += count;
+    }
+
+    Ok(ActiveSessionStats { total, by_role })
+}
@@ -1,61 +1,112 @@
 use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    password_hash::{
+        rand_core::{OsRng, RngCore},
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+    },
     Argon2,
 };
-use chrono::Utc;
-use sqlx::PgPool;
-use tokio::task;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{Stream, StreamExt};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
+use crate::config::{AppConfig, LoginLimitMode};
 use crate::models::ChangePasswordRequest;
 use crate::repositories;
 use crate::errors::AppError;
+use crate::metrics::{self, LoginFailureReason};
 
 use crate::models::{
-    AuthResponse, Claims, LoginRequest, UpdateUserRequest, User, UserRequest, UserResponse, UserRole,
+    AdminResetPasswordRequest, AdminUserResponse, AuditAction, AuthResponse, BulkStatusOutcome, BulkStatusResult, Claims,
+    DeactivationResponse, LoginRequest, NewAuditEvent, SecurityStatusResponse, TokenRefreshResponse, UpdateUserRequest, User, UserRequest,
+    UserResponse, UserRole, UserRolesRequest, UserRolesResponse,
 };
 use crate::repositories::user::{
-    create_user as create_user_repo, find_user_by_email, update_user as update_user_repo,
+    create_user as create_user_repo, email_exists, find_user_by_email, find_user_by_email_any_status,
+    update_user as update_user_repo,
 };
+use crate::services::audit::{commit_with_audit, AuditWriter};
+use crate::utils::{cpu_bound_concurrency, jwt_algorithm, map_blocking_bounded, password_hash_limiter};
 use jsonwebtoken::{encode, EncodingKey, Header};
-use std::env;
+use sha2::{Digest, Sha256};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use validator::Validate;
 
-// Константы для токенов
-const TOKEN_EXPIRY_SECONDS: i64 = 3600; // 1 час
+// Крайний срок восстановления аккаунта, деактивированного в deactivated_at
+// (window_days — REACTIVATION_WINDOW_DAYS, 0 — восстановление отключено)
+fn reactivation_deadline(deactivated_at: DateTime<Utc>, window_days: i64) -> Option<DateTime<Utc>> {
+    match window_days {
+        0 => None,
+        days => Some(deactivated_at + chrono::Duration::days(days)),
+    }
+}
+
+// Хеширует пароль с использованием Argon2id (блокирующая операция, вызывать вне async-потоков)
+fn hash_password_blocking(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let argon2 = Argon2::default();
+    
+    argon2.hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Ошибка хеширования пароля: {}", e)))
+}
+
+// Проверяет соответствие пароля хешу (блокирующая операция)
+fn verify_password_blocking(password: &str, hash: &str) -> Result<bool, AppError> {
+    let parsed_hash = match PasswordHash::new(hash) {
+        Ok(h) => h,
+        Err(e) => return Err(AppError::Internal(anyhow::anyhow!("Ошибка парсинга хеша: {}", e))),
+    };
+    
+    Ok(Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok())
+}
 
-// Хеширует пароль с использованием Argon2id
+// Хеширует пароль с использованием Argon2id. Число одновременных хеширований ограничено,
+// чтобы всплеск регистраций и входов не занял весь пул блокирующих задач
 async fn hash_password(password: String) -> Result<String, AppError> {
-    task::spawn_blocking(move || {
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-        
-        argon2.hash_password(password.as_bytes(), &salt)
-            .map(|hash| hash.to_string())
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Ошибка хеширования пароля: {}", e)))
-    })
-    .await
-    .map_err(|e| AppError::Internal(anyhow::anyhow!("Ошибка в задаче хеширования: {}", e)))?
+    password_hash_limiter()
+        .run(move || hash_password_blocking(&password))
+        .await?
 }
 
-// Проверяет соответствие пароля хешу
+// Проверяет соответствие пароля хешу (с тем же ограничением, что и хеширование)
 async fn verify_password(password: String, hash: String) -> Result<bool, AppError> {
-    task::spawn_blocking(move || {
-        let parsed_hash = match PasswordHash::new(&hash) {
-            Ok(h) => h,
-            Err(e) => return Err(AppError::Internal(anyhow::anyhow!("Ошибка парсинга хеша: {}", e))),
-        };
-        
-        Ok(Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok())
+    password_hash_limiter()
+        .run(move || verify_password_blocking(&password, &hash))
+        .await?
+}
+
+// Хеширует пакет паролей параллельно (не больше задач, чем ядер процессора).
+// Для пакетных заданий: импорт пользователей, перехеширование при миграции.
+// Результаты возвращаются в порядке входных паролей
+pub async fn hash_passwords_batch(passwords: Vec<String>) -> Vec<Result<String, AppError>> {
+    map_blocking_bounded(passwords, cpu_bound_concurrency(), |password| hash_password_blocking(&password))
+        .await
+        .into_iter()
+        .map(|result| result.and_then(|hash| hash))
+        .collect()
+}
+
+// Проверяет пакет пар (пароль, хеш) параллельно с тем же ограничением
+pub async fn verify_passwords_batch(credentials: Vec<(String, String)>) -> Vec<Result<bool, AppError>> {
+    map_blocking_bounded(credentials, cpu_bound_concurrency(), |(password, hash)| {
+        verify_password_blocking(&password, &hash)
     })
     .await
-    .map_err(|e| AppError::Internal(anyhow::anyhow!("Ошибка в задаче проверки: {}", e)))?
+    .into_iter()
+    .map(|result| result.and_then(|valid| valid))
+    .collect()
 }
 
-// Создаёт токен JWT
-fn generate_token(user_id: &Uuid, email: &str, role: UserRole) -> Result<String, AppError> {
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET должен быть задан в .env");
-    
+// Создаёт токен JWT, подписанный секретом jwt_secret из конфигурации
+fn generate_token(
+    jwt_secret: &str,
+    user_id: &Uuid,
+    email: &str,
+    role: UserRole,
+    expires_in: i64,
+    fingerprint: Option<String>,
+    session_id: Option<Uuid>,
+) -> Result<String, AppError> {
     // Текущее время в секундах
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -64,14 +115,16 @@ fn generate_token(user_id: &Uuid, email: &str, role: UserRole) -> Result<String,
     
     let claims = Claims {
         sub: user_id.to_string(),
-        exp: now + TOKEN_EXPIRY_SECONDS,
+        exp: now + expires_in,
         iat: now,
         role,
         email: email.to_string(),
+        fingerprint,
+        sid: session_id,
     };
     
     encode(
-        &Header::default(),
+        &Header::new(jwt_algorithm()),
         &claims,
         &EncodingKey::from_secret(jwt_secret.as_bytes()),
     )
@@ -80,6 +133,161 @@ fn generate_token(user_id: &Uuid, email: &str, role: UserRole) -> Result<String,
     })
 }
 
+// Хеш refresh-токена для хранения. Токен случайный и длинный, поэтому достаточно SHA-256
+fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Выпускает refresh-токен цепочки ротации family_id сроком на expiry_seconds (REFRESH_TOKEN_EXPIRY)
+// и сохраняет его хеш. Сам токен (32 случайных байта в hex) возвращается клиенту и нигде не хранится
+async fn issue_refresh_token<'e, E: sqlx::PgExecutor<'e>>(
+    user_id: Uuid,
+    family_id: Uuid,
+    session_id: Uuid,
+    expiry_seconds: i64,
+    executor: E,
+) -> Result<String, AppError> {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+
+    let expires_at = Utc::now() + chrono::Duration::seconds(expiry_seconds);
+    repositories::refresh_token::insert_refresh_token(
+        user_id,
+        family_id,
+        session_id,
+        &hash_refresh_token(&token),
+        expires_at,
+        executor,
+    )
+    .await?;
+    Ok(token)
+}
+
+// Обновляет JWT по refresh-токену с ротацией: предъявленный токен погашается, взамен
+// выдается новый той же цепочки, а сессия продлевается на срок нового JWT.
+// Повторное предъявление уже погашенного токена считается признаком кражи: вся цепочка
+// и ее сессия отзываются, и для продолжения работы нужно войти заново
+pub async fn refresh_token_service(
+    refresh_token: &str,
+    fingerprint: Option<String>,
+    config: &AppConfig,
+    pool: &PgPool,
+) -> Result<TokenRefreshResponse, AppError> {
+    let token_hash = hash_refresh_token(refresh_token);
+    let mut tx = pool.begin().await?;
+    let stored = repositories::refresh_token::find_refresh_token_for_update(&token_hash, &mut *tx)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+    if stored.revoked_at.is_some() {
+        return Err(AppError::InvalidToken);
+    }
+
+    if stored.used_at.is_some() {
+        // Отзыв фиксируется до ответа с ошибкой, иначе его откатит незавершенная транзакция
+        let revoked = repositories::refresh_token::revoke_refresh_token_family(stored.family_id, &mut *tx).await?;
+        repositories::session::revoke_session(stored.session_id, &mut *tx).await?;
+        tx.commit().await?;
+        log::warn!(
+            "Повторное использование refresh-токена пользователя {}: цепочка {} отозвана ({} токенов)",
+            stored.user_id,
+            stored.family_id,
+            revoked
+        );
+        return Err(AppError::InvalidToken);
+    }
+
+    if stored.expires_at <= Utc::now() {
+        return Err(AppError::TokenExpired);
+    }
+
+    // Деактивированный пользователь новый токен не получает
+    let user = repositories::user::find_user_by_id_with(stored.user_id, &mut *tx).await?;
+    if !user.is_active {
+        log::warn!("Попытка обновления токена деактивированного пользователя: {}", user.id);
+        return Err(AppError::Unauthorized);
+    }
+
+    // Отозванная сессия (выход, отзыв администратором) новых токенов не выдает
+    let expires_in = config.tokens.expiry_seconds(false);
+    let session_expires_at = Utc::now() + chrono::Duration::seconds(expires_in);
+    if !repositories::session::extend_session(stored.session_id, session_expires_at, &mut *tx).await? {
+        return Err(AppError::InvalidToken);
+    }
+
+    repositories::refresh_token::mark_refresh_token_used(stored.id, &mut *tx).await?;
+    let new_refresh_token = issue_refresh_token(
+        user.id,
+        stored.family_id,
+        stored.session_id,
+        config.tokens.refresh_token_expiry_seconds,
+        &mut *tx,
+    )
+    .await?;
+    tx.commit().await?;
+
+    // Привязка к отпечатку клиента (TOKEN_FINGERPRINT_BINDING) выключена по умолчанию
+    let fingerprint = fingerprint.filter(|_| config.tokens.fingerprint_binding);
+    let token = generate_token(&config.jwt_secret, &user.id, &user.email, user.role, expires_in, fingerprint, Some(stored.session_id))?;
+
+    log::info!("Токен пользователя {} обновлен по refresh-токену", user.id);
+    Ok(TokenRefreshResponse {
+        token,
+        expires_in,
+        refresh_token: new_refresh_token,
+    })
+}
+
+// Выпускает токен для cookie-сессии взамен предъявленного. Срок действия не продлевается:
+// новая сессия истекает вместе с исходным токеном. Если revoke_previous, сессия исходного
+// токена отзывается в той же транзакции, и он перестает приниматься
+pub async fn exchange_token_service(
+    claims: &Claims,
+    revoke_previous: bool,
+    config: &AppConfig,
+    pool: &PgPool,
+) -> Result<(String, i64, bool), AppError> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AppError::InvalidToken)?;
+    let expires_in = claims.exp - Utc::now().timestamp();
+    if expires_in <= 0 {
+        return Err(AppError::TokenExpired);
+    }
+
+    // Деактивированный после входа пользователь новую сессию не получает
+    let user = repositories::user::find_user_by_id(user_id, pool).await?;
+    if !user.is_active {
+        log::warn!("Попытка обмена токена деактивированного пользователя: {}", user_id);
+        return Err(AppError::Unauthorized);
+    }
+
+    let session_expires_at = Utc::now() + chrono::Duration::seconds(expires_in);
+    let mut tx = pool.begin().await?;
+    let session = repositories::session::insert_session(user.id, session_expires_at, &mut *tx).await?;
+    let bearer_revoked = match claims.sid {
+        Some(previous) if revoke_previous => repositories::session::revoke_session(previous, &mut *tx).await?,
+        _ => false,
+    };
+    tx.commit().await?;
+
+    let token = generate_token(
+        &config.jwt_secret,
+        &user.id,
+        &user.email,
+        user.role,
+        expires_in,
+        claims.fingerprint.clone(),
+        Some(session.id),
+    )?;
+
+    log::info!(
+        "Токен пользователя {} обменян на cookie-сессию (исходная сессия отозвана: {})",
+        user.id,
+        bearer_revoked
+    );
+    Ok((token, expires_in, bearer_revoked))
+}
+
 // Создаёт нового пользователя с безопасно хешированным паролем
 pub async fn create_user_service(user_request: UserRequest, pool: &PgPool) -> Result<User, AppError> {
     log::info!("Запрос на создание пользователя с email: {}", user_request.email);
@@ -92,11 +300,9 @@ pub async fn create_user_service(user_request: UserRequest, pool: &PgPool) -> Re
         })?;
     
     // Проверяем, что пользователь с таким email не существует
-    if let Ok(_) = find_user_by_email(&user_request.email, pool).await {
+    if email_exists(&user_request.email, pool).await? {
         log::warn!("Попытка создать пользователя с существующим email: {}", user_request.email);
-        return Err(AppError::Conflict(
-            format!("Пользователь с email '{}' уже существует", user_request.email)
-        ));
+        return Err(AppError::email_taken());
     }
 
     // Хешируем пароль безопасным алгоритмом Argon2id
@@ -116,6 +322,15 @@ pub async fn create_user_service(user_request: UserRequest, pool: &PgPool) -> Re
         created_at: now,
         updated_at: now,
         is_active: true,
+        name_updated_at: None,
+        age_updated_at: None,
+        failed_login_attempts: 0,
+        locked_until: None,
+        pending_email: None,
+        must_change_password: false,
+        timezone: None,
+        login_count: 0,
+        last_login_at: None,
     };
     
     let created_user = create_user_repo(&user, pool).await?;
@@ -124,8 +339,57 @@ pub async fn create_user_service(user_request: UserRequest, pool: &PgPool) -> Re
     Ok(created_user)
 }
 
-// Аутентифицирует пользователя и возвращает JWT-токен и данные
-pub async fn login_service(login_request: LoginRequest, pool: &PgPool) -> Result<AuthResponse, AppError> {
+// Аутентифицирует пользователя и возвращает JWT-токен и данные. Неудачные попытки ограничиваются
+// по LOGIN_LIMIT_MODE и лимитам из конфигурации запроса, сроки токенов берутся из нее же
+pub async fn login_service(
+    login_request: LoginRequest,
+    config: &AppConfig,
+    pool: &PgPool,
+) -> Result<AuthResponse, AppError> {
+    login_service_with_fingerprint(login_request, config, None, pool).await
+}
+
+// Аутентифицирует пользователя; если включена привязка токена, в токен записывается
+// отпечаток клиента, который затем проверяется при каждом запросе
+pub async fn login_service_with_fingerprint(
+    login_request: LoginRequest,
+    config: &AppConfig,
+    fingerprint: Option<String>,
+    pool: &PgPool,
+) -> Result<AuthResponse, AppError> {
+    login_service_classified(login_request, config, fingerprint, pool)
+        .await
+        .map_err(|failure| failure.error)
+}
+
+// Неудачный вход: ошибка для клиента и категория причины. Клиенту неизвестный email и
+// неверный пароль неотличимы, а ограничение попыток учитывает их по отдельности
+#[derive(Debug)]
+pub struct LoginFailure {
+    pub error: AppError,
+    pub reason: Option<LoginFailureReason>, // None — ошибка не связана с учетными данными (например, БД)
+}
+
+impl LoginFailure {
+    fn new(error: AppError, reason: LoginFailureReason) -> Self {
+        Self { error, reason: Some(reason) }
+    }
+}
+
+impl From<AppError> for LoginFailure {
+    fn from(error: AppError) -> Self {
+        Self { error, reason: None }
+    }
+}
+
+// Аутентифицирует пользователя как login_service_with_fingerprint, но при неудаче
+// сообщает категорию причины
+pub async fn login_service_classified(
+    login_request: LoginRequest,
+    config: &AppConfig,
+    fingerprint: Option<String>,
+    pool: &PgPool,
+) -> Result<AuthResponse, LoginFailure> {
     log::info!("Попытка входа пользователя с email: {}", login_request.email);
     
     // Валидируем данные
@@ -136,48 +400,223 @@ pub async fn login_service(login_request: LoginRequest, pool: &PgPool) -> Result
         })?;
     
     // Находим пользователя по email
-    let user = find_user_by_email(&login_request.email, pool)
+    let mut user = find_user_by_email_any_status(&login_request.email, pool)
         .await
-        .map_err(|e| {
+        .map_err(|_| {
             log::warn!("Неудачный вход: пользователь с email {} не найден", login_request.email);
+            metrics::record_login_failure(LoginFailureReason::NotFound);
             // Не раскрываем, существует ли пользователь
-            AppError::Unauthorized
+            LoginFailure::new(AppError::Unauthorized, LoginFailureReason::NotFound)
         })?;
 
+    // Проверяем временную блокировку аккаунта
+    if let Some(locked_until) = user.locked_until {
+        if locked_until > Utc::now() {
+            log::warn!("Попытка входа в заблокированный аккаунт: {} (до {})", user.email, locked_until);
+            metrics::record_login_failure(LoginFailureReason::Locked);
+            return Err(LoginFailure::new(AppError::AccountLocked(locked_until), LoginFailureReason::Locked));
+        }
+        // Блокировка истекла — начинаем отсчет неудачных попыток заново
+        repositories::user::reset_failed_logins(user.id, pool).await?;
+    }
+
     // Проверяем пароль
     let is_valid = verify_password(login_request.password, user.password_hash.clone()).await?;
     
     if !is_valid {
         log::warn!("Неудачный вход: неверный пароль для пользователя {}", user.email);
-        return Err(AppError::Unauthorized);
+        metrics::record_login_failure(LoginFailureReason::BadPassword);
+
+        // В режиме slowdown аккаунт не блокируется: ответ задерживается тем сильнее,
+        // чем больше неудачных попыток подряд
+        if config.login_limit_mode == LoginLimitMode::Slowdown {
+            repositories::user::register_failed_login(user.id, i32::MAX, Utc::now(), pool).await?;
+            let delay = config.login_slowdown.delay(user.failed_login_attempts + 1);
+            log::info!("Задержка ответа {:?} после неудачного входа для {}", delay, user.email);
+            tokio::time::sleep(delay).await;
+            return Err(LoginFailure::new(AppError::Unauthorized, LoginFailureReason::BadPassword));
+        }
+
+        let lockout = config.login_lockout;
+        let lockout_until = Utc::now() + chrono::Duration::seconds(lockout.duration_seconds);
+        let locked_until = repositories::user::register_failed_login(
+            user.id,
+            lockout.max_failed_attempts,
+            lockout_until,
+            pool,
+        )
+        .await?;
+        if let Some(until) = locked_until.filter(|until| *until > Utc::now()) {
+            log::warn!("Аккаунт {} заблокирован до {} после неудачных попыток входа", user.email, until);
+            return Err(LoginFailure::new(AppError::AccountLocked(until), LoginFailureReason::BadPassword));
+        }
+        return Err(LoginFailure::new(AppError::Unauthorized, LoginFailureReason::BadPassword));
+    }
+
+    // Успешная проверка пароля сбрасывает счетчик неудачных попыток
+    if user.failed_login_attempts > 0 {
+        repositories::user::reset_failed_logins(user.id, pool).await?;
     }
 
-    // Проверяем, что аккаунт активен
+    // Проверяем, что аккаунт активен. Аккаунт, деактивированный самим пользователем, в пределах
+    // REACTIVATION_WINDOW_DAYS восстанавливается входом с reactivate: true; без него клиент
+    // получает предложение восстановить аккаунт вместо окончательного отказа
     if !user.is_active {
-        log::warn!("Попытка входа в неактивный аккаунт: {}", user.email);
-        return Err(AppError::Forbidden("Аккаунт деактивирован".to_string()));
+        let deadline = repositories::user::find_deactivated_at(user.id, pool)
+            .await?
+            .and_then(|deactivated_at| reactivation_deadline(deactivated_at, config.reactivation_window_days))
+            .filter(|deadline| *deadline > Utc::now());
+
+        match deadline {
+            Some(_) if login_request.reactivate => {
+                repositories::user::reactivate_user(user.id, pool).await?;
+                user.is_active = true;
+                log::info!("Аккаунт восстановлен входом: {}", user.email);
+            }
+            Some(deadline) => {
+                log::info!("Вход в деактивированный аккаунт {}, восстановление доступно до {}", user.email, deadline);
+                metrics::record_login_failure(LoginFailureReason::Inactive);
+                return Err(LoginFailure::new(AppError::ReactivationAvailable(deadline), LoginFailureReason::Inactive));
+            }
+            None => {
+                log::warn!("Попытка входа в неактивный аккаунт: {}", user.email);
+                metrics::record_login_failure(LoginFailureReason::Inactive);
+                return Err(LoginFailure::new(
+                    AppError::Forbidden("Аккаунт деактивирован".to_string()),
+                    LoginFailureReason::Inactive,
+                ));
+            }
+        }
     }
 
-    // Генерируем JWT-токен
-    let token = generate_token(&user.id, &user.email, user.role)?;
+    // Учитываем вход в счетчике входов и времени последнего входа
+    (user.login_count, user.last_login_at) = repositories::user::record_successful_login(user.id, pool)
+        .await
+        .map(|(count, at)| (count, Some(at)))?;
+
+    // Создаем серверную сессию со сроком токена и генерируем JWT-токен с ее ID
+    let expires_in = config.tokens.expiry_seconds(login_request.remember_me);
+    let session_expires_at = Utc::now() + chrono::Duration::seconds(expires_in);
+    let session = repositories::session::insert_session(user.id, session_expires_at, pool).await?;
+    let fingerprint = fingerprint.filter(|_| config.tokens.fingerprint_binding);
+    let token = generate_token(&config.jwt_secret, &user.id, &user.email, user.role, expires_in, fingerprint, Some(session.id))?;
+    // Каждый вход начинает новую цепочку ротации refresh-токенов
+    let refresh_token =
+        issue_refresh_token(user.id, Uuid::new_v4(), session.id, config.tokens.refresh_token_expiry_seconds, pool).await?;
     
     log::info!("Успешный вход пользователя: {} (ID: {})", user.email, user.id);
+    metrics::record_login_success();
     
     // Создаем безопасный ответ (без пароля)
     let user_response = UserResponse::from(&user);
     
     Ok(AuthResponse {
         token,
+        expires_in,
+        refresh_token,
         user: user_response,
     })
 }
 
+// Деактивирует аккаунт по запросу пользователя: вход блокируется, все сессии отзываются,
+// событие записывается в журнал аудита вместе с изменением.
+// В течение REACTIVATION_WINDOW_DAYS аккаунт можно восстановить входом
+pub async fn deactivate_user_service(
+    user_id: Uuid,
+    reactivation_window_days: i64,
+    audit: Option<&AuditWriter>,
+    pool: &PgPool,
+) -> Result<DeactivationResponse, AppError> {
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    let deactivated_at = repositories::user::deactivate_user(user_id, &mut *tx).await?;
+    let revoked = repositories::session::revoke_user_sessions(user_id, &mut *tx).await?;
+    let event = NewAuditEvent::new(
+        Some(user_id),
+        Some(user_id),
+        AuditAction::AccountDeactivated,
+        Some(serde_json::json!({ "sessions_revoked": revoked })),
+    );
+    commit_with_audit(tx, event, audit, pool).await?;
+
+    log::info!("Аккаунт деактивирован пользователем: {} (отозвано сессий: {})", user_id, revoked);
+    Ok(DeactivationResponse {
+        deactivated_at,
+        reactivate_before: reactivation_deadline(deactivated_at, reactivation_window_days),
+    })
+}
+
+// Возвращает пользователя по ID. Обычный пользователь может получить только свой профиль,
+// модераторы и администраторы — любой. actor_role — текущая роль из БД (chain().current_role())
+pub async fn get_user_service(
+    actor_id: Uuid,
+    actor_role: UserRole,
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<User, AppError> {
+    if actor_id != user_id && actor_role == UserRole::User {
+        log::warn!("Пользователь {} запросил чужой профиль {}", actor_id, user_id);
+        return Err(AppError::Forbidden("Можно просматривать только свой профиль".to_string()));
+    }
+
+    repositories::user::find_user_by_id(user_id, pool).await
+}
+
+// Собирает сводку безопасности аккаунта. Данные сессий и кодов восстановления необязательны:
+// если их не удалось получить, поле остается пустым, а не ломает весь ответ.
+// Все чтения выполняются на одном соединении (соединении запроса)
+pub async fn security_status_service(
+    user_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<SecurityStatusResponse, AppError> {
+    let user = repositories::user::find_user_by_id_with(user_id, &mut *conn).await?;
+    let password_changed_at = repositories::user::find_password_changed_at(user_id, &mut *conn).await?;
+
+    let (active_sessions, last_login_at) = match repositories::session::session_activity(user_id, &mut *conn).await {
+        Ok((count, last_login_at)) => (Some(count), last_login_at),
+        Err(e) => {
+            log::warn!("Не удалось получить сессии пользователя {} для сводки безопасности: {:?}", user_id, e);
+            (None, None)
+        }
+    };
+    let recovery_codes_remaining = match repositories::recovery_code::count_unused_recovery_codes(user_id, &mut *conn).await {
+        Ok(count) => Some(count),
+        Err(e) => {
+            log::warn!("Не удалось получить коды восстановления {} для сводки безопасности: {:?}", user_id, e);
+            None
+        }
+    };
+
+    let now = Utc::now();
+    Ok(SecurityStatusResponse {
+        failed_login_attempts: user.failed_login_attempts,
+        locked_until: user.locked_until.filter(|until| *until > now),
+        login_count: user.login_count,
+        // Для входов до появления счетчика время берется из сессий
+        last_login_at: user.last_login_at.or(last_login_at),
+        active_sessions,
+        recovery_codes_remaining,
+        password_changed_at,
+        password_age_days: (now - password_changed_at).num_days(),
+    })
+}
+
 // Обновляет данные пользователя
 pub async fn update_user_service(
     user_id: Uuid,
     update_request: UpdateUserRequest,
     pool: &PgPool,
 ) -> Result<User, AppError> {
+    update_user_tracked_service(user_id, update_request, pool)
+        .await
+        .map(|(user, _changed)| user)
+}
+
+// Обновляет данные пользователя и сообщает, были ли фактические изменения
+pub async fn update_user_tracked_service(
+    user_id: Uuid,
+    update_request: UpdateUserRequest,
+    pool: &PgPool,
+) -> Result<(User, bool), AppError> {
     log::info!("Запрос на обновление пользователя с ID: {}", user_id);
     
     // Валидируем данные
@@ -186,12 +625,249 @@ pub async fn update_user_service(
             log::warn!("Ошибки валидации при обновлении пользователя: {:?}", e);
             AppError::from(e)
         })?;
-    
-    // Обновляем данные через репозиторий
-    let updated_user = update_user_repo(user_id, update_request, pool).await?;
+
+    // Если ни одно поле фактически не меняется, UPDATE не выполняем,
+    // чтобы не сдвигать updated_at (в том числе триггером в БД)
+    let current_user = repositories::user::find_user_by_id(user_id, pool).await?;
+    if !update_request.has_changes(&current_user) {
+        log::debug!("Обновление пользователя {} не содержит изменений, пропускаем", user_id);
+        return Ok((current_user, false));
+    }
+
+    // Новый email не должен принадлежать другому пользователю
+    let pending_email = update_request.email_change(&current_user).map(str::to_string);
+    if let Some(email) = &pending_email {
+        // Поиск не учитывает регистр: смена регистра собственного адреса конфликтом не считается
+        if matches!(find_user_by_email(email, pool).await, Ok(owner) if owner.id != user_id) {
+            log::warn!("Попытка сменить email на занятый: {}", email);
+            return Err(AppError::email_taken());
+        }
+    }
+
+    // Имя и возраст применяются сразу, email становится ожидающим подтверждения.
+    // Обе части выполняются в одной транзакции: ошибка откатывает всё
+    let has_profile_changes = update_request.has_profile_changes(&current_user);
+    let mut tx = pool.begin().await?;
+    let mut updated_user = current_user;
+
+    if has_profile_changes {
+        updated_user = update_user_repo(user_id, update_request, &mut *tx).await?;
+    }
+    if let Some(email) = &pending_email {
+        updated_user = repositories::user::set_pending_email(user_id, email, &mut *tx).await?;
+        log::info!("Пользователь с ID {} запросил смену email, ожидается подтверждение", user_id);
+    }
+
+    tx.commit().await?;
     log::info!("Пользователь с ID {} успешно обновлен", user_id);
     
-    Ok(updated_user)
+    Ok((updated_user, true))
+}
+
+// Минимальная длина строки поиска пользователей
+const MIN_SEARCH_QUERY_LENGTH: usize = 2;
+
+// Возвращает страницу пользователей и, если include_total, их общее количество (для админов).
+// Если задан search, выбираются пользователи с этой подстрокой в имени или email
+pub async fn list_users_service(
+    offset: i64,
+    limit: i64,
+    search: Option<&str>,
+    include_total: bool,
+    pool: &PgPool,
+) -> Result<(Vec<User>, Option<i64>), AppError> {
+    let search = search.map(str::trim);
+    if let Some(query) = search {
+        if query.chars().count() < MIN_SEARCH_QUERY_LENGTH {
+            return Err(AppError::BadRequest(format!(
+                "Строка поиска должна содержать не менее {} символов",
+                MIN_SEARCH_QUERY_LENGTH
+            )));
+        }
+    }
+
+    let users = repositories::user::list_users(offset, limit, search, pool).await?;
+    // Подсчет — отдельный запрос по всей таблице, поэтому выполняется только по запросу клиента
+    let total = if include_total {
+        Some(repositories::user::count_users(search, pool).await?)
+    } else {
+        None
+    };
+    Ok((users, total))
+}
+
+// Поток пользователей для экспорта: администраторское представление без секретов
+pub fn export_users_service(pool: &PgPool) -> impl Stream<Item = Result<AdminUserResponse, AppError>> + '_ {
+    repositories::user::stream_users(pool).map(|user| user.map(|user| AdminUserResponse::from(&user)))
+}
+
+// Массово изменяет статус активации пользователей в одной транзакции.
+// Администраторы при деактивации пропускаются, если не задан force
+pub async fn bulk_update_status_service(
+    ids: &[Uuid],
+    is_active: bool,
+    force: bool,
+    pool: &PgPool,
+) -> Result<Vec<BulkStatusResult>, AppError> {
+    log::info!(
+        "Массовое изменение статуса: {} пользователей, active={}, force={}",
+        ids.len(),
+        is_active,
+        force
+    );
+
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(ids.len());
+
+    for &id in ids {
+        let user = match repositories::user::find_user_by_id_with(id, &mut *tx).await {
+            Ok(user) => user,
+            Err(AppError::NotFound(_)) => {
+                results.push(BulkStatusResult { id, result: BulkStatusOutcome::NotFound });
+                continue;
+            }
+            // Любая другая ошибка откатывает всю операцию
+            Err(e) => return Err(e),
+        };
+
+        if !is_active && user.role == UserRole::Admin && !force {
+            log::warn!("Администратор {} пропущен при массовой деактивации", id);
+            results.push(BulkStatusResult { id, result: BulkStatusOutcome::SkippedAdmin });
+            continue;
+        }
+
+        repositories::user::update_user_status(id, is_active, &mut *tx).await?;
+        results.push(BulkStatusResult { id, result: BulkStatusOutcome::Updated });
+    }
+
+    tx.commit().await?;
+    log::info!("Массовое изменение статуса завершено");
+
+    Ok(results)
+}
+
+// Сбрасывает пароль пользователя администратором без проверки текущего пароля.
+// Сброс и запись в журнал аудита выполняются в одной транзакции
+pub async fn admin_reset_password_service(
+    actor_id: Uuid,
+    user_id: Uuid,
+    request: &AdminResetPasswordRequest,
+    audit: Option<&AuditWriter>,
+    pool: &PgPool,
+) -> Result<(), AppError> {
+    request.validate().map_err(AppError::from)?;
+
+    let new_password_hash = hash_password(request.new_password.clone()).await?;
+
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    repositories::user::update_user_password(
+        user_id,
+        &new_password_hash,
+        request.must_change_password,
+        &mut *tx,
+    )
+    .await?;
+    let event = NewAuditEvent::new(
+        Some(actor_id),
+        Some(user_id),
+        AuditAction::PasswordReset,
+        Some(serde_json::json!({ "must_change_password": request.must_change_password })),
+    );
+    commit_with_audit(tx, event, audit, pool).await?;
+
+    log::info!("Администратор {} сбросил пароль пользователя {}", actor_id, user_id);
+    Ok(())
+}
+
+// Разблокировка аккаунта администратором до истечения блокировки: сбрасывает счетчик
+// неудачных попыток и время блокировки, действие записывается в журнал аудита
+pub async fn unlock_user_service(
+    actor_id: Uuid,
+    user_id: Uuid,
+    audit: Option<&AuditWriter>,
+    pool: &PgPool,
+) -> Result<(), AppError> {
+    let user = repositories::user::find_user_by_id(user_id, pool).await?;
+
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    repositories::user::reset_failed_logins(user_id, &mut *tx).await?;
+    let event = NewAuditEvent::new(
+        Some(actor_id),
+        Some(user_id),
+        AuditAction::AccountUnlocked,
+        Some(serde_json::json!({
+            "failed_login_attempts": user.failed_login_attempts,
+            "locked_until": user.locked_until,
+        })),
+    );
+    commit_with_audit(tx, event, audit, pool).await?;
+
+    log::info!("Администратор {} разблокировал аккаунт пользователя {}", actor_id, user_id);
+    Ok(())
+}
+
+// Роли пользователя: основная и дополнительные
+pub async fn user_roles_service(user_id: Uuid, pool: &PgPool) -> Result<UserRolesResponse, AppError> {
+    let user = repositories::user::find_user_by_id(user_id, pool).await?;
+    let additional = repositories::user::list_additional_roles(user_id, pool).await?;
+    Ok(roles_response(&user, additional))
+}
+
+// Заменяет дополнительные роли пользователя (администратором). Основная роль не меняется,
+// поэтому старые токены и код, читающий users.role, продолжают работать. Изменение
+// записывается в журнал аудита
+pub async fn set_user_roles_service(
+    actor_id: Uuid,
+    user_id: Uuid,
+    request: &UserRolesRequest,
+    audit: Option<&AuditWriter>,
+    pool: &PgPool,
+) -> Result<UserRolesResponse, AppError> {
+    let user = repositories::user::find_user_by_id(user_id, pool).await?;
+
+    // Основная роль и повторы в дополнительные не записываются
+    let mut additional: Vec<UserRole> = Vec::new();
+    for role in &request.roles {
+        if *role != user.role && !additional.contains(role) {
+            additional.push(*role);
+        }
+    }
+
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    let previous = repositories::user::list_additional_roles(user_id, &mut *tx).await?;
+    repositories::user::delete_additional_roles(user_id, &mut *tx).await?;
+    for role in &additional {
+        repositories::user::insert_additional_role(user_id, *role, &mut *tx).await?;
+    }
+    let event = NewAuditEvent::new(
+        Some(actor_id),
+        Some(user_id),
+        AuditAction::RolesChanged,
+        Some(serde_json::json!({
+            "previous": previous.iter().map(UserRole::as_str).collect::<Vec<_>>(),
+            "roles": additional.iter().map(UserRole::as_str).collect::<Vec<_>>(),
+        })),
+    );
+    commit_with_audit(tx, event, audit, pool).await?;
+
+    log::info!(
+        "Администратор {} изменил дополнительные роли пользователя {}: {:?}",
+        actor_id,
+        user_id,
+        additional
+    );
+    Ok(roles_response(&user, additional))
+}
+
+// Ответ с ролями: основная первой, затем дополнительные
+fn roles_response(user: &User, additional: Vec<UserRole>) -> UserRolesResponse {
+    let mut roles = vec![user.role];
+    roles.extend(additional.into_iter().filter(|role| *role != user.role));
+    UserRolesResponse {
+        user_id: user.id,
+        role: user.role,
+        roles,
+    }
 }
 
 // Сменить пароль пользователя
@@ -214,7 +890,8 @@ pub async fn change_password_service(
     let new_password_hash = hash_password(request.new_password.clone()).await?;
     
     // Обновляем пароль в базе данных
-    repositories::user::update_user_password(user_id, &new_password_hash, pool).await?;
+    // Самостоятельная смена пароля снимает требование сменить его после сброса
+    repositories::user::update_user_password(user_id, &new_password_hash, false, pool).await?;
     
     Ok(())
 }
\ No newline at end of file
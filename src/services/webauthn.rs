@@ -0,0 +1,102 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+use webauthn_rs::prelude::{CreationChallengeResponse, RegisterPublicKeyCredential, Url, Webauthn, WebauthnBuilder};
+
+use crate::config::WebauthnSettings;
+use crate::errors::AppError;
+use crate::models::{WebauthnCredential, WebauthnRegisterFinishRequest};
+use crate::repositories::user::find_user_by_id;
+use crate::repositories::webauthn::{
+    consume_webauthn_registration, insert_webauthn_credential, list_webauthn_passkeys, replace_webauthn_registration,
+};
+
+// Время на завершение регистрации ключа
+const REGISTRATION_TIMEOUT_SECONDS: u64 = 300;
+
+// Проверяющая сторона WebAuthn для настроек сервиса. Ошибка означает, что RP ID не является
+// доменом origin или origin не разбирается как URL; при запуске такая конфигурация фатальна
+pub fn relying_party(settings: &WebauthnSettings) -> Result<Webauthn, AppError> {
+    let origin = Url::parse(&settings.origin)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Некорректный WEBAUTHN_ORIGIN {}: {}", settings.origin, e)))?;
+    WebauthnBuilder::new(&settings.rp_id, &origin)
+        .and_then(|builder| {
+            builder
+                .rp_name(&settings.rp_name)
+                .timeout(Duration::from_secs(REGISTRATION_TIMEOUT_SECONDS))
+                .build()
+        })
+        .map_err(|e| {
+            AppError::Internal(anyhow::anyhow!(
+                "WEBAUTHN_RP_ID {} не подходит для WEBAUTHN_ORIGIN {}: {}",
+                settings.rp_id,
+                settings.origin,
+                e
+            ))
+        })
+}
+
+// Начинает регистрацию ключа доступа: выдает параметры для navigator.credentials.create
+// и сохраняет состояние регистрации, привязанное к пользователю
+pub async fn start_registration_service(
+    user_id: Uuid,
+    settings: &WebauthnSettings,
+    pool: &PgPool,
+) -> Result<CreationChallengeResponse, AppError> {
+    let user = find_user_by_id(user_id, pool).await?;
+    let webauthn = relying_party(settings)?;
+
+    // Уже зарегистрированные ключи исключаются, чтобы один аутентификатор не регистрировался дважды
+    let exclude_credentials: Vec<_> = list_webauthn_passkeys(user_id, pool)
+        .await?
+        .iter()
+        .map(|passkey| passkey.cred_id().clone())
+        .collect();
+
+    let (options, registration) = webauthn
+        .start_passkey_registration(user.id, &user.email, &user.name, Some(exclude_credentials))
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Не удалось начать регистрацию ключа доступа: {}", e)))?;
+
+    let expires_at = Utc::now() + chrono::Duration::seconds(REGISTRATION_TIMEOUT_SECONDS as i64);
+    let challenge = URL_SAFE_NO_PAD.encode(&options.public_key.challenge);
+    replace_webauthn_registration(user_id, &challenge, &registration, expires_at, pool).await?;
+
+    log::info!("Начата регистрация ключа доступа: user_id={}", user_id);
+    Ok(options)
+}
+
+// Завершает регистрацию: погашает состояние регистрации, проверяет ответ аутентификатора
+// средствами webauthn-rs (challenge, origin, RP ID, флаги, аттестация) и сохраняет ключ
+// в одной транзакции. Отклоненный ответ тоже расходует регистрацию — ее нужно начать заново
+pub async fn finish_registration_service(
+    user_id: Uuid,
+    settings: &WebauthnSettings,
+    request: WebauthnRegisterFinishRequest,
+    pool: &PgPool,
+) -> Result<WebauthnCredential, AppError> {
+    let webauthn = relying_party(settings)?;
+    let credential = RegisterPublicKeyCredential::from(request);
+
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    let registration = consume_webauthn_registration(user_id, &mut *tx)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Регистрация ключа не начата или истекла".to_string()))?;
+    let passkey = match webauthn.finish_passkey_registration(&credential, &registration) {
+        Ok(passkey) => passkey,
+        Err(e) => {
+            // Погашение фиксируется и при отказе, чтобы ответ нельзя было подбирать к одному challenge
+            tx.commit().await.map_err(AppError::from)?;
+            return Err(AppError::BadRequest(format!("Ответ аутентификатора отклонен: {}", e)));
+        }
+    };
+
+    let credential_id = URL_SAFE_NO_PAD.encode(passkey.cred_id());
+    let stored = insert_webauthn_credential(user_id, &credential_id, &passkey, &mut *tx).await?;
+    tx.commit().await.map_err(AppError::from)?;
+
+    log::info!("Зарегистрирован ключ доступа: user_id={}, credential_id={}", user_id, credential_id);
+    Ok(stored)
+}
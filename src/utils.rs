@@ -1,11 +1,189 @@
 // Модуль вспомогательных функций для приложения
 use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use jsonwebtoken::Algorithm;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use ulid::{Generator, Ulid};
 use uuid::Uuid;
 
-// Генерирует уникальный идентификатор запроса
+use crate::config::AppConfig;
+use crate::errors::AppError;
+
+// Заголовок с идентификатором запроса (входящий и передаваемый в исходящие запросы)
+pub const REQUEST_ID_HEADER: &str = "X-Request-ID";
+
+// ID текущего запроса в extensions: из X-Request-ID или сгенерированный при приеме запроса
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+// Предельный размер тела запроса в extensions, определенный по классу маршрута при маршрутизации
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RequestBodyLimit(pub usize);
+
+// Лимит тела по умолчанию для запросов, не проходивших маршрутизацию (тесты обработчиков)
+const DEFAULT_REQUEST_BODY_LIMIT: usize = 1024 * 1024;
+
+// Предельный размер тела текущего запроса
+pub fn request_body_limit<B>(req: &hyper::Request<B>) -> usize {
+    req.extensions()
+        .get::<RequestBodyLimit>()
+        .map_or(DEFAULT_REQUEST_BODY_LIMIT, |limit| limit.0)
+}
+
+// Читает тело запроса, но не больше limit байт: на первом куске сверх лимита чтение прекращается
+// с 413, поэтому тело без Content-Length (chunked) не буферизуется целиком
+pub async fn read_body_limited(mut body: hyper::Body, limit: usize) -> Result<hyper::body::Bytes, AppError> {
+    use hyper::body::HttpBody;
+
+    let mut buffer = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| AppError::BadRequest(format!("Не удалось прочитать тело запроса: {}", e)))?;
+        if buffer.len() + chunk.len() > limit {
+            return Err(AppError::PayloadTooLarge(format!("Тело запроса больше {} байт", limit)));
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.into())
+}
+
+// ID текущего запроса: из extensions, а если запрос не проходил через сервер (тесты) —
+// из заголовка X-Request-ID
+pub fn current_request_id<B>(req: &hyper::Request<B>) -> Option<String> {
+    req.extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .or_else(|| {
+            req.headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        })
+}
+
+// Формат идентификатора запроса
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RequestIdFormat {
+    Uuid, // Случайный UUID v4 (по умолчанию)
+    Ulid, // ULID — сортируется по времени создания
+}
+
+impl RequestIdFormat {
+    // Читает формат из REQUEST_ID_FORMAT (uuid | ulid)
+    pub fn from_env() -> Self {
+        match env::var("REQUEST_ID_FORMAT") {
+            Ok(value) if value.eq_ignore_ascii_case("ulid") => RequestIdFormat::Ulid,
+            _ => RequestIdFormat::Uuid,
+        }
+    }
+}
+
+// Формат загружается из окружения один раз
+static REQUEST_ID_FORMAT: OnceLock<RequestIdFormat> = OnceLock::new();
+
+lazy_static::lazy_static! {
+    // Монотонный генератор: ULID в пределах одной миллисекунды тоже идут по возрастанию
+    static ref ULID_GENERATOR: Mutex<Generator> = Mutex::new(Generator::new());
+}
+
+// Генерирует уникальный идентификатор запроса в формате из конфигурации
 pub fn generate_request_id() -> String {
-    let uuid = Uuid::new_v4();
-    format!("{}", uuid.as_simple())
+    let format = *REQUEST_ID_FORMAT.get_or_init(RequestIdFormat::from_env);
+    generate_request_id_with(format)
+}
+
+// Генерирует идентификатор запроса в заданном формате
+pub fn generate_request_id_with(format: RequestIdFormat) -> String {
+    match format {
+        RequestIdFormat::Uuid => format!("{}", Uuid::new_v4().as_simple()),
+        RequestIdFormat::Ulid => {
+            // Ulid::default() — нулевой ULID, а не новый, поэтому unwrap_or_default здесь не подходит
+            #[allow(clippy::unwrap_or_default)]
+            let ulid = ULID_GENERATOR
+                .lock()
+                .ok()
+                .and_then(|mut generator| generator.generate().ok())
+                // При переполнении случайной части в пределах миллисекунды берем обычный ULID
+                .unwrap_or_else(Ulid::new);
+            ulid.to_string()
+        }
+    }
+}
+
+// Окно обнаружения повторного X-Request-ID по умолчанию (секунды)
+const DEFAULT_REQUEST_ID_REUSE_WINDOW_SECS: u64 = 60;
+
+// Сколько клиентских ID отслеживается одновременно, чтобы поток уникальных ID не раздувал память
+const MAX_TRACKED_REQUEST_IDS: usize = 10_000;
+
+// Обнаружение повторного X-Request-ID: клиент, присылающий один и тот же ID во многих запросах,
+// ломает сопоставление логов. Повтор в пределах окна получает внутренний ID с суффиксом (-2, -3...),
+// а в ответах по-прежнему возвращается исходный ID клиента
+pub struct RequestIdTracker {
+    window: Duration,
+    seen: Mutex<HashMap<String, (Instant, u64)>>, // ID клиента -> (первое появление в окне, число запросов)
+}
+
+impl RequestIdTracker {
+    pub fn new(window: Duration) -> Self {
+        Self { window, seen: Mutex::new(HashMap::new()) }
+    }
+
+    // Внутренний ID для X-Request-ID клиента: при первом появлении в окне — сам ID, при повторе — ID с суффиксом
+    pub fn internal_id(&self, client_id: &str, now: Instant) -> String {
+        if self.window.is_zero() {
+            return client_id.to_string();
+        }
+
+        let mut seen = self.seen.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if seen.len() >= MAX_TRACKED_REQUEST_IDS && !seen.contains_key(client_id) {
+            seen.retain(|_, (first_seen, _)| now.duration_since(*first_seen) < self.window);
+            if seen.len() >= MAX_TRACKED_REQUEST_IDS {
+                seen.clear();
+            }
+        }
+
+        match seen.get_mut(client_id) {
+            Some((first_seen, count)) if now.duration_since(*first_seen) < self.window => {
+                *count += 1;
+                let internal_id = format!("{}-{}", client_id, count);
+                log::debug!("Повторный X-Request-ID {}, внутренний ID запроса {}", client_id, internal_id);
+                internal_id
+            }
+            _ => {
+                seen.insert(client_id.to_string(), (now, 1));
+                client_id.to_string()
+            }
+        }
+    }
+}
+
+// Общий трекер; окно — REQUEST_ID_REUSE_WINDOW_SECS (0 — повторы не отслеживаются)
+pub fn request_id_tracker() -> &'static RequestIdTracker {
+    static TRACKER: OnceLock<RequestIdTracker> = OnceLock::new();
+    TRACKER.get_or_init(|| {
+        let window = env::var("REQUEST_ID_REUSE_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_REQUEST_ID_REUSE_WINDOW_SECS);
+        RequestIdTracker::new(Duration::from_secs(window))
+    })
+}
+
+// Назначает запросу внутренний ID: X-Request-ID клиента (повтор — с суффиксом) или новый, если
+// заголовка нет. ID кладется в extensions и возвращается — его же затем отдает current_request_id
+pub fn assign_request_id<B>(req: &mut hyper::Request<B>) -> String {
+    let id = match req.headers().get(REQUEST_ID_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(client_id) => request_id_tracker().internal_id(client_id, Instant::now()),
+        None => generate_request_id(),
+    };
+    req.extensions_mut().insert(RequestId(id.clone()));
+    id
 }
 
 // Возвращает текущее время с форматированием для логов
@@ -23,4 +201,440 @@ pub fn safe_substring(s: &str, start: usize, end: usize) -> &str {
     
     let real_end = if end > len { len } else { end };
     &s[start..real_end]
-}
\ No newline at end of file
+}
+// Верхняя граница размера страницы списков (размер по умолчанию — DEFAULT_PAGE_SIZE в конфигурации)
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+// Алгоритм подписи JWT из JWT_ALGORITHM. Ключ — общий секрет, поэтому допускаются только HMAC
+// (HS256, HS384, HS512); остальные значения, в том числе none и RS256, заменяются на HS256,
+// чтобы исключить подмену алгоритма (alg confusion)
+pub fn jwt_algorithm() -> Algorithm {
+    match env::var("JWT_ALGORITHM").map(|v| v.to_ascii_uppercase()).as_deref() {
+        Err(_) | Ok("HS256") => Algorithm::HS256,
+        Ok("HS384") => Algorithm::HS384,
+        Ok("HS512") => Algorithm::HS512,
+        Ok(other) => {
+            log::warn!("Недопустимый JWT_ALGORITHM={}, используется HS256", other);
+            Algorithm::HS256
+        }
+    }
+}
+
+// Секрет подписи JWT из JWT_SECRET. Пустое значение (или только пробелы) считается
+// незаданным: такой ключ позволил бы подделать любой токен
+pub fn jwt_secret() -> Result<String, String> {
+    match env::var("JWT_SECRET") {
+        Ok(secret) if !secret.trim().is_empty() => Ok(secret),
+        Ok(_) => Err("JWT_SECRET задан, но пуст".to_string()),
+        Err(_) => Err("JWT_SECRET должен быть задан в .env".to_string()),
+    }
+}
+
+// Распаковывает gzip-тело запроса. Читается не больше max_size + 1 байт, поэтому
+// тело-бомба с огромной степенью сжатия не распаковывается целиком: превышение — 413
+pub fn decompress_gzip(data: &[u8], max_size: usize) -> Result<Vec<u8>, AppError> {
+    use std::io::Read;
+
+    if max_size == 0 {
+        return Err(AppError::BadRequest("Сжатые тела запросов не принимаются".to_string()));
+    }
+
+    let mut decompressed = Vec::new();
+    flate2::read::GzDecoder::new(data)
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| AppError::BadRequest(format!("Некорректное gzip-тело запроса: {}", e)))?;
+
+    if decompressed.len() > max_size {
+        return Err(AppError::PayloadTooLarge(format!(
+            "Распакованное тело запроса превышает {} байт",
+            max_size
+        )));
+    }
+    Ok(decompressed)
+}
+
+// Форматирует время как HTTP-date (RFC 7231): Wed, 21 Oct 2015 07:28:00 GMT
+pub fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+// Разбирает HTTP-date из заголовков If-Modified-Since и подобных (None — некорректная дата)
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+// Формирует заголовок Link (RFC 5988) со ссылками first/prev/next/last
+pub fn pagination_link_header(path: &str, total: i64, offset: i64, limit: i64) -> String {
+    pagination_link_header_with_query(path, "", total, offset, limit)
+}
+
+// То же, что pagination_link_header, но сохраняет в ссылках дополнительные параметры
+// запроса (например, строку поиска). query передается уже закодированным: "q=abc"
+pub fn pagination_link_header_with_query(
+    path: &str,
+    query: &str,
+    total: i64,
+    offset: i64,
+    limit: i64,
+) -> String {
+    let last_offset = if total > 0 { (total - 1) / limit * limit } else { 0 };
+    pagination_links(path, query, offset, limit, offset + limit < total, Some(last_offset))
+}
+
+// Ссылки для страницы без подсчета общего количества: rel="last" не указывается,
+// а наличие следующей страницы определяет вызывающий (например, по заполненности текущей)
+pub fn pagination_link_header_without_total(
+    path: &str,
+    query: &str,
+    offset: i64,
+    limit: i64,
+    has_next: bool,
+) -> String {
+    pagination_links(path, query, offset, limit, has_next, None)
+}
+
+fn pagination_links(
+    path: &str,
+    query: &str,
+    offset: i64,
+    limit: i64,
+    has_next: bool,
+    last_offset: Option<i64>,
+) -> String {
+    let prefix = if query.is_empty() { String::new() } else { format!("{}&", query) };
+    let page_url = |offset: i64| format!("<{}?{}offset={}&limit={}>", path, prefix, offset, limit);
+
+    let mut links = vec![format!("{}; rel=\"first\"", page_url(0))];
+    if offset > 0 {
+        links.push(format!("{}; rel=\"prev\"", page_url((offset - limit).max(0))));
+    }
+    if has_next {
+        links.push(format!("{}; rel=\"next\"", page_url(offset + limit)));
+    }
+    if let Some(last_offset) = last_offset {
+        links.push(format!("{}; rel=\"last\"", page_url(last_offset)));
+    }
+
+    links.join(", ")
+}
+
+// Убирает из начала тела JSON метку порядка байтов UTF-8 (BOM) и пробельные символы,
+// которые добавляют некоторые редакторы и клиенты: serde_json отклоняет BOM
+pub fn strip_json_preamble(body: &[u8]) -> &[u8] {
+    let body = body.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(body);
+    let start = body.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(body.len());
+    &body[start..]
+}
+
+// Отпечаток клиента для привязки токена: SHA-256 (hex) от User-Agent и необязательного ID устройства
+pub fn client_fingerprint(user_agent: Option<&str>, device_id: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(user_agent.unwrap_or("").as_bytes());
+    // Разделитель исключает совпадение разных пар (User-Agent, ID устройства)
+    hasher.update(b"\n");
+    hasher.update(device_id.unwrap_or("").as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// Значение параметра пути по шаблону вида "/api/v1/users/:id".
+// None — путь не соответствует шаблону или в шаблоне нет такого параметра
+pub fn path_param<'a>(pattern: &str, path: &'a str, name: &str) -> Option<&'a str> {
+    let mut pattern_segments = pattern.trim_end_matches('/').split('/');
+    let mut path_segments = path.trim_end_matches('/').split('/');
+    let mut value = None;
+
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (None, None) => return value,
+            (Some(expected), Some(actual)) => match expected.strip_prefix(':') {
+                // Пустой сегмент ("/users/") не считается значением параметра
+                Some(_) if actual.is_empty() => return None,
+                Some(param) if param == name => value = Some(actual),
+                Some(_) => {}
+                None if expected == actual => {}
+                None => return None,
+            },
+            _ => return None,
+        }
+    }
+}
+
+// Соответствует ли путь шаблону вида "/api/v1/users/:id" (параметр — любой непустой сегмент)
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern_segments: Vec<&str> = pattern.trim_end_matches('/').split('/').collect();
+    let path_segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+
+    pattern_segments.len() == path_segments.len()
+        && pattern_segments
+            .iter()
+            .zip(&path_segments)
+            .all(|(expected, actual)| match expected.strip_prefix(':') {
+                Some(_) => !actual.is_empty(),
+                None => expected == actual,
+            })
+}
+
+// Параметр пути, разобранный как UUID. Некорректное значение — 400 с именем параметра,
+// чтобы все маршруты с идентификатором в пути отвечали одинаково
+pub fn path_param_uuid(pattern: &str, path: &str, name: &str) -> Result<Uuid, AppError> {
+    let value = path_param(pattern, path, name)
+        .ok_or_else(|| AppError::BadRequest(format!("Отсутствует параметр пути '{}'", name)))?;
+
+    Uuid::parse_str(value).map_err(|_| {
+        AppError::BadRequest(format!("Некорректный параметр пути '{}': ожидается UUID", name))
+    })
+}
+
+// Целочисленный параметр строки запроса. Нечисловое значение или переполнение i64 — 400
+// с именем параметра, чтобы такие запросы не доходили до БД и не превращались в 500
+pub fn query_param_i64(name: &str, value: &str) -> Result<i64, AppError> {
+    use std::num::IntErrorKind;
+
+    value.trim().parse::<i64>().map_err(|e| {
+        let reason = match e.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => "значение вне допустимого диапазона",
+            _ => "ожидается целое число",
+        };
+        AppError::BadRequest(format!("Некорректный параметр запроса '{}': {}", name, reason))
+    })
+}
+
+// Разбирает параметр частичного ответа ?fields=id,email. None — параметр не задан и
+// возвращается полный ответ. Имена принимаются и в camelCase; неизвестное имя — 400
+pub fn parse_fields_param(query: Option<&str>, known: &[&str]) -> Result<Option<Vec<String>>, AppError> {
+    let pairs: Vec<(String, String)> = serde_urlencoded::from_str(query.unwrap_or("")).unwrap_or_default();
+    let Some((_, value)) = pairs.into_iter().find(|(key, _)| key == "fields") else {
+        return Ok(None);
+    };
+
+    let mut fields = Vec::new();
+    for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let field = camel_to_snake(name);
+        if !known.contains(&field.as_str()) {
+            return Err(AppError::BadRequest(format!("Неизвестное поле в параметре fields: '{}'", name)));
+        }
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+
+    if fields.is_empty() {
+        return Err(AppError::BadRequest("Параметр fields не может быть пустым".to_string()));
+    }
+    Ok(Some(fields))
+}
+
+// Оставляет в JSON-объекте только запрошенные ключи (значения другого вида не меняются)
+pub fn project_fields(value: serde_json::Value, fields: &[String]) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter().filter(|(key, _)| fields.contains(key)).collect(),
+        ),
+        other => other,
+    }
+}
+
+// Число параллельных CPU-задач (хеширование и т.п.) — по количеству доступных ядер
+pub fn cpu_bound_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+// Выполняет блокирующую функцию для каждого элемента в пуле spawn_blocking,
+// одновременно не более limit задач. Порядок результатов совпадает с порядком элементов
+pub async fn map_blocking_bounded<T, R, F>(items: Vec<T>, limit: usize, f: F) -> Vec<Result<R, AppError>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    stream::iter(items)
+        .map(|item| {
+            let f = Arc::clone(&f);
+            async move {
+                tokio::task::spawn_blocking(move || f(item))
+                    .await
+                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Ошибка в фоновой задаче: {}", e)))
+            }
+        })
+        .buffered(limit.max(1))
+        .collect()
+        .await
+}
+
+// Время ожидания свободного слота хеширования паролей по умолчанию
+const DEFAULT_PASSWORD_HASH_QUEUE_TIMEOUT_MS: u64 = 5000;
+
+// Ограничение числа одновременных блокирующих CPU-задач. Лишние задачи ждут слота не дольше
+// queue_timeout (0 — не ждут), после чего получают 503, а не занимают пул spawn_blocking
+pub struct BlockingLimiter {
+    semaphore: Arc<Semaphore>,
+    queue_timeout: Duration,
+    in_flight: Arc<AtomicUsize>, // Выполняемые сейчас задачи
+    peak: Arc<AtomicUsize>,      // Наибольшее число одновременно выполнявшихся задач
+    rejected: AtomicU64,         // Задачи, отклоненные из-за переполнения очереди
+}
+
+impl BlockingLimiter {
+    pub fn new(limit: usize, queue_timeout: Duration) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit.max(1))),
+            queue_timeout,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            peak: Arc::new(AtomicUsize::new(0)),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    // Выполняет f в пуле spawn_blocking, когда освободится слот. Слот удерживается до конца
+    // задачи, даже если ожидающий ее запрос уже отменен
+    pub async fn run<R, F>(&self, f: F) -> Result<R, AppError>
+    where
+        R: Send + 'static,
+        F: FnOnce() -> R + Send + 'static,
+    {
+        let permit = if self.queue_timeout.is_zero() {
+            Arc::clone(&self.semaphore).try_acquire_owned().ok()
+        } else {
+            tokio::time::timeout(self.queue_timeout, Arc::clone(&self.semaphore).acquire_owned())
+                .await
+                .ok()
+                .and_then(Result::ok)
+        };
+        let Some(permit) = permit else {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            log::warn!("Нет свободного слота для CPU-задачи за {:?}, запрос отклонен", self.queue_timeout);
+            return Err(AppError::ServiceUnavailable);
+        };
+
+        let in_flight = Arc::clone(&self.in_flight);
+        let current = in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+
+        tokio::task::spawn_blocking(move || {
+            let result = f();
+            in_flight.fetch_sub(1, Ordering::Relaxed);
+            drop(permit);
+            result
+        })
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Ошибка в фоновой задаче: {}", e)))
+    }
+
+    // Задачи, выполняемые сейчас
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    // Наибольшее число одновременно выполнявшихся задач
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    // Сколько задач отклонено из-за переполнения очереди
+    pub fn rejected_count(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+// Общее ограничение для хеширования и проверки паролей: PASSWORD_HASH_CONCURRENCY задач
+// (по умолчанию по числу ядер), ожидание слота — PASSWORD_HASH_QUEUE_TIMEOUT_MS
+pub fn password_hash_limiter() -> &'static BlockingLimiter {
+    static LIMITER: OnceLock<BlockingLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| {
+        let limit = env::var("PASSWORD_HASH_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or_else(cpu_bound_concurrency);
+        let queue_timeout_ms = env::var("PASSWORD_HASH_QUEUE_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_PASSWORD_HASH_QUEUE_TIMEOUT_MS);
+        BlockingLimiter::new(limit, Duration::from_millis(queue_timeout_ms))
+    })
+}
+
+// Стиль имен полей JSON
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonCase {
+    #[default]
+    Snake, // created_at (по умолчанию, совпадает с полями структур)
+    Camel, // createdAt (для JS-клиентов)
+}
+
+impl JsonCase {
+    // Стиль из JSON_CASE конфигурации обрабатываемого запроса (вне запроса — snake_case)
+    pub fn current() -> Self {
+        AppConfig::current().map(|config| config.json_case).unwrap_or_default()
+    }
+}
+
+// created_at -> createdAt
+pub fn snake_to_camel(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut upper_next = false;
+    for c in key.chars() {
+        if c == '_' && !result.is_empty() {
+            upper_next = true;
+        } else if upper_next {
+            result.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// createdAt -> created_at
+pub fn camel_to_snake(key: &str) -> String {
+    let mut result = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_uppercase() {
+            if !result.is_empty() {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// Рекурсивно переименовывает ключи всех объектов (значения не меняются)
+pub fn rename_json_keys(value: serde_json::Value, rename: fn(&str) -> String) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (rename(&key), rename_json_keys(value, rename)))
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(|item| rename_json_keys(item, rename)).collect())
+        }
+        other => other,
+    }
+}
+
+// Сериализует тело ответа в JSON с учетом JSON_CASE обрабатываемого запроса
+pub fn to_response_json<T: serde::Serialize>(data: &T) -> serde_json::Result<String> {
+    to_response_json_with(data, JsonCase::current())
+}
+
+// Сериализует тело ответа в JSON в заданном стиле ключей (для потоковых ответов, тело которых
+// сериализуется уже после выхода из обработчика).
+// Структуры описаны в snake_case, поэтому в режиме camel ключи переименовываются после сериализации
+pub fn to_response_json_with<T: serde::Serialize>(data: &T, case: JsonCase) -> serde_json::Result<String> {
+    match case {
+        JsonCase::Snake => serde_json::to_string(data),
+        JsonCase::Camel => serde_json::to_string(&rename_json_keys(serde_json::to_value(data)?, snake_to_camel)),
+    }
+}
@@ -0,0 +1,334 @@
+use hyper::{Body, Request, StatusCode};
+use std::env;
+use uuid::Uuid;
+
+use webapi::controllers::admin::{export_users, list_user_audit, list_users, list_users_page, session_stats, unlock_user};
+use webapi::models::{AdminResetPasswordRequest, BulkStatusOutcome, LoginRequest, UserRequest, UserRole};
+use webapi::repositories::user::{find_user_by_id, update_user_role};
+use webapi::errors::AppError;
+use webapi::metrics::render_session_metrics;
+use webapi::services::session::{active_session_stats_service, ensure_session_active, list_user_sessions_service, revoke_user_sessions_service};
+use webapi::services::user::{
+    admin_reset_password_service, bulk_update_status_service, create_user_service, list_users_service,
+    login_service,
+};
+
+mod common;
+use common::TEST_DB_URL;
+
+#[tokio::test]
+async fn test_bulk_status_change() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let pool = common::setup_test_db().await;
+
+    // Создаем несколько пользователей (например, спам-аккаунты)
+    let mut ids = Vec::new();
+    for i in 0..3 {
+        let request = UserRequest {
+            name: format!("Спамер {}", i),
+            email: format!("spam{}@example.com", i),
+            password: "Password123!".to_string(),
+            age: 20,
+        };
+        ids.push(create_user_service(request, &pool).await.unwrap().id);
+    }
+
+    // Тест 1: Массовая деактивация меняет статус у всех пользователей
+    let results = bulk_update_status_service(&ids, false, false, &pool).await.unwrap();
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|r| r.result == BulkStatusOutcome::Updated));
+    for id in &ids {
+        let user = find_user_by_id(*id, &pool).await.unwrap();
+        assert!(!user.is_active);
+    }
+
+    // Тест 2: Несуществующий ID отражается в результате, остальные обрабатываются
+    let missing = Uuid::new_v4();
+    let results = bulk_update_status_service(&[ids[0], missing], true, false, &pool).await.unwrap();
+    assert_eq!(results[0].result, BulkStatusOutcome::Updated);
+    assert_eq!(results[1].id, missing);
+    assert_eq!(results[1].result, BulkStatusOutcome::NotFound);
+
+    // Тест 3: Администратор не деактивируется без force
+    let admin_request = UserRequest {
+        name: "Администратор".to_string(),
+        email: "admin@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 35,
+    };
+    let admin = create_user_service(admin_request, &pool).await.unwrap();
+    update_user_role(admin.id, UserRole::Admin, &pool).await.unwrap();
+
+    let results = bulk_update_status_service(&[admin.id], false, false, &pool).await.unwrap();
+    assert_eq!(results[0].result, BulkStatusOutcome::SkippedAdmin);
+    assert!(find_user_by_id(admin.id, &pool).await.unwrap().is_active);
+
+    // Тест 4: С force администратор деактивируется
+    let results = bulk_update_status_service(&[admin.id], false, true, &pool).await.unwrap();
+    assert_eq!(results[0].result, BulkStatusOutcome::Updated);
+    assert!(!find_user_by_id(admin.id, &pool).await.unwrap().is_active);
+
+    // Тест 5: Поиск по подстроке email без учета регистра
+    let (users, total) = list_users_service(0, 20, Some("SPAM"), true, &pool).await.unwrap();
+    assert_eq!(total, Some(3));
+    assert!(users.iter().all(|u| u.email.starts_with("spam")));
+
+    // Тест 6: Поиск по подстроке имени с пагинацией
+    let (users, total) = list_users_service(0, 1, Some("Админ"), true, &pool).await.unwrap();
+    assert_eq!(total, Some(1));
+    assert_eq!(users[0].id, admin.id);
+
+    // Тест 7: Символы % и _ ищутся буквально, а не как шаблон
+    let (users, total) = list_users_service(0, 20, Some("%_"), true, &pool).await.unwrap();
+    assert_eq!(total, Some(0));
+    assert!(users.is_empty());
+
+    // Тест 8: Слишком короткая строка поиска отклоняется
+    let result = list_users_service(0, 20, Some(" a "), true, &pool).await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    // Тест 9: Администратор сбрасывает пароль без текущего, пользователь входит с новым
+    let request = UserRequest {
+        name: "Забывчивый".to_string(),
+        email: "forgetful@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 40,
+    };
+    let user = create_user_service(request, &pool).await.unwrap();
+
+    let reset = AdminResetPasswordRequest {
+        new_password: "NewPassword456!".to_string(),
+        must_change_password: true,
+    };
+    admin_reset_password_service(admin.id, user.id, &reset, None, &pool).await.unwrap();
+
+    let login = |password: &str| LoginRequest {
+        email: "forgetful@example.com".to_string(),
+        password: password.to_string(),
+        remember_me: false,
+        reactivate: false,
+    };
+    assert!(login_service(login("Password123!"), &common::test_config(), &pool).await.is_err());
+    let auth = login_service(login("NewPassword456!"), &common::test_config(), &pool).await.unwrap();
+    assert!(auth.user.must_change_password);
+
+    // Сброс записан в журнал аудита
+    let (actor_id, action): (Uuid, String) =
+        sqlx::query_as("SELECT actor_id, action FROM audit_log WHERE user_id = $1")
+            .bind(user.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(actor_id, admin.id);
+    assert_eq!(action, "password_reset");
+
+    // Тест 10: Сброс пароля несуществующего пользователя — NotFound
+    let result = admin_reset_password_service(admin.id, Uuid::new_v4(), &reset, None, &pool).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    // Тест 11: Администратор видит активные сессии другого пользователя (вход из Теста 9)
+    let sessions = list_user_sessions_service(user.id, &pool).await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].user_id, user.id);
+
+    // Тест 12: Отзыв сессий делает их недействительными и записывается в журнал аудита
+    let revoked = revoke_user_sessions_service(admin.id, user.id, None, &pool).await.unwrap();
+    assert_eq!(revoked, 1);
+    assert!(list_user_sessions_service(user.id, &pool).await.unwrap().is_empty());
+    let result = ensure_session_active(Some(sessions[0].id), &pool).await;
+    assert!(matches!(result, Err(AppError::InvalidToken)));
+
+    let revoked_events: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE user_id = $1 AND action = 'sessions_revoked'")
+            .bind(user.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(revoked_events, 1);
+
+    // Тест 13: Сессии несуществующего пользователя — NotFound
+    let result = list_user_sessions_service(Uuid::new_v4(), &pool).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    // Тест 14: Экспорт отдает NDJSON — по одной строке-объекту на каждого пользователя
+    let request = common::with_config(Request::get("/api/v1/admin/users/export").body(Body::empty()).unwrap(), &common::test_config());
+    let response = export_users(request, pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["Content-Type"], "application/x-ndjson");
+
+    let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = String::from_utf8(body_bytes.to_vec()).unwrap();
+    assert!(body.ends_with('\n'));
+    let lines: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Каждая строка экспорта должна быть JSON"))
+        .collect();
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users").fetch_one(&pool).await.unwrap();
+    assert_eq!(lines.len() as i64, total);
+    assert!(lines.iter().all(|line| line["email"].is_string() && line.get("password_hash").is_none()));
+
+    // Тест 15: Без include_total количество не считается, а в Link нет rel="last"
+    let request = common::with_config(Request::get("/api/v1/admin/users?limit=2").body(Body::empty()).unwrap(), &common::test_config());
+    let response = list_users(request, pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let links = response.headers()["Link"].to_str().unwrap().to_string();
+    assert!(links.contains(r#"rel="next""#));
+    assert!(!links.contains(r#"rel="last""#));
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert!(body["total"].is_null());
+    assert_eq!(body["items"].as_array().unwrap().len(), 2);
+    assert!(body["items"][0]["is_active"].is_boolean());
+
+    // Тест 16: С include_total=true возвращается общее количество, параметр сохраняется в ссылках
+    let request = common::with_config(Request::get("/api/v1/admin/users?limit=2&include_total=true").body(Body::empty()).unwrap(), &common::test_config());
+    let response = list_users(request, pool.clone()).await.unwrap();
+    let links = response.headers()["Link"].to_str().unwrap().to_string();
+    assert!(links.contains("include_total=true"));
+    assert!(links.contains(r#"rel="last""#));
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body["total"].as_i64(), Some(total));
+
+    // Тест 17: Переполняющий limit — 400 с именем параметра, а не 500
+    let request = common::with_config(Request::get("/api/v1/admin/users?limit=99999999999999999999").body(Body::empty()).unwrap(), &common::test_config());
+    let response = list_users(request, pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("limit"));
+
+    // Тест 18: Заблокированный после неудачных входов аккаунт разблокируется администратором
+    let mut config = common::test_config();
+    config.login_lockout.max_failed_attempts = 2;
+    for _ in 0..2 {
+        assert!(login_service(login("WrongPassword1!"), &config, &pool).await.is_err());
+    }
+    let result = login_service(login("NewPassword456!"), &config, &pool).await;
+    assert!(matches!(result, Err(AppError::AccountLocked(_))));
+
+    let mut request = Request::post(format!("/api/v1/admin/users/{}/unlock", user.id))
+        .body(Body::empty())
+        .unwrap();
+    request.extensions_mut().insert(admin.id);
+    let response = unlock_user(request, pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let unlocked = find_user_by_id(user.id, &pool).await.unwrap();
+    assert_eq!(unlocked.failed_login_attempts, 0);
+    assert!(unlocked.locked_until.is_none());
+    assert!(login_service(login("NewPassword456!"), &config, &pool).await.is_ok());
+
+    let unlock_events: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE user_id = $1 AND action = 'account_unlocked'")
+            .bind(user.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(unlock_events, 1);
+
+    // Тест 19: Журнал аудита пользователя содержит только его события, новые первыми
+    let other = ids[0];
+    for action in ["password_reset", "sessions_revoked"] {
+        sqlx::query("INSERT INTO audit_log (id, actor_id, user_id, action) VALUES ($1, $2, $3, $4)")
+            .bind(Uuid::new_v4())
+            .bind(admin.id)
+            .bind(other)
+            .bind(action)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let audit_request = |user_id: Uuid, query: &str| {
+        let mut request = Request::get(format!("/api/v1/admin/users/{}/audit{}", user_id, query))
+            .body(Body::empty())
+            .unwrap();
+        request.extensions_mut().insert(admin.id);
+        common::with_config(request, &common::test_config())
+    };
+    let response = list_user_audit(audit_request(user.id, ""), pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    let events = body["items"].as_array().unwrap();
+    assert_eq!(body["total"].as_i64(), Some(3));
+    assert_eq!(events.len(), 3);
+    assert!(events.iter().all(|event| event["user_id"] == user.id.to_string()));
+    assert_eq!(events[0]["action"], "account_unlocked");
+
+    // Пагинация и Link по журналу пользователя
+    let response = list_user_audit(audit_request(user.id, "?limit=1&offset=1"), pool.clone()).await.unwrap();
+    assert!(response.headers()["Link"].to_str().unwrap().contains(r#"rel="next""#));
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+    assert_eq!(body["total"].as_i64(), Some(3));
+
+    // Журнал несуществующего пользователя — 404
+    let response = list_user_audit(audit_request(Uuid::new_v4(), ""), pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Тест 20: Счетчик активных сессий отражает вошедших пользователей (вход после разблокировки в Тесте 18)
+    let stats = active_session_stats_service(&pool).await.unwrap();
+    assert_eq!(stats.total, 1);
+    assert_eq!(stats.by_role["User"], 1);
+    assert_eq!(stats.by_role["Admin"], 0);
+
+    login_service(login("NewPassword456!"), &common::test_config(), &pool).await.unwrap();
+    let response = session_stats(Request::get("/api/v1/admin/sessions/stats").body(Body::empty()).unwrap(), pool.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["by_role"]["User"], 2);
+
+    // Те же значения попадают в /metrics как gauge
+    let metrics = render_session_metrics(&active_session_stats_service(&pool).await.unwrap());
+    assert!(metrics.contains("# TYPE active_sessions_total gauge"));
+    assert!(metrics.contains("active_sessions_total 2\n"));
+    assert!(metrics.contains("active_sessions{role=\"User\"} 2\n"));
+
+    // Тест 21: Полная страница пользователей с общим количеством
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users").fetch_one(&pool).await.unwrap();
+    let body = users_page("page=1&per_page=2", &pool).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["total"].as_i64(), Some(total));
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["per_page"], 2);
+    assert!(body["data"][0]["email"].is_string() && body["data"][0].get("password_hash").is_none());
+    // Элементы страницы — публичное представление UserResponse, без полей администратора
+    assert!(body["data"][0].get("is_active").is_none());
+
+    // Тест 22: Страница за пределами списка пуста, но total остается общим
+    let body = users_page("page=1000&per_page=20", &pool).await;
+    assert!(body["data"].as_array().unwrap().is_empty());
+    assert_eq!(body["total"].as_i64(), Some(total));
+
+    // Тест 23: per_page больше максимума ограничивается 100, без параметров — первая страница по 20
+    let body = users_page("per_page=1000", &pool).await;
+    assert_eq!(body["per_page"], 100);
+    assert_eq!(body["data"].as_array().unwrap().len() as i64, total.min(100));
+    let body = users_page("", &pool).await;
+    assert_eq!((body["page"].as_i64(), body["per_page"].as_i64()), (Some(1), Some(20)));
+
+    // Тест 24: Нечисловые page и per_page — 400
+    for query in ["page=abc", "per_page=ten", "page=99999999999999999999"] {
+        let request = common::with_config(Request::get(format!("/api/v1/users?{}", query)).body(Body::empty()).unwrap(), &common::test_config());
+        let response = list_users_page(request, pool.clone()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", query);
+    }
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
+
+// Запрашивает страницу пользователей и возвращает тело ответа
+async fn users_page(query: &str, pool: &sqlx::PgPool) -> serde_json::Value {
+    let request = common::with_config(Request::get(format!("/api/v1/users?{}", query)).body(Body::empty()).unwrap(), &common::test_config());
+    let response = list_users_page(request, pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap()
+}
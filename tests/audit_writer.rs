@@ -0,0 +1,71 @@
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+use webapi::config::{AuditQueueSettings, EnvVars};
+use webapi::metrics::audit_events_lost_count;
+use webapi::models::{AuditAction, NewAuditEvent};
+use webapi::services::audit::{commit_with_audit, run_audit_writer, AuditWriter};
+
+mod common;
+
+#[tokio::test]
+async fn test_async_audit_writer() {
+    let pool = common::setup_test_db().await;
+    let user_id = Uuid::new_v4();
+    let event = |action| NewAuditEvent::new(None, Some(user_id), action, Some(serde_json::json!({ "test": true })));
+
+    // Тест 1: Без писателя (FEATURE_ASYNC_AUDIT выключен) событие пишется в транзакции действия и видно сразу
+    let tx = pool.begin().await.unwrap();
+    commit_with_audit(tx, event(AuditAction::PasswordReset), None, &pool).await.unwrap();
+    assert_eq!(count_events(user_id, "password_reset", &pool).await, 1);
+
+    // Тест 2: С писателем событие ставится в очередь и со временем записывается
+    let writer = AuditWriter::from_settings(pool.clone(), &AuditQueueSettings::from_vars(&EnvVars::default()));
+    for _ in 0..3 {
+        let tx = pool.begin().await.unwrap();
+        commit_with_audit(tx, event(AuditAction::SessionsRevoked), Some(&writer), &pool).await.unwrap();
+    }
+    writer.flush().await;
+    assert_eq!(count_events(user_id, "sessions_revoked", &pool).await, 3);
+
+    // Тест 3: Переполненная очередь не теряет событие — после таймаута оно пишется напрямую
+    let (writer, receiver) = AuditWriter::new(1, Duration::from_millis(10));
+    writer.enqueue(event(AuditAction::AccountUnlocked), &pool).await.unwrap();
+    writer.enqueue(event(AuditAction::RolesChanged), &pool).await.unwrap();
+    assert_eq!(count_events(user_id, "account_unlocked", &pool).await, 0);
+    assert_eq!(count_events(user_id, "roles_changed", &pool).await, 1);
+
+    // Тест 4: Запущенная задача дописывает событие, ожидавшее в очереди
+    tokio::spawn(run_audit_writer(receiver, pool.clone(), 10));
+    writer.flush().await;
+    assert_eq!(count_events(user_id, "account_unlocked", &pool).await, 1);
+
+    // Тест 5: Остановленная задача записи — событие пишется напрямую, а не теряется
+    let (writer, receiver) = AuditWriter::new(4, Duration::from_millis(10));
+    drop(receiver);
+    writer.enqueue(event(AuditAction::RolesChanged), &pool).await.unwrap();
+    assert_eq!(count_events(user_id, "roles_changed", &pool).await, 2);
+
+    // Тест 6: Если событие не удалось ни поставить в очередь, ни записать напрямую, зафиксированное
+    // действие не превращается в ошибку, а потеря учитывается в метриках
+    let unavailable = PgPool::connect_lazy(common::TEST_DB_URL).unwrap();
+    unavailable.close().await;
+    let lost_before = audit_events_lost_count();
+    let tx = pool.begin().await.unwrap();
+    commit_with_audit(tx, event(AuditAction::AccountUnlocked), Some(&writer), &unavailable).await.unwrap();
+    assert_eq!(audit_events_lost_count(), lost_before + 1);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
+
+// Количество событий пользователя с заданным действием
+async fn count_events(user_id: Uuid, action: &str, pool: &PgPool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE user_id = $1 AND action = $2")
+        .bind(user_id)
+        .bind(action)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
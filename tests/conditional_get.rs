@@ -0,0 +1,97 @@
+use hyper::header::{IF_MODIFIED_SINCE, LAST_MODIFIED};
+use hyper::{Body, Request, StatusCode};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use std::env;
+use uuid::Uuid;
+
+use webapi::controllers::user::get_user;
+use webapi::middleware::auth::auth_middleware;
+use webapi::models::{Claims, UserRequest, UserRole};
+use webapi::services::user::create_user_service;
+use webapi::utils::{http_date, parse_http_date};
+
+mod common;
+use common::TEST_DB_URL;
+static TEST_JWT_SECRET: &str = "test_secret_key_for_jwt_token_generation";
+
+#[tokio::test]
+async fn test_get_user_if_modified_since() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", TEST_JWT_SECRET);
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Наблюдаемый".to_string(),
+        email: "watched@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 25,
+    };
+    let user = create_user_service(request, &pool).await.unwrap();
+
+    // Токен администратора без серверной сессии
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        sub: Uuid::new_v4().to_string(),
+        exp: now + 3600,
+        iat: now,
+        role: UserRole::Admin,
+        email: "admin@example.com".to_string(),
+        fingerprint: None,
+        sid: None,
+    };
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(TEST_JWT_SECRET.as_bytes())).unwrap();
+    let get = |if_modified_since: Option<String>| {
+        let mut builder = Request::builder()
+            .uri(format!("/api/v1/users/{}", user.id))
+            .header("Authorization", format!("Bearer {}", token));
+        if let Some(date) = if_modified_since {
+            builder = builder.header(IF_MODIFIED_SINCE, date);
+        }
+        builder.body(Body::empty()).unwrap()
+    };
+
+    // Тест 1: Первый запрос возвращает 200 и Last-Modified
+    let resp = auth_middleware(get(None), pool.clone(), get_user).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let last_modified = resp.headers().get(LAST_MODIFIED).unwrap().to_str().unwrap().to_string();
+    assert!(parse_http_date(&last_modified).is_some());
+
+    // Тест 2: Повтор с полученной датой — 304 без тела
+    let resp = auth_middleware(get(Some(last_modified.clone())), pool.clone(), get_user).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers().get(LAST_MODIFIED).unwrap(), last_modified.as_str());
+    assert!(hyper::body::to_bytes(resp.into_body()).await.unwrap().is_empty());
+
+    // Тест 3: Данные изменились позже указанной даты — снова 200
+    let earlier = http_date(parse_http_date(&last_modified).unwrap() - chrono::Duration::hours(1));
+    let resp = auth_middleware(get(Some(earlier)), pool.clone(), get_user).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Тест 4: Некорректная дата игнорируется
+    let resp = auth_middleware(get(Some("вчера".to_string())), pool.clone(), get_user).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Тест 5: ?fields= оставляет в ответе только запрошенные поля
+    let with_fields = |fields: &str| {
+        Request::builder()
+            .uri(format!("/api/v1/users/{}?fields={}", user.id, fields))
+            .header("Authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let resp = auth_middleware(with_fields("id,email"), pool.clone(), get_user).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(resp.into_body()).await.unwrap()).unwrap();
+    let keys: Vec<&String> = body.as_object().unwrap().keys().collect();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(body["id"], user.id.to_string());
+    assert_eq!(body["email"], "watched@example.com");
+
+    // Тест 6: Неизвестное имя поля — 400
+    let resp = auth_middleware(with_fields("id,password_hash"), pool.clone(), get_user).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
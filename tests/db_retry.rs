@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+use std::env;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use sqlx::error::{DatabaseError, ErrorKind};
+
+use webapi::errors::AppError;
+use webapi::repositories::{retry_read, retry_serialization_failure};
+
+// Ошибка обрыва соединения с БД
+fn connection_reset() -> sqlx::Error {
+    sqlx::Error::Io(io::Error::new(io::ErrorKind::ConnectionReset, "connection reset by peer"))
+}
+
+// Ошибка PostgreSQL с заданным SQLSTATE
+#[derive(Debug)]
+struct PgCodeError(&'static str);
+
+impl fmt::Display for PgCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "database error {}", self.0)
+    }
+}
+
+impl StdError for PgCodeError {}
+
+impl DatabaseError for PgCodeError {
+    fn message(&self) -> &str {
+        "could not serialize access due to concurrent update"
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self.0))
+    }
+
+    fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+fn db_error(code: &'static str) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(PgCodeError(code)))
+}
+
+#[tokio::test]
+async fn test_read_retry_on_transient_error() {
+    // Тест 1: Временная ошибка при первой попытке — чтение повторяется и завершается успешно
+    let attempts = AtomicUsize::new(0);
+    let result = retry_read(|| {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if attempt == 0 {
+                Err(connection_reset())
+            } else {
+                Ok(42)
+            }
+        }
+    })
+    .await;
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    // Тест 2: Повтор только один — устойчивая недоступность превращается в 503
+    let attempts = AtomicUsize::new(0);
+    let result: Result<i32, _> = retry_read(|| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        async { Err(connection_reset()) }
+    })
+    .await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    let error = AppError::from(result.unwrap_err());
+    assert!(matches!(error, AppError::ServiceUnavailable));
+    assert_eq!(error.into_response(None).status(), hyper::StatusCode::SERVICE_UNAVAILABLE);
+
+    // Тест 3: Обычные ошибки (например, отсутствие строки) не повторяются
+    let attempts = AtomicUsize::new(0);
+    let result: Result<i32, _> = retry_read(|| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        async { Err(sqlx::Error::RowNotFound) }
+    })
+    .await;
+    assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_write_retry_on_serialization_failure() {
+    env::remove_var("DB_RETRY_SERIALIZATION_FAILURES");
+
+    // Тест 1: Конфликт сериализации при первой попытке — запись повторяется и проходит
+    let attempts = AtomicUsize::new(0);
+    let result = retry_serialization_failure(|| {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if attempt == 0 {
+                Err(db_error("40001"))
+            } else {
+                Ok(7)
+            }
+        }
+    })
+    .await;
+    assert_eq!(result.unwrap(), 7);
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    // Тест 2: Повтор только один — повторный конфликт возвращается вызывающему
+    let attempts = AtomicUsize::new(0);
+    let result: Result<i32, _> = retry_serialization_failure(|| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        async { Err(db_error("40001")) }
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    // Тест 3: Другие ошибки БД (например, нарушение уникальности) не повторяются
+    let attempts = AtomicUsize::new(0);
+    let result: Result<i32, _> = retry_serialization_failure(|| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        async { Err(db_error("23505")) }
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+    // Тест 4: Повтор отключается через DB_RETRY_SERIALIZATION_FAILURES=false
+    env::set_var("DB_RETRY_SERIALIZATION_FAILURES", "false");
+    let attempts = AtomicUsize::new(0);
+    let result: Result<i32, _> = retry_serialization_failure(|| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        async { Err(db_error("40001")) }
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    env::remove_var("DB_RETRY_SERIALIZATION_FAILURES");
+}
@@ -0,0 +1,93 @@
+use hyper::StatusCode;
+use serde_json::Value;
+use std::env;
+
+use webapi::errors::AppError;
+use webapi::models::{LoginRequest, UserRequest};
+use webapi::repositories::user::{find_user_by_id, update_user_status};
+use webapi::services::user::{create_user_service, deactivate_user_service, login_service};
+
+mod common;
+use common::TEST_DB_URL;
+
+#[tokio::test]
+async fn test_deactivation_and_reactivation_window() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Временно Ушедший".to_string(),
+        email: "deactivated@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 41,
+    };
+    let user = create_user_service(request, &pool).await.unwrap();
+
+    let login = |reactivate: bool| LoginRequest {
+        email: "deactivated@example.com".to_string(),
+        password: "Password123!".to_string(),
+        remember_me: false,
+        reactivate,
+    };
+
+    // Тест 1: Деактивация выключает аккаунт и сообщает срок восстановления
+    login_service(login(false), &common::test_config(), &pool).await.unwrap();
+    let deactivation = deactivate_user_service(user.id, 30, None, &pool).await.unwrap();
+    let window = deactivation.reactivate_before.unwrap() - deactivation.deactivated_at;
+    assert_eq!(window.num_days(), 30);
+    assert!(!find_user_by_id(user.id, &pool).await.unwrap().is_active);
+    let active_sessions: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM user_sessions WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(active_sessions, 0);
+
+    // Тест 1.1: Деактивация записывается в журнал аудита вместе с числом отозванных сессий
+    let details: Value = sqlx::query_scalar(
+        "SELECT details FROM audit_log WHERE user_id = $1 AND actor_id = $1 AND action = 'account_deactivated'",
+    )
+    .bind(user.id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(details["sessions_revoked"], 1);
+
+    // Тест 2: Вход в пределах окна предлагает восстановление вместо окончательного отказа
+    let err = login_service(login(false), &common::test_config(), &pool).await.unwrap_err();
+    assert!(matches!(err, AppError::ReactivationAvailable(_)));
+    let response = err.into_response(None);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: Value = serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body["code"], "REACTIVATION_AVAILABLE");
+    assert_eq!(body["reactivation_available"], true);
+    assert!(body["reactivate_before"].is_string());
+
+    // Тест 3: Вход с reactivate: true восстанавливает аккаунт
+    login_service(login(true), &common::test_config(), &pool).await.unwrap();
+    assert!(find_user_by_id(user.id, &pool).await.unwrap().is_active);
+    login_service(login(false), &common::test_config(), &pool).await.unwrap();
+
+    // Тест 4: После окончания окна аккаунт не восстанавливается
+    deactivate_user_service(user.id, 30, None, &pool).await.unwrap();
+    sqlx::query("UPDATE users SET deactivated_at = NOW() - INTERVAL '31 days' WHERE id = $1")
+        .bind(user.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let result = login_service(login(true), &common::test_config(), &pool).await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+    assert!(!find_user_by_id(user.id, &pool).await.unwrap().is_active);
+
+    // Тест 5: Аккаунт, деактивированный администратором, входом не восстанавливается
+    update_user_status(user.id, true, &pool).await.unwrap();
+    deactivate_user_service(user.id, 30, None, &pool).await.unwrap();
+    update_user_status(user.id, false, &pool).await.unwrap();
+    let result = login_service(login(true), &common::test_config(), &pool).await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
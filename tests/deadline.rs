@@ -0,0 +1,64 @@
+use hyper::{Body, Method, Response, StatusCode};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+use webapi::middleware::deadline::{log_slow_request, with_soft_deadline};
+
+// Обработчик, который отвечает через заданное время
+async fn slow_handler(delay: Duration) -> Result<Response<Body>, hyper::Error> {
+    tokio::time::sleep(delay).await;
+    Ok(Response::new(Body::from("ok")))
+}
+
+#[tokio::test]
+async fn test_soft_deadline() {
+    // Тест 1: Обработчик, превысивший мягкий бюджет, прерывается с 503
+    let response = with_soft_deadline(
+        slow_handler(Duration::from_millis(500)),
+        Some(Duration::from_millis(50)),
+        Some("req-deadline-1".to_string()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body["status"], 503);
+
+    // Тест 2: Обработчик, уложившийся в бюджет, отвечает как обычно
+    let response = with_soft_deadline(slow_handler(Duration::ZERO), Some(Duration::from_millis(500)), None)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Тест 3: Без бюджета обработчик не прерывается
+    let response = with_soft_deadline(slow_handler(Duration::from_millis(100)), None, None)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_slow_request_logged() {
+    let threshold = Some(Duration::from_millis(50));
+
+    // Тест 1: Обработчик медленнее SLOW_REQUEST_MS попадает в лог медленных запросов
+    let started_at = Instant::now();
+    let response = slow_handler(Duration::from_millis(100)).await.unwrap();
+    assert!(log_slow_request(
+        &Method::GET,
+        "/api/v1/users/:id",
+        response.status(),
+        started_at.elapsed(),
+        threshold,
+        Some("req-slow-1"),
+    ));
+
+    // Тест 2: Быстрый обработчик не логируется
+    let started_at = Instant::now();
+    let response = slow_handler(Duration::ZERO).await.unwrap();
+    assert!(!log_slow_request(&Method::GET, "/api/v1/version", response.status(), started_at.elapsed(), threshold, None));
+
+    // Тест 3: Без порога медленные запросы не логируются
+    assert!(!log_slow_request(&Method::GET, "/api/v1/version", StatusCode::OK, Duration::from_secs(10), None, None));
+}
@@ -0,0 +1,88 @@
+use sqlx::Executor;
+use std::env;
+
+use webapi::errors::AppError;
+use webapi::models::{LoginRequest, UserRequest};
+use webapi::repositories::user::{email_exists, find_user_by_email};
+use webapi::services::user::{create_user_service, login_service};
+
+mod common;
+use common::TEST_DB_URL;
+
+#[tokio::test]
+async fn test_email_lookup_is_case_insensitive() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Смешанный Регистр".to_string(),
+        email: "Mixed.Case@Example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 30,
+    };
+    let user = create_user_service(request, &pool).await.unwrap();
+
+    // Тест 1: Поиск находит пользователя независимо от регистра адреса
+    let found = find_user_by_email("mixed.case@EXAMPLE.COM", &pool).await.unwrap();
+    assert_eq!(found.id, user.id);
+
+    // Тест 2: Вход с адресом в другом регистре успешен
+    let login = LoginRequest {
+        email: "MIXED.CASE@example.com".to_string(),
+        password: "Password123!".to_string(),
+        remember_me: false,
+        reactivate: false,
+    };
+    assert_eq!(login_service(login, &common::test_config(), &pool).await.unwrap().user.id, user.id);
+
+    // Тест 3: Адрес, отличающийся только регистром, считается занятым
+    let duplicate = UserRequest {
+        name: "Двойник".to_string(),
+        email: "mixed.case@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 30,
+    };
+    let result = create_user_service(duplicate, &pool).await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    // Тест 4: Условие поиска использует функциональный индекс, а не последовательное сканирование
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query("SET LOCAL enable_seqscan = off").execute(&mut *tx).await.unwrap();
+    let plan: Vec<String> = sqlx::query_scalar("EXPLAIN SELECT id FROM users WHERE LOWER(email) = LOWER($1)")
+        .bind("mixed.case@example.com")
+        .fetch_all(&mut *tx)
+        .await
+        .unwrap();
+    tx.rollback().await.unwrap();
+    assert!(plan.join("\n").contains("users_email_lower_key"), "План запроса: {:?}", plan);
+
+    // Тест 5: Проверка существования email без чтения строки учитывает регистр так же, как поиск
+    assert!(email_exists("MIXED.CASE@example.com", &pool).await.unwrap());
+    assert!(!email_exists("missing@example.com", &pool).await.unwrap());
+
+    // Тест 6: На базе с адресами, отличающимися только регистром, миграция индекса останавливается
+    // с перечнем конфликтов, а не падает на создании индекса
+    let other = UserRequest {
+        name: "Другой".to_string(),
+        email: "other@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 30,
+    };
+    let other = create_user_service(other, &pool).await.unwrap();
+    sqlx::query("DROP INDEX users_email_lower_key").execute(&pool).await.unwrap();
+    sqlx::query("UPDATE users SET email = 'MIXED.case@example.com' WHERE id = $1")
+        .bind(other.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let err = pool
+        .execute(include_str!("../migrations/0010_add_lower_email_index.sql"))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("mixed.case@example.com (2)"), "{}", err);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
@@ -0,0 +1,124 @@
+use hyper::StatusCode;
+use std::sync::Arc;
+use validator::Validate;
+
+use webapi::config::{AppConfig, ErrorDetail};
+use webapi::errors::{redact_server_error, AppError};
+use webapi::models::UserRequest;
+
+mod common;
+
+// Тело ответа с ошибкой в виде JSON
+async fn error_body(error: AppError) -> serde_json::Value {
+    let response = error.into_response(None);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_field_errors_are_capped() {
+    let many_errors = || {
+        AppError::validation_errors(
+            (0..50)
+                .map(|i| (format!("field_{}", i), "Некорректное значение".to_string()))
+                .collect(),
+        )
+    };
+
+    // Тест 1: По умолчанию в ответ попадает не более 20 ошибок полей, усечение отмечено флагом
+    let body = error_body(many_errors()).await;
+    let field_errors = body["field_errors"].as_array().unwrap();
+    assert_eq!(field_errors.len(), 20);
+    assert_eq!(field_errors[0]["field"], "field_0");
+    assert_eq!(field_errors[0]["message"], "Некорректное значение");
+    assert_eq!(body["field_errors_truncated"], true);
+    assert!(!body["details"].as_str().unwrap().contains("field_20"));
+
+    // Тест 2: Предел задается через MAX_FIELD_ERRORS в конфигурации запроса
+    let mut config = common::test_config();
+    config.max_field_errors = 5;
+    let body = AppConfig::scope(Arc::new(config), error_body(many_errors())).await;
+    assert_eq!(body["field_errors"].as_array().unwrap().len(), 5);
+
+    // Тест 3: Без усечения флаг в ответ не добавляется
+    let few = AppError::validation_errors(vec![("email".to_string(), "Некорректный email".to_string())]);
+    let body = error_body(few).await;
+    assert_eq!(body["field_errors"].as_array().unwrap().len(), 1);
+    assert!(body.get("field_errors_truncated").is_none());
+}
+
+#[tokio::test]
+async fn test_server_error_detail_modes() {
+    let internal = || AppError::Internal(anyhow::anyhow!("пул соединений исчерпан: db-primary:5432"));
+    let body_of = |error: AppError, detail: ErrorDetail| async move {
+        let response = redact_server_error(error.into_response(Some("req-500")), detail);
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        serde_json::from_slice::<serde_json::Value>(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap()
+    };
+
+    // Тест 1: В режиме full ответ 500 содержит подробности и время
+    let body = body_of(internal(), ErrorDetail::Full).await;
+    assert_eq!(body["status"], 500);
+    assert_eq!(body["trace_id"], "req-500");
+    assert!(body["details"].as_str().unwrap().contains("db-primary"));
+    assert!(body["timestamp"].is_string());
+
+    // Тест 2: В режиме minimal остаются статус, общее сообщение и trace_id
+    let body = body_of(internal(), ErrorDetail::Minimal).await;
+    assert_eq!(body["status"], 500);
+    assert_eq!(body["code"], "INTERNAL_ERROR");
+    assert_eq!(body["message"], "Внутренняя ошибка сервера");
+    assert_eq!(body["trace_id"], "req-500");
+    assert!(body.get("details").is_none());
+    assert!(body.get("timestamp").is_none());
+    assert!(!body.to_string().contains("db-primary"));
+
+    // Тест 3: Ошибка БД в режиме minimal не раскрывает свой тип
+    let body = body_of(AppError::Database(sqlx::Error::PoolTimedOut), ErrorDetail::Minimal).await;
+    assert_eq!(body["error"], "InternalServerError");
+    assert_eq!(body["code"], "INTERNAL_ERROR");
+
+    // Тест 4: Ответы 4xx не меняются и в режиме minimal
+    let response = redact_server_error(AppError::NotFound("пользователь".to_string()).into_response(None), ErrorDetail::Minimal);
+    let body: serde_json::Value = serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body["code"], "NOT_FOUND");
+    assert!(body["timestamp"].is_string());
+}
+
+#[tokio::test]
+async fn test_field_errors_carry_validator_code() {
+    let request = UserRequest {
+        name: "Иван".to_string(),
+        email: "ivan@example.com".to_string(),
+        password: "Ab1".to_string(),
+        age: 30,
+    };
+    let error = AppError::from(request.validate().unwrap_err());
+    let body = error_body(error).await;
+
+    // Тест 1: Ошибка короткого пароля содержит код правила валидатора
+    let field_errors = body["field_errors"].as_array().unwrap();
+    let password_error = field_errors.iter().find(|e| e["field"] == "password").unwrap();
+    assert_eq!(password_error["code"], "length");
+    assert_eq!(password_error["message"], "Пароль должен быть не менее 8 символов");
+
+    // Тест 2: В details коды не попадают
+    let details = body["details"].as_str().unwrap();
+    assert!(details.contains("password: Пароль должен быть не менее 8 символов"));
+    assert!(!details.contains("[length]"));
+
+    // Тест 3: Ошибки, созданные без валидатора, не содержат кода
+    let manual = AppError::validation_errors(vec![("email".to_string(), "Некорректный email".to_string())]);
+    let body = error_body(manual).await;
+    assert!(body["field_errors"][0].get("code").is_none());
+
+    // Тест 4: Сообщение с разделителями "[", ";" и ":" передается без изменений
+    let tricky = AppError::validation_errors(vec![(
+        "name".to_string(),
+        "Недопустимые символы: [a-z]; пробелы".to_string(),
+    )]);
+    let body = error_body(tricky).await;
+    assert_eq!(body["field_errors"].as_array().unwrap().len(), 1);
+    assert_eq!(body["field_errors"][0]["field"], "name");
+    assert_eq!(body["field_errors"][0]["message"], "Недопустимые символы: [a-z]; пробелы");
+}
@@ -0,0 +1,49 @@
+use hyper::{Body, Request, StatusCode};
+use std::net::{IpAddr, SocketAddr};
+
+use webapi::middleware::https::https_redirect;
+
+// Запрос от адреса peer с заданным X-Forwarded-Proto
+fn proxied_request(peer: &str, forwarded_proto: Option<&str>) -> Request<Body> {
+    request_to("/api/v1/users?source=form", peer, forwarded_proto)
+}
+
+// Запрос к uri от адреса peer с заданным X-Forwarded-Proto
+fn request_to(uri: &str, peer: &str, forwarded_proto: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri(uri).header("Host", "api.example.com");
+    if let Some(proto) = forwarded_proto {
+        builder = builder.header("X-Forwarded-Proto", proto);
+    }
+    let mut req = builder.body(Body::empty()).unwrap();
+    req.extensions_mut().insert(peer.parse::<SocketAddr>().unwrap());
+    req
+}
+
+#[test]
+fn test_https_redirect() {
+    let trusted: Vec<IpAddr> = vec!["10.0.0.1".parse().unwrap()];
+
+    // Тест 1: Доверенный прокси сообщает о http — 308 на https с тем же путем и запросом
+    let resp = https_redirect(&proxied_request("10.0.0.1:40000", Some("http")), &trusted).unwrap();
+    assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        resp.headers().get("Location").unwrap(),
+        "https://api.example.com/api/v1/users?source=form"
+    );
+
+    // Тест 2: Запрос уже пришел по https — переадресации нет
+    assert!(https_redirect(&proxied_request("10.0.0.1:40000", Some("https")), &trusted).is_none());
+
+    // Тест 3: X-Forwarded-Proto от недоверенного адреса игнорируется (соединение — http)
+    let resp = https_redirect(&proxied_request("203.0.113.5:40000", Some("https")), &trusted).unwrap();
+    assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+
+    // Тест 4: Значение, присланное клиентом до прокси, не учитывается — берется дописанное прокси
+    let resp = https_redirect(&proxied_request("10.0.0.1:40000", Some("https, http")), &trusted).unwrap();
+    assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+
+    // Тест 5: Пробы балансировщика и метрики по http без TRUSTED_PROXIES не переадресуются
+    assert!(https_redirect(&request_to("/health", "203.0.113.5:40000", None), &[]).is_none());
+    assert!(https_redirect(&request_to("/metrics", "203.0.113.5:40000", None), &[]).is_none());
+    assert!(https_redirect(&request_to("/healthz", "203.0.113.5:40000", None), &[]).is_some());
+}
@@ -0,0 +1,78 @@
+use hyper::{Body, Request, StatusCode};
+use serde_json::json;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use webapi::config::AppConfig;
+use webapi::controllers::user::login;
+use webapi::middleware::rate_limit::RateLimiters;
+use webapi::models::UserRequest;
+use webapi::services::user::create_user_service;
+
+mod common;
+use common::TEST_DB_URL;
+
+// POST /api/v1/auth/login с адреса peer; конфигурация и ограничители передаются так же,
+// как их добавляет сервер
+fn login_request(config: &AppConfig, limiters: &Arc<RateLimiters>, peer: &str, email: &str, password: &str) -> Request<Body> {
+    let body = json!({ "email": email, "password": password }).to_string();
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("/api/v1/auth/login")
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    req.extensions_mut().insert(peer.parse::<SocketAddr>().unwrap());
+    req.extensions_mut().insert(Arc::new(config.clone()));
+    req.extensions_mut().insert(Arc::clone(limiters));
+    req
+}
+
+#[tokio::test]
+async fn test_login_throttle_by_outcome() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let mut config = common::test_config();
+    config.login_throttle.unknown_email = 2;
+    config.login_throttle.wrong_password = 5;
+    let limiters = Arc::new(RateLimiters::from_config(&config));
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Ограничение Входа".to_string(),
+        email: "throttle@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 33,
+    };
+    create_user_service(request, &pool).await.unwrap();
+
+    // Тест 1: Перебор несуществующих email с одного IP упирается в строгий лимит,
+    // после чего отклоняется даже вход с верными данными
+    for i in 0..2 {
+        let email = format!("unknown{}@example.com", i);
+        let resp = login(login_request(&config, &limiters, "198.51.100.1:40000", &email, "Password123!"), pool.clone()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+    let resp = login(login_request(&config, &limiters, "198.51.100.1:40000", "throttle@example.com", "Password123!"), pool.clone())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("Retry-After"));
+
+    // Тест 2: Столько же неверных паролей существующего пользователя с другого IP
+    // не мешают войти с верным паролем
+    for _ in 0..2 {
+        let resp = login(login_request(&config, &limiters, "198.51.100.2:40000", "throttle@example.com", "WrongPassword1!"), pool.clone())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+    let resp = login(login_request(&config, &limiters, "198.51.100.2:40000", "throttle@example.com", "Password123!"), pool.clone())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
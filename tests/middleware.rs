@@ -0,0 +1,146 @@
+use hyper::{Body, Request, Response, StatusCode};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use webapi::controllers::user::get_user;
+use webapi::middleware::chain;
+use webapi::models::{Claims, UserRole};
+use webapi::repositories::user::update_user_role;
+
+// Роль при проверке прав читается из БД, поэтому тест работает с тестовой базой
+mod common;
+
+static TEST_JWT_SECRET: &str = "test_secret_key_for_jwt_token_generation";
+
+// Создает действующий токен для пользователя с заданной ролью в claims
+fn token_for(user_id: Uuid, role: UserRole) -> String {
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        sub: user_id.to_string(),
+        exp: now + 3600,
+        iat: now,
+        role,
+        email: "chain@example.com".to_string(),
+        fingerprint: None,
+        sid: None,
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(TEST_JWT_SECRET.as_bytes())).unwrap()
+}
+
+// Запрос к защищенному маршруту с необязательным токеном
+fn admin_request(token: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri("/api/v1/admin/ping");
+    if let Some(token) = token {
+        builder = builder.header("Authorization", format!("Bearer {}", token));
+    }
+    common::with_config(builder.body(Body::empty()).unwrap(), &common::test_config())
+}
+
+// Обработчик, доступный только администраторам
+async fn admin_handler(req: Request<Body>, _pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    // Аутентификация положила роль в extensions
+    assert_eq!(req.extensions().get::<UserRole>(), Some(&UserRole::Admin));
+    Ok(Response::new(Body::from("ok")))
+}
+
+#[tokio::test]
+async fn test_chain_requires_auth_and_admin_role() {
+    env::set_var("JWT_SECRET", TEST_JWT_SECRET);
+    let pool = common::setup_test_db().await;
+    let admin_chain = chain().auth().role(UserRole::Admin);
+    let user_id = insert_user("chain-user@example.com", UserRole::User, &pool).await;
+    let admin_id = insert_user("chain-admin@example.com", UserRole::Admin, &pool).await;
+
+    // Тест 1: Без токена цепочка останавливается на аутентификации
+    let resp = admin_chain.handle(admin_request(None), pool.clone(), admin_handler).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Тест 2: Обычный пользователь не проходит проверку роли
+    let user_token = token_for(user_id, UserRole::User);
+    let resp = admin_chain
+        .handle(admin_request(Some(&user_token)), pool.clone(), admin_handler)
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Тест 3: Администратор проходит обе проверки и попадает в обработчик
+    let admin_token = token_for(admin_id, UserRole::Admin);
+    let resp = admin_chain
+        .handle(admin_request(Some(&admin_token)), pool.clone(), admin_handler)
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Тест 4: После понижения администратора его прежний токен не проходит проверку роли
+    update_user_role(admin_id, UserRole::User, &pool).await.unwrap();
+    let resp = admin_chain
+        .handle(admin_request(Some(&admin_token)), pool.clone(), admin_handler)
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Тест 5: После повышения пользователя прежний токен сразу получает права администратора
+    update_user_role(user_id, UserRole::Admin, &pool).await.unwrap();
+    let resp = admin_chain
+        .handle(admin_request(Some(&user_token)), pool.clone(), admin_handler)
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Тест 6: Токен удаленного пользователя отклоняется, даже если в claims роль администратора
+    let resp = admin_chain
+        .handle(admin_request(Some(&token_for(Uuid::new_v4(), UserRole::Admin))), pool.clone(), admin_handler)
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
+
+#[tokio::test]
+async fn test_get_user_uses_current_role() {
+    env::set_var("JWT_SECRET", TEST_JWT_SECRET);
+    let pool = common::setup_test_db().await;
+    let moderator_id = insert_user("chain-moderator@example.com", UserRole::Moderator, &pool).await;
+    let other_id = insert_user("chain-other@example.com", UserRole::User, &pool).await;
+    let moderator_token = token_for(moderator_id, UserRole::Moderator);
+    let profile_request = || {
+        let req = Request::builder()
+            .uri(format!("/api/v1/users/{}", other_id))
+            .header("Authorization", format!("Bearer {}", moderator_token))
+            .body(Body::empty())
+            .unwrap();
+        common::with_config(req, &common::test_config())
+    };
+
+    // Тест 1: Модератор видит чужой профиль
+    let resp = chain().current_role().handle(profile_request(), pool.clone(), get_user).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Тест 2: После понижения модератора его прежний токен получает 403 на чужой профиль
+    update_user_role(moderator_id, UserRole::User, &pool).await.unwrap();
+    let resp = chain().current_role().handle(profile_request(), pool.clone(), get_user).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
+
+// Создает пользователя с заданной ролью напрямую в БД
+async fn insert_user(email: &str, role: UserRole, pool: &PgPool) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, name, email, password_hash, age, role) VALUES ($1, $2, $3, $4, $5, $6)")
+        .bind(id)
+        .bind("Пользователь цепочки")
+        .bind(email)
+        .bind("hash")
+        .bind(30)
+        .bind(role)
+        .execute(pool)
+        .await
+        .expect("Не удалось создать пользователя");
+    id
+}
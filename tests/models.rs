@@ -0,0 +1,138 @@
+use chrono::Utc;
+use serde_json::json;
+use std::env;
+use uuid::Uuid;
+
+use webapi::models::{
+    init_password_policy, AdminUserResponse, PasswordPolicy, UpdateUserRequest, User, UserRequest, UserResponse, UserRole,
+};
+
+#[test]
+fn test_user_role_parsing_is_case_insensitive() {
+    // Тест 1: Десериализация роли в любом регистре
+    for raw in ["\"Admin\"", "\"admin\"", "\"ADMIN\""] {
+        let role: UserRole = serde_json::from_str(raw).unwrap();
+        assert_eq!(role, UserRole::Admin);
+    }
+
+    // Тест 2: FromStr для значений из БД
+    assert_eq!("Moderator".parse::<UserRole>().unwrap(), UserRole::Moderator);
+    assert_eq!("user".parse::<UserRole>().unwrap(), UserRole::User);
+
+    // Тест 3: Неизвестная роль отклоняется
+    assert!("superuser".parse::<UserRole>().is_err());
+    assert!(serde_json::from_str::<UserRole>("\"root\"").is_err());
+
+    // Тест 4: Сериализация использует каноническое имя
+    assert_eq!(serde_json::to_string(&UserRole::Admin).unwrap(), "\"Admin\"");
+}
+
+#[test]
+fn test_oversized_string_fields_rejected_while_parsing() {
+    let huge_name = "А".repeat(100_000);
+
+    // Тест 1: Огромное имя отклоняется при разборе, до валидации
+    let body = json!({
+        "name": huge_name,
+        "email": "huge@example.com",
+        "password": "Password123!",
+        "age": 30
+    });
+    let error = serde_json::from_value::<UserRequest>(body).unwrap_err();
+    assert!(error.to_string().contains("поле name длиннее 1024 символов"));
+
+    // Тест 2: Необязательные поля обновления проверяются так же, отсутствие и null допустимы
+    let error = serde_json::from_value::<UpdateUserRequest>(json!({ "email": huge_name })).unwrap_err();
+    assert!(error.to_string().contains("поле email длиннее"));
+    let update: UpdateUserRequest = serde_json::from_value(json!({ "name": null, "age": 30 })).unwrap();
+    assert!(update.name.is_none() && update.email.is_none());
+
+    // Тест 3: Обычные значения разбираются без изменений
+    let body = json!({
+        "name": "Обычное Имя",
+        "email": "normal@example.com",
+        "password": "Password123!",
+        "age": 30
+    });
+    let request: UserRequest = serde_json::from_value(body).unwrap();
+    assert_eq!(request.name, "Обычное Имя");
+}
+
+#[test]
+fn test_timezone_validation() {
+    use validator::Validate;
+
+    // Тест 1: Известный часовой пояс IANA проходит валидацию
+    let update: UpdateUserRequest = serde_json::from_value(json!({ "timezone": "Europe/Moscow" })).unwrap();
+    assert!(update.validate().is_ok());
+    assert_eq!(update.timezone, Some(Some("Europe/Moscow".to_string())));
+
+    // Тест 2: Неизвестное имя отклоняется с ошибкой поля timezone
+    let update: UpdateUserRequest = serde_json::from_value(json!({ "timezone": "Mars/Olympus" })).unwrap();
+    let errors = update.validate().unwrap_err();
+    assert!(errors.field_errors().contains_key("timezone"));
+
+    // Тест 3: null сбрасывает часовой пояс, а отсутствие поля оставляет его без изменений
+    let update: UpdateUserRequest = serde_json::from_value(json!({ "timezone": null })).unwrap();
+    assert!(update.validate().is_ok());
+    assert_eq!(update.timezone, Some(None));
+    let update: UpdateUserRequest = serde_json::from_value(json!({ "age": 30 })).unwrap();
+    assert_eq!(update.timezone, None);
+}
+
+#[test]
+fn test_invalid_password_pattern_fails_at_init() {
+    // Тест 1: Некорректное выражение в PASSWORD_PATTERN — ошибка при инициализации, а не паника
+    env::set_var("PASSWORD_PATTERN", "^(?=.*[!@#]).+$");
+    let error = PasswordPolicy::from_env().unwrap_err();
+    assert!(error.contains("PASSWORD_PATTERN"));
+    assert!(init_password_policy().is_err());
+    env::remove_var("PASSWORD_PATTERN");
+
+    // Тест 2: Базовые требования проверяются без регулярного выражения
+    let policy = PasswordPolicy::default();
+    assert!(policy.is_satisfied_by("Password123"));
+    assert!(!policy.is_satisfied_by("password123"));
+    assert!(!policy.is_satisfied_by("PASSWORD123"));
+    assert!(!policy.is_satisfied_by("Passwordabc"));
+    assert!(!policy.is_satisfied_by("Pass1"));
+}
+
+#[test]
+fn test_admin_user_view_includes_account_state() {
+    let now = Utc::now();
+    let user = User {
+        id: Uuid::new_v4(),
+        name: "Администратор".to_string(),
+        email: "admin-view@example.com".to_string(),
+        password_hash: "hash".to_string(),
+        age: 30,
+        role: UserRole::User,
+        created_at: now,
+        updated_at: now,
+        is_active: false,
+        name_updated_at: None,
+        age_updated_at: None,
+        failed_login_attempts: 0,
+        locked_until: None,
+        pending_email: None,
+        must_change_password: false,
+        timezone: None,
+        login_count: 0,
+        last_login_at: None,
+    };
+
+    // Тест 1: Публичное представление не раскрывает состояние учетной записи
+    let public = serde_json::to_value(UserResponse::from(&user)).unwrap();
+    assert!(public.get("is_active").is_none());
+    assert!(public.get("updated_at").is_none());
+
+    // Тест 2: Администраторское представление содержит is_active и updated_at
+    let admin = serde_json::to_value(AdminUserResponse::from(&user)).unwrap();
+    assert_eq!(admin["is_active"], json!(false));
+    assert!(admin["updated_at"].is_string());
+
+    // Тест 3: Публичные поля остаются на верхнем уровне, секреты не попадают в ответ
+    assert_eq!(admin["email"], json!("admin-view@example.com"));
+    assert!(admin.get("password_hash").is_none());
+}
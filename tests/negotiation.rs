@@ -0,0 +1,40 @@
+use hyper::{Body, Request, StatusCode};
+
+use webapi::middleware::negotiation::{api_version, negotiate_api_version};
+
+// Запрос к пути с необязательным заголовком Accept
+fn request(path: &str, accept: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri(path);
+    if let Some(accept) = accept {
+        builder = builder.header("Accept", accept);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_api_version_negotiation() {
+    assert_eq!(api_version("/api/v2/users/me"), Some(2));
+    assert_eq!(api_version("/health"), None);
+
+    // Тест 1: Маршрут v2 без Accept — 406 с кодом NOT_ACCEPTABLE
+    let response = negotiate_api_version(&request("/api/v2/users/me", None)).unwrap_err();
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body["code"], "NOT_ACCEPTABLE");
+
+    // Тест 2: Accept с обычным JSON или другой версией для v2 не подходит
+    for accept in ["application/json", "application/vnd.webapi.v1+json"] {
+        let response = negotiate_api_version(&request("/api/v2/users/me", Some(accept))).unwrap_err();
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    // Тест 3: Нужный тип принимается, в том числе среди нескольких и с параметрами
+    assert!(negotiate_api_version(&request("/api/v2/users/me", Some("application/vnd.webapi.v2+json"))).is_ok());
+    let accept = "text/html, application/vnd.webapi.v2+json; q=0.9";
+    assert!(negotiate_api_version(&request("/api/v2/users/me", Some(accept))).is_ok());
+
+    // Тест 4: v1 и служебные пути не требуют Accept
+    assert!(negotiate_api_version(&request("/api/v1/users/me", None)).is_ok());
+    assert!(negotiate_api_version(&request("/health", Some("text/plain"))).is_ok());
+}
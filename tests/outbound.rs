@@ -0,0 +1,58 @@
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use sha1::{Digest, Sha1};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use webapi::clients::hibp::HibpClient;
+use webapi::utils::{current_request_id, RequestId};
+
+// Поднимает mock-сервер HIBP, который запоминает X-Request-ID и отдает заданное тело ответа
+async fn start_mock_hibp(body: String, seen: Arc<Mutex<Vec<Option<String>>>>) -> SocketAddr {
+    let make_svc = make_service_fn(move |_| {
+        let body = body.clone();
+        let seen = seen.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let request_id = req
+                    .headers()
+                    .get("X-Request-ID")
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from);
+                seen.lock().unwrap().push(request_id);
+                let body = body.clone();
+                async move { Ok::<_, Infallible>(Response::new(Body::from(body))) }
+            }))
+        }
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+#[tokio::test]
+async fn test_request_id_passthrough() {
+    let hash = hex::encode_upper(Sha1::digest(b"Password123!"));
+    let body = format!("0000000000000000000000000000000000A:0\r\n{}:42\r\n", &hash[5..]);
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let addr = start_mock_hibp(body, seen.clone()).await;
+    let client = HibpClient::new(&format!("http://{}", addr));
+
+    // Тест 1: ID входящего запроса извлекается из extensions и уходит в исходящий запрос
+    let mut inbound = Request::post("/api/v1/users").body(Body::empty()).unwrap();
+    inbound.extensions_mut().insert(RequestId("req-outbound-1".to_string()));
+    let request_id = current_request_id(&inbound);
+    assert_eq!(request_id.as_deref(), Some("req-outbound-1"));
+
+    let pwned = client.is_password_pwned("Password123!", request_id.as_deref()).await.unwrap();
+    assert!(pwned);
+    assert_eq!(seen.lock().unwrap().last().cloned().flatten().as_deref(), Some("req-outbound-1"));
+
+    // Тест 2: Без ID заголовок не добавляется, строки дополнения с нулем не считаются утечкой
+    let pwned = client.is_password_pwned("Другой-Пароль-789!", None).await.unwrap();
+    assert!(!pwned);
+    assert_eq!(seen.lock().unwrap().last().cloned().flatten(), None);
+}
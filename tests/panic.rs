@@ -0,0 +1,31 @@
+use hyper::{Body, Response, StatusCode};
+use serde_json::Value;
+
+use webapi::middleware::panic::catch_panic;
+
+// Обработчик, который паникует (как при unwrap на неожиданных данных)
+async fn panicking_handler() -> Result<Response<Body>, hyper::Error> {
+    let value: Option<&str> = std::hint::black_box(None);
+    Ok(Response::new(Body::from(value.expect("обработчик упал"))))
+}
+
+#[tokio::test]
+async fn test_handler_panic_returns_500() {
+    // Тест 1: Паника обработчика превращается в JSON-ответ 500 с trace_id
+    let response = catch_panic(panicking_handler(), Some("req-panic-1".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.headers().get("X-Trace-ID").unwrap(), "req-panic-1");
+
+    let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body["status"], 500);
+    assert_eq!(body["trace_id"], "req-panic-1");
+
+    // Тест 2: Обычный ответ проходит без изменений
+    let response = catch_panic(async { Ok(Response::new(Body::from("ok"))) }, None)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,53 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use webapi::services::user::{hash_passwords_batch, verify_passwords_batch};
+use webapi::utils::map_blocking_bounded;
+
+#[tokio::test]
+async fn test_hash_and_verify_passwords_batch() {
+    let passwords: Vec<String> = (0..6).map(|i| format!("Password{}!", i)).collect();
+
+    // Тест 1: Все пароли пакета хешируются, порядок результатов сохраняется
+    let hashes: Vec<String> = hash_passwords_batch(passwords.clone())
+        .await
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect();
+    assert_eq!(hashes.len(), passwords.len());
+    assert!(hashes.iter().all(|hash| hash.starts_with("$argon2id$")));
+
+    // Тест 2: Каждый хеш соответствует своему паролю и не подходит к чужому
+    let mut credentials: Vec<(String, String)> = passwords.iter().cloned().zip(hashes.iter().cloned()).collect();
+    credentials.push((passwords[0].clone(), hashes[1].clone()));
+    let results: Vec<bool> = verify_passwords_batch(credentials)
+        .await
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect();
+    assert!(results[..passwords.len()].iter().all(|valid| *valid));
+    assert!(!results[passwords.len()]);
+}
+
+#[tokio::test]
+async fn test_map_blocking_bounded_respects_limit() {
+    let active = Arc::new(AtomicUsize::new(0));
+    let max_active = Arc::new(AtomicUsize::new(0));
+
+    let (active_in_task, max_in_task) = (Arc::clone(&active), Arc::clone(&max_active));
+    let results = map_blocking_bounded((0..12).collect::<Vec<u32>>(), 3, move |item| {
+        // Отмечаем число одновременно выполняющихся задач
+        let now = active_in_task.fetch_add(1, Ordering::SeqCst) + 1;
+        max_in_task.fetch_max(now, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(20));
+        active_in_task.fetch_sub(1, Ordering::SeqCst);
+        item * 2
+    })
+    .await;
+
+    // Одновременно выполнялось не больше трех задач, результаты — в исходном порядке
+    assert!(max_active.load(Ordering::SeqCst) <= 3);
+    let values: Vec<u32> = results.into_iter().map(|result| result.unwrap()).collect();
+    assert_eq!(values, (0..12).map(|i| i * 2).collect::<Vec<u32>>());
+}
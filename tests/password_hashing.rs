@@ -0,0 +1,86 @@
+use futures_util::future::join_all;
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use webapi::errors::AppError;
+use webapi::models::{LoginRequest, UserRequest};
+use webapi::services::user::{create_user_service, login_service};
+use webapi::utils::{password_hash_limiter, BlockingLimiter};
+
+mod common;
+use common::TEST_DB_URL;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_blocking_limiter_bounds_concurrency() {
+    // Тест 1: Из десяти одновременных задач выполняются не больше двух сразу
+    let limiter = BlockingLimiter::new(2, Duration::from_secs(5));
+    let running = Arc::new(AtomicUsize::new(0));
+    let observed = Arc::new(AtomicUsize::new(0));
+    let tasks = (0..10).map(|_| {
+        let running = Arc::clone(&running);
+        let observed = Arc::clone(&observed);
+        limiter.run(move || {
+            let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+            observed.fetch_max(current, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(30));
+            running.fetch_sub(1, Ordering::SeqCst);
+        })
+    });
+    assert!(join_all(tasks).await.iter().all(Result::is_ok));
+    assert_eq!(observed.load(Ordering::SeqCst), 2);
+    assert_eq!(limiter.peak(), 2);
+    assert_eq!(limiter.in_flight(), 0);
+
+    // Тест 2: Без ожидания в очереди лишняя задача сразу получает 503
+    let limiter = BlockingLimiter::new(1, Duration::ZERO);
+    let busy = limiter.run(|| std::thread::sleep(Duration::from_millis(200)));
+    let rejected = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        limiter.run(|| ()).await
+    };
+    let (busy, rejected) = tokio::join!(busy, rejected);
+    assert!(busy.is_ok());
+    assert!(matches!(rejected, Err(AppError::ServiceUnavailable)));
+    assert_eq!(limiter.rejected_count(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_login_burst_hashing_is_bounded() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    env::set_var("PASSWORD_HASH_CONCURRENCY", "2");
+    env::set_var("PASSWORD_HASH_QUEUE_TIMEOUT_MS", "30000");
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Пиковая Нагрузка".to_string(),
+        email: "burst@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 27,
+    };
+    create_user_service(request, &pool).await.unwrap();
+
+    // Всплеск из восьми одновременных входов: все проходят, но проверок пароля
+    // одновременно выполняется не больше PASSWORD_HASH_CONCURRENCY
+    let config = common::test_config();
+    let logins = (0..8).map(|_| {
+        let login = LoginRequest {
+            email: "burst@example.com".to_string(),
+            password: "Password123!".to_string(),
+            remember_me: false,
+            reactivate: false,
+        };
+        login_service(login, &config, &pool)
+    });
+    assert!(join_all(logins).await.iter().all(Result::is_ok));
+    assert!(password_hash_limiter().peak() <= 2);
+    assert_eq!(password_hash_limiter().rejected_count(), 0);
+
+    env::remove_var("PASSWORD_HASH_CONCURRENCY");
+    env::remove_var("PASSWORD_HASH_QUEUE_TIMEOUT_MS");
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
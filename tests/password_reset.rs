@@ -0,0 +1,82 @@
+use hyper::{Body, Request, StatusCode};
+use serde_json::Value;
+use sqlx::PgPool;
+
+use webapi::config::AppConfig;
+use webapi::controllers::user::request_password_reset;
+use webapi::models::UserRequest;
+use webapi::repositories::password_reset::count_active_password_reset_tokens;
+use webapi::services::user::create_user_service;
+
+mod common;
+
+fn reset_request(email: &str, config: &AppConfig) -> Request<Body> {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/api/v1/auth/password-reset")
+        .header("Content-Type", "application/json")
+        .body(Body::from(format!(r#"{{"email":"{}"}}"#, email)))
+        .unwrap();
+    common::with_config(req, config)
+}
+
+async fn send(email: &str, config: &AppConfig, pool: &PgPool) -> (StatusCode, Value) {
+    let response = request_password_reset(reset_request(email, config), pool.clone()).await.unwrap();
+    let status = response.status();
+    let body = serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    (status, body)
+}
+
+#[tokio::test]
+async fn test_rapid_reset_requests_are_capped() {
+    let mut config = common::test_config();
+    config.features.password_reset = true;
+    config.password_reset.max_active = 2;
+    config.password_reset.max_per_hour = 4;
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Забывчивый".to_string(),
+        email: "forgetful@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 35,
+    };
+    let user = create_user_service(request, &pool).await.unwrap();
+
+    // Тест 1: Серия быстрых запросов всегда получает 200 с одинаковым ответом
+    let (status, first_body) = send("forgetful@example.com", &config, &pool).await;
+    assert_eq!(status, StatusCode::OK);
+    for _ in 0..9 {
+        let (status, body) = send("forgetful@example.com", &config, &pool).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, first_body);
+    }
+
+    // Тест 2: Выпущено не больше лимита за час, в базе только хеши, действуют не больше PASSWORD_RESET_MAX_ACTIVE
+    let hashes: Vec<String> = sqlx::query_scalar("SELECT token_hash FROM password_reset_tokens WHERE user_id = $1")
+        .bind(user.id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(hashes.len(), 4);
+    assert!(hashes.iter().all(|hash| hash.len() == 64));
+    assert_eq!(count_active_password_reset_tokens(user.id, &pool).await.unwrap(), 2);
+
+    // Тест 3: Неизвестный email получает тот же ответ и не создает токенов
+    let (status, body) = send("nobody@example.com", &config, &pool).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, first_body);
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM password_reset_tokens")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(total, 4);
+
+    // Тест 4: При выключенном FEATURE_PASSWORD_RESET маршрут недоступен
+    config.features.password_reset = false;
+    let (status, _) = send("forgetful@example.com", &config, &pool).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
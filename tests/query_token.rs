@@ -0,0 +1,102 @@
+use hyper::header::REFERRER_POLICY;
+use hyper::{Body, Request, Response, StatusCode};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use webapi::config::AppConfig;
+use webapi::controllers::admin::export_users;
+use webapi::controllers::user::get_current_user;
+use webapi::middleware::auth::auth_middleware;
+use webapi::middleware::chain;
+use webapi::models::{Claims, UserRole};
+
+// Путь к тестовой базе данных (роль при проверке прав читается из БД)
+mod common;
+static TEST_JWT_SECRET: &str = "test_secret_key_for_jwt_token_generation";
+
+// Создает действующий токен администратора
+fn admin_token(user_id: Uuid) -> String {
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        sub: user_id.to_string(),
+        exp: now + 3600,
+        iat: now,
+        role: UserRole::Admin,
+        email: "export-admin@example.com".to_string(),
+        fingerprint: None,
+        sid: None,
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(TEST_JWT_SECRET.as_bytes())).unwrap()
+}
+
+// GET-запрос без заголовков аутентификации с конфигурацией config
+fn get(uri: &str, config: &AppConfig) -> Request<Body> {
+    common::with_config(Request::builder().uri(uri).body(Body::empty()).unwrap(), config)
+}
+
+#[tokio::test]
+async fn test_download_with_query_token() {
+    env::set_var("JWT_SECRET", TEST_JWT_SECRET);
+    let mut config = common::test_config();
+    config.query_token_auth = true;
+    let pool = common::setup_test_db().await;
+    let admin_id = insert_user("export-admin@example.com", UserRole::Admin, &pool).await;
+    let export_uri = format!("/api/v1/admin/users/export?access_token={}", admin_token(admin_id));
+
+    // Тест 1: Ссылка на скачивание выгрузки аутентифицируется токеном из строки запроса
+    let response = chain()
+        .role(UserRole::Admin)
+        .handle(get(&export_uri, &config), pool.clone(), export_users)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(REFERRER_POLICY).unwrap(), "no-referrer");
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("export-admin@example.com"));
+
+    // Тест 2: Обработчик получает URI без токена, остальные параметры сохраняются
+    let response = chain()
+        .role(UserRole::Admin)
+        .handle(get(&format!("{}&limit=10", export_uri), &config), pool.clone(), |req: Request<Body>, _pool: PgPool| async move {
+            Ok::<_, hyper::Error>(Response::new(Body::from(req.uri().to_string())))
+        })
+        .await
+        .unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(&body[..], b"/api/v1/admin/users/export?limit=10");
+
+    // Тест 3: Маршрут не из списка выгрузок токен в строке запроса не принимает
+    let profile_uri = format!("/api/v1/users/me?access_token={}", admin_token(admin_id));
+    let response = auth_middleware(get(&profile_uri, &config), pool.clone(), get_current_user).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Тест 4: Без QUERY_TOKEN_AUTH токен в строке запроса игнорируется и для выгрузки
+    config.query_token_auth = false;
+    let response = chain()
+        .role(UserRole::Admin)
+        .handle(get(&export_uri, &config), pool.clone(), export_users)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
+
+// Создает пользователя с заданной ролью напрямую в БД
+async fn insert_user(email: &str, role: UserRole, pool: &PgPool) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, name, email, password_hash, age, role) VALUES ($1, $2, $3, $4, $5, $6)")
+        .bind(id)
+        .bind("Администратор выгрузки")
+        .bind(email)
+        .bind("hash")
+        .bind(30)
+        .bind(role)
+        .execute(pool)
+        .await
+        .expect("Не удалось создать пользователя");
+    id
+}
@@ -0,0 +1,181 @@
+use hyper::{Body, Request, StatusCode};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use uuid::Uuid;
+
+use webapi::config::{AppConfig, EnvVars, LoginThrottleLimits, RoleRateLimits};
+use webapi::metrics::LoginFailureReason;
+use webapi::middleware::proxy::client_ip;
+use webapi::middleware::rate_limit::{check_user_rate_limit, LoginThrottle, RateLimiter, RateLimiters, UserRateLimiter};
+use webapi::models::UserRole;
+
+// Запрос от адреса peer с необязательным X-Forwarded-For
+fn request_from(peer: &str, forwarded_for: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri("/api/v1/users/me");
+    if let Some(ip) = forwarded_for {
+        builder = builder.header("X-Forwarded-For", ip);
+    }
+    let mut req = builder.body(Body::empty()).unwrap();
+    req.extensions_mut().insert(peer.parse::<SocketAddr>().unwrap());
+    req
+}
+
+#[test]
+fn test_rate_limit_key_uses_forwarded_client_ip() {
+    let trusted: Vec<IpAddr> = vec!["10.0.0.1".parse().unwrap()];
+    let limiter = RateLimiter::new(1, Duration::from_secs(60));
+
+    // Тест 1: Клиенты за одним доверенным прокси получают независимые лимиты
+    assert!(limiter.check_request(&request_from("10.0.0.1:40000", Some("198.51.100.1")), &trusted).is_ok());
+    assert!(limiter.check_request(&request_from("10.0.0.1:40001", Some("198.51.100.2")), &trusted).is_ok());
+
+    // Тест 2: Повторный запрос того же клиента сверх лимита — 429 с Retry-After
+    let response = limiter
+        .check_request(&request_from("10.0.0.1:40002", Some("198.51.100.1, 10.0.0.1")), &trusted)
+        .unwrap_err();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("Retry-After"));
+
+    // Тест 3: X-Forwarded-For от недоверенного адреса игнорируется — ключ по адресу соединения
+    assert!(limiter.check_request(&request_from("203.0.113.5:40000", Some("198.51.100.3")), &trusted).is_ok());
+    assert!(limiter.check_request(&request_from("203.0.113.5:40001", Some("198.51.100.4")), &trusted).is_err());
+
+    // Тест 4: Нулевой лимит отключает ограничение
+    let disabled = RateLimiter::new(0, Duration::from_secs(60));
+    for _ in 0..3 {
+        assert!(disabled.check_request(&request_from("203.0.113.5:40000", None), &trusted).is_ok());
+    }
+
+    // Тест 5: Адрес, который клиент сам дописал в начало X-Forwarded-For, не становится ключом
+    let spoofed = request_from("10.0.0.1:40000", Some("192.0.2.66, 198.51.100.7"));
+    assert_eq!(client_ip(&spoofed, &trusted), Some("198.51.100.7".parse().unwrap()));
+
+    // Тест 6: Нечитаемая запись в цепочке — ключ по адресу соединения
+    let garbled = request_from("10.0.0.1:40000", Some("198.51.100.7, not-an-ip"));
+    assert_eq!(client_ip(&garbled, &trusted), Some("10.0.0.1".parse().unwrap()));
+}
+
+// Аутентифицированный запрос: ID пользователя и роль в extensions, как после auth-middleware
+fn authenticated_request(user_id: Uuid, role: UserRole) -> Request<Body> {
+    let mut req = Request::builder().uri("/api/v1/users/me").body(Body::empty()).unwrap();
+    req.extensions_mut().insert(user_id);
+    req.extensions_mut().insert(role);
+    req
+}
+
+#[test]
+fn test_user_rate_limit_depends_on_role() {
+    let policy = RoleRateLimits { user: 2, moderator: 5, admin: 0 };
+    let limiter = UserRateLimiter::new(policy, Duration::from_secs(60));
+    let user = Uuid::new_v4();
+    let admin = Uuid::new_v4();
+
+    // Тест 1: Пользователь сверх своего лимита получает 429 с Retry-After
+    for _ in 0..2 {
+        assert!(limiter.check_request(&authenticated_request(user, UserRole::User)).is_ok());
+    }
+    let response = limiter.check_request(&authenticated_request(user, UserRole::User)).unwrap_err();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("Retry-After"));
+
+    // Тест 2: Администратор с нулевым лимитом не ограничивается при том же числе запросов
+    for _ in 0..10 {
+        assert!(limiter.check_request(&authenticated_request(admin, UserRole::Admin)).is_ok());
+    }
+
+    // Тест 3: Лимит модератора берется из его роли
+    let moderator = Uuid::new_v4();
+    for _ in 0..5 {
+        assert!(limiter.check(moderator, UserRole::Moderator).is_ok());
+    }
+    assert!(limiter.check(moderator, UserRole::Moderator).is_err());
+
+    // Тест 4: Без аутентификации (нет ID в extensions) лимит пользователя не применяется
+    let anonymous = Request::builder().uri("/api/v1/version").body(Body::empty()).unwrap();
+    assert!(limiter.check_request(&anonymous).is_ok());
+}
+
+#[test]
+fn test_login_throttle_stricter_for_unknown_email() {
+    let limits = LoginThrottleLimits { unknown_email: 2, wrong_password: 5 };
+    let throttle = LoginThrottle::new(limits, Duration::from_secs(60));
+    let sprayer: IpAddr = "198.51.100.10".parse().unwrap();
+    let typo: IpAddr = "198.51.100.20".parse().unwrap();
+
+    // Тест 1: Несуществующие email исчерпывают строгий лимит за две попытки
+    for _ in 0..2 {
+        assert!(throttle.check(sprayer).is_ok());
+        throttle.record_failure(sprayer, LoginFailureReason::NotFound);
+    }
+    assert!(throttle.check(sprayer).is_err());
+
+    // Тест 2: То же число неверных паролей существующего пользователя не блокирует вход
+    for _ in 0..2 {
+        throttle.record_failure(typo, LoginFailureReason::BadPassword);
+    }
+    assert!(throttle.check(typo).is_ok());
+
+    // Тест 3: Неверные пароли ограничиваются своим, более мягким лимитом
+    for _ in 0..3 {
+        throttle.record_failure(typo, LoginFailureReason::BadPassword);
+    }
+    assert!(throttle.check(typo).is_err());
+
+    // Тест 4: Ответ на запрос с исчерпанным лимитом — 429 с Retry-After
+    let response = throttle.check_request(&request_from("198.51.100.10:40000", None), &[]).unwrap_err();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("Retry-After"));
+
+    // Тест 5: Блокировка и неактивный аккаунт в лимитах не учитываются
+    let other: IpAddr = "198.51.100.30".parse().unwrap();
+    for _ in 0..10 {
+        throttle.record_failure(other, LoginFailureReason::Locked);
+        throttle.record_failure(other, LoginFailureReason::Inactive);
+    }
+    assert!(throttle.check(other).is_ok());
+}
+
+// Конфигурация с лимитом limit для IP, пользователей и неудачных входов
+fn config_with_limit(limit: &str) -> AppConfig {
+    AppConfig::from_vars(&EnvVars::from_iter([
+        ("DATABASE_URL", "postgres://localhost/webapi_test"),
+        ("JWT_SECRET", "test_secret_key_for_jwt_token_generation"),
+        ("RATE_LIMIT_PER_MINUTE", limit),
+        ("RATE_LIMIT_USER_PER_MINUTE", limit),
+        ("LOGIN_THROTTLE_UNKNOWN_EMAIL_PER_MINUTE", limit),
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn test_rate_limiters_follow_config() {
+    let limiters = std::sync::Arc::new(RateLimiters::from_config(&config_with_limit("1")));
+    let client: IpAddr = "198.51.100.40".parse().unwrap();
+    let user_id = Uuid::new_v4();
+
+    // Тест 1: Лимиты берутся из конфигурации, с которой созданы ограничители
+    assert!(limiters.ip.check(client).is_ok());
+    assert!(limiters.ip.check(client).is_err());
+
+    // Тест 2: Лимит пользователя проверяется ограничителями из extensions запроса
+    let with_limiters = || {
+        let mut req = authenticated_request(user_id, UserRole::User);
+        req.extensions_mut().insert(std::sync::Arc::clone(&limiters));
+        req
+    };
+    assert!(check_user_rate_limit(&with_limiters()).is_ok());
+    assert_eq!(check_user_rate_limit(&with_limiters()).unwrap_err().status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Тест 3: Запрос без ограничителей (не прошедший через сервер) не ограничивается
+    assert!(check_user_rate_limit(&authenticated_request(user_id, UserRole::User)).is_ok());
+
+    // Тест 4: Неудачные входы ограничиваются тем же набором ограничителей
+    limiters.login.record_failure(client, LoginFailureReason::NotFound);
+    assert!(limiters.login.check(client).is_err());
+
+    // Тест 5: Перезагруженная конфигурация без лимитов снимает ограничения
+    limiters.apply(&config_with_limit("0"));
+    assert!(limiters.ip.check(client).is_ok());
+    assert!(check_user_rate_limit(&with_limiters()).is_ok());
+    assert!(limiters.login.check(client).is_ok());
+}
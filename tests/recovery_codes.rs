@@ -0,0 +1,84 @@
+use hyper::{Body, Request, StatusCode};
+use serde_json::Value;
+use std::env;
+
+use webapi::controllers::user::generate_recovery_codes;
+use webapi::errors::AppError;
+use webapi::models::UserRequest;
+use webapi::services::recovery_code::{consume_recovery_code_service, generate_recovery_codes_service};
+use webapi::services::user::{create_user_service, security_status_service};
+
+mod common;
+use common::TEST_DB_URL;
+
+#[tokio::test]
+async fn test_recovery_codes() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    env::set_var("RECOVERY_CODES_COUNT", "10");
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Пользователь 2FA".to_string(),
+        email: "recovery@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 30,
+    };
+    let user = create_user_service(request, &pool).await.unwrap();
+
+    // Тест 1: Выпускается набор уникальных кодов, в базе хранятся только хеши
+    let codes = generate_recovery_codes_service(user.id, &pool).await.unwrap();
+    assert_eq!(codes.len(), 10);
+    let mut unique = codes.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), codes.len());
+
+    let stored: Vec<String> = sqlx::query_scalar("SELECT code_hash FROM user_recovery_codes WHERE user_id = $1")
+        .bind(user.id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 10);
+    assert!(stored.iter().all(|hash| !codes.contains(hash)));
+
+    // Тест 2: Код принимается (регистр и дефис не важны)
+    let code = codes[0].to_lowercase().replace('-', "");
+    assert!(consume_recovery_code_service(user.id, &code, &pool).await.is_ok());
+
+    // Тест 3: Повторное использование того же кода отклоняется
+    let result = consume_recovery_code_service(user.id, &codes[0], &pool).await;
+    assert!(matches!(result, Err(AppError::Unauthorized)));
+
+    // Тест 4: Новый набор делает прежние коды недействительными
+    let new_codes = generate_recovery_codes_service(user.id, &pool).await.unwrap();
+    let result = consume_recovery_code_service(user.id, &codes[1], &pool).await;
+    assert!(matches!(result, Err(AppError::Unauthorized)));
+    assert!(consume_recovery_code_service(user.id, &new_codes[1], &pool).await.is_ok());
+
+    // Тест 5: Без FEATURE_2FA маршрут выпуска кодов отвечает 404
+    let mut config = common::test_config();
+    let request = |config: &webapi::config::AppConfig| {
+        let mut req = Request::post("/api/v1/users/me/2fa/recovery-codes").body(Body::empty()).unwrap();
+        req.extensions_mut().insert(user.id);
+        common::with_config(req, config)
+    };
+    let response = generate_recovery_codes(request(&config), pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Тест 6: С FEATURE_2FA маршрут выдает новый набор, и сводка безопасности видит его
+    config.features.two_factor = true;
+    let response = generate_recovery_codes(request(&config), pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body: Value = serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    let codes = body["codes"].as_array().unwrap();
+    assert_eq!(codes.len(), 10);
+    consume_recovery_code_service(user.id, codes[0].as_str().unwrap(), &pool).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    let status = security_status_service(user.id, &mut conn).await.unwrap();
+    assert_eq!(status.recovery_codes_remaining, Some(9));
+    drop(conn);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
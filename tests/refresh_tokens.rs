@@ -0,0 +1,84 @@
+use hyper::header::AUTHORIZATION;
+use hyper::{Body, Request, StatusCode};
+use std::env;
+
+use webapi::controllers::user::{refresh_token, verify_token};
+use webapi::errors::AppError;
+use webapi::middleware::auth::auth_middleware;
+use webapi::models::{LoginRequest, UserRequest};
+use webapi::services::user::{create_user_service, login_service, refresh_token_service};
+
+mod common;
+use common::TEST_DB_URL;
+
+// Проверяет JWT через /api/v1/auth/verify и возвращает статус ответа
+async fn verify_status(token: &str, pool: &sqlx::PgPool) -> StatusCode {
+    let request = Request::get("/api/v1/auth/verify")
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    auth_middleware(request, pool.clone(), verify_token).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_refresh_token_rotation() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Долгожитель".to_string(),
+        email: "longlived@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 33,
+    };
+    create_user_service(request, &pool).await.unwrap();
+    let login = LoginRequest {
+        email: "longlived@example.com".to_string(),
+        password: "Password123!".to_string(),
+        remember_me: false,
+        reactivate: false,
+    };
+    let auth = login_service(login, &common::test_config(), &pool).await.unwrap();
+
+    // Тест 1: Обновление выдает новый JWT и новый refresh-токен
+    let first = refresh_token_service(&auth.refresh_token, None, &common::test_config(), &pool).await.unwrap();
+    assert_ne!(first.refresh_token, auth.refresh_token);
+    assert_eq!(verify_status(&first.token, &pool).await, StatusCode::OK);
+
+    // Тест 2: Обработчик принимает refresh-токен в теле и продолжает ротацию
+    let request = common::with_config(
+        Request::post("/api/v1/auth/refresh")
+            .body(Body::from(format!(r#"{{"refresh_token":"{}"}}"#, first.refresh_token)))
+            .unwrap(),
+        &common::test_config(),
+    );
+    let response = refresh_token(request, pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    let second_refresh = body["refresh_token"].as_str().unwrap().to_string();
+    let second_token = body["token"].as_str().unwrap().to_string();
+    assert_eq!(verify_status(&second_token, &pool).await, StatusCode::OK);
+
+    // Тест 3: Повторное предъявление погашенного токена отклоняется и отзывает всю цепочку:
+    // последний выданный refresh-токен и JWT сессии больше не действуют
+    let result = refresh_token_service(&auth.refresh_token, None, &common::test_config(), &pool).await;
+    assert!(matches!(result, Err(AppError::InvalidToken)));
+    let result = refresh_token_service(&second_refresh, None, &common::test_config(), &pool).await;
+    assert!(matches!(result, Err(AppError::InvalidToken)));
+    assert_eq!(verify_status(&second_token, &pool).await, StatusCode::UNAUTHORIZED);
+
+    let revoked: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM refresh_tokens WHERE revoked_at IS NOT NULL")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(revoked, 3);
+
+    // Тест 4: Неизвестный refresh-токен отклоняется
+    let result = refresh_token_service("не-токен", None, &common::test_config(), &pool).await;
+    assert!(matches!(result, Err(AppError::InvalidToken)));
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
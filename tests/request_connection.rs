@@ -0,0 +1,84 @@
+use hyper::{Body, Request, StatusCode};
+use std::env;
+use uuid::Uuid;
+
+use webapi::controllers::user::get_security_status;
+use webapi::repositories::connection::RequestConnection;
+
+mod common;
+use common::TEST_DB_URL;
+
+// PID серверного процесса PostgreSQL — у каждого соединения свой
+async fn backend_pid(db: &RequestConnection) -> i32 {
+    let mut conn = db.acquire().await.unwrap();
+    sqlx::query_scalar::<_, i32>("SELECT pg_backend_pid()")
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_request_connection_reuse() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let pool = common::setup_test_db().await;
+
+    // Тест 1: Все запросы к БД в рамках одного HTTP-запроса выполняются на одном соединении
+    let db = RequestConnection::new(pool.clone());
+    let pid = backend_pid(&db).await;
+    assert_eq!(backend_pid(&db).await, pid);
+    assert_eq!(backend_pid(&db.clone()).await, pid);
+
+    // Тест 2: Состояние сессии (временная таблица) видно следующим запросам того же HTTP-запроса
+    {
+        let mut conn = db.acquire().await.unwrap();
+        sqlx::query("CREATE TEMP TABLE request_scratch (value INTEGER)")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO request_scratch VALUES (1), (2)")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+    }
+    let mut conn = db.acquire().await.unwrap();
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM request_scratch")
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+    drop(conn);
+
+    // Тест 3: Параллельный HTTP-запрос получает собственное соединение
+    let other = RequestConnection::new(pool.clone());
+    assert_ne!(backend_pid(&other).await, pid);
+    drop(other);
+
+    // Тест 4: Обработчик берет соединение запроса из extensions и выполняет на нем все чтения
+    let user_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, name, email, password_hash, age) VALUES ($1, 'Сводка', 'summary@example.com', 'hash', 30)")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let mut request = Request::get("/api/v1/users/me/security").body(Body::empty()).unwrap();
+    request.extensions_mut().insert(user_id);
+    request.extensions_mut().insert(db.clone());
+    let response = get_security_status(request, pool.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(backend_pid(&db).await, pid);
+
+    // Тест 5: После завершения запроса соединение возвращается в пул
+    let idle_before = pool.num_idle();
+    drop(db);
+    for _ in 0..50 {
+        if pool.num_idle() > idle_before {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(pool.num_idle(), idle_before + 1);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
@@ -0,0 +1,38 @@
+use hyper::{Body, Response, StatusCode};
+
+use webapi::middleware::response_limit::enforce_response_size_limit;
+
+// Обработчик, который по ошибке формирует тело ответа заданного размера
+async fn bloated_handler(size: usize) -> Response<Body> {
+    Response::new(Body::from(vec![b'x'; size]))
+}
+
+#[tokio::test]
+async fn test_response_size_limit() {
+    // Тест 1: Буферизованное тело сверх лимита заменяется ошибкой 500
+    let response = enforce_response_size_limit(bloated_handler(2048).await, Some(1024), Some("req-limit-1"));
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert!(body.len() < 1024);
+    assert!(String::from_utf8_lossy(&body).contains("req-limit-1"));
+
+    // Тест 2: Тело в пределах лимита передается без изменений
+    let response = enforce_response_size_limit(bloated_handler(512).await, Some(1024), None);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(hyper::body::to_bytes(response.into_body()).await.unwrap().len(), 512);
+
+    // Тест 3: Без лимита тело не проверяется
+    let response = enforce_response_size_limit(bloated_handler(2048).await, None, None);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Тест 4: Потоковый ответ не ограничивается, даже если передает больше лимита
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        for _ in 0..4 {
+            sender.send_data(vec![b'x'; 512].into()).await.unwrap();
+        }
+    });
+    let response = enforce_response_size_limit(Response::new(body), Some(1024), None);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(hyper::body::to_bytes(response.into_body()).await.unwrap().len(), 2048);
+}
@@ -0,0 +1,134 @@
+use hyper::{Body, Request, Response, StatusCode};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use webapi::config::AppConfig;
+use webapi::middleware::chain;
+use webapi::models::{Claims, UserRole, UserRolesRequest};
+use webapi::repositories::user::{check_role_column_type, find_user_role};
+use webapi::services::user::{set_user_roles_service, user_roles_service};
+
+// Роли при проверке прав читаются из БД, поэтому тест работает с тестовой базой
+mod common;
+
+static TEST_JWT_SECRET: &str = "test_secret_key_for_jwt_token_generation";
+
+// Создает действующий токен для пользователя с заданной ролью в claims
+fn token_for(user_id: Uuid, role: UserRole) -> String {
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        sub: user_id.to_string(),
+        exp: now + 3600,
+        iat: now,
+        role,
+        email: "roles@example.com".to_string(),
+        fingerprint: None,
+        sid: None,
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(TEST_JWT_SECRET.as_bytes())).unwrap()
+}
+
+// Обработчик защищенного маршрута
+async fn protected_handler(_req: Request<Body>, _pool: PgPool) -> Result<Response<Body>, hyper::Error> {
+    Ok(Response::new(Body::from("ok")))
+}
+
+// Статус ответа цепочки с проверкой роли для запроса с токеном
+async fn status_for(role: UserRole, token: &str, config: &AppConfig, pool: &PgPool) -> StatusCode {
+    let req = Request::builder()
+        .uri("/api/v1/protected")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let req = common::with_config(req, config);
+    chain().role(role).handle(req, pool.clone(), protected_handler).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_multiple_roles() {
+    env::set_var("JWT_SECRET", TEST_JWT_SECRET);
+    let mut config = common::test_config();
+    config.features.multiple_roles = true;
+    let pool = common::setup_test_db().await;
+    let admin_id = insert_user("roles-admin@example.com", UserRole::Admin, &pool).await;
+    let user_id = insert_user("roles-user@example.com", UserRole::User, &pool).await;
+    let token = token_for(user_id, UserRole::User);
+
+    // Тест 1: Без дополнительных ролей пользователь не проходит проверку модератора
+    assert_eq!(status_for(UserRole::Moderator, &token, &config, &pool).await, StatusCode::FORBIDDEN);
+
+    // Тест 2: Администратор добавляет роль модератора; основная роль не дублируется
+    let request = UserRolesRequest { roles: vec![UserRole::Moderator, UserRole::User, UserRole::Moderator] };
+    let roles = set_user_roles_service(admin_id, user_id, &request, None, &pool).await.unwrap();
+    assert_eq!(roles.role, UserRole::User);
+    assert_eq!(roles.roles, vec![UserRole::User, UserRole::Moderator]);
+    assert_eq!(user_roles_service(user_id, &pool).await.unwrap().roles, roles.roles);
+
+    // Тест 3: Пользователь с двумя ролями проходит обе проверки, но не проверку администратора
+    assert_eq!(status_for(UserRole::User, &token, &config, &pool).await, StatusCode::OK);
+    assert_eq!(status_for(UserRole::Moderator, &token, &config, &pool).await, StatusCode::OK);
+    assert_eq!(status_for(UserRole::Admin, &token, &config, &pool).await, StatusCode::FORBIDDEN);
+
+    // Тест 4: Изменение ролей записывается в журнал аудита
+    let events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE user_id = $1 AND action = 'roles_changed'")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(events, 1);
+
+    // Тест 5: С выключенным FEATURE_MULTIPLE_ROLES учитывается только основная роль
+    let mut single_role = config.clone();
+    single_role.features.multiple_roles = false;
+    assert_eq!(status_for(UserRole::Moderator, &token, &single_role, &pool).await, StatusCode::FORBIDDEN);
+
+    // Тест 5.1: Флаг берется из загруженной конфигурации, а не из окружения процесса
+    env::set_var("FEATURE_MULTIPLE_ROLES", "true");
+    assert_eq!(status_for(UserRole::Moderator, &token, &single_role, &pool).await, StatusCode::FORBIDDEN);
+    env::set_var("FEATURE_MULTIPLE_ROLES", "false");
+    assert_eq!(status_for(UserRole::Moderator, &token, &config, &pool).await, StatusCode::OK);
+    env::remove_var("FEATURE_MULTIPLE_ROLES");
+
+    // Тест 6: Пустой список снимает дополнительные роли
+    let request = UserRolesRequest { roles: vec![] };
+    let roles = set_user_roles_service(admin_id, user_id, &request, None, &pool).await.unwrap();
+    assert_eq!(roles.roles, vec![UserRole::User]);
+    assert_eq!(status_for(UserRole::Moderator, &token, &config, &pool).await, StatusCode::FORBIDDEN);
+
+    // Тест 7: Схема с users.role типа TEXT проходит стартовую проверку, роли читаются
+    assert!(check_role_column_type(&pool).await.is_ok());
+    assert_eq!(find_user_role(admin_id, &pool).await.unwrap(), Some(UserRole::Admin));
+    assert_eq!(find_user_role(user_id, &pool).await.unwrap(), Some(UserRole::User));
+
+    // Тест 8: Колонка с enum user_role (база без миграции 0004) отклоняется с понятной ошибкой
+    sqlx::query("CREATE TYPE user_role AS ENUM ('User', 'Admin', 'Moderator')").execute(&pool).await.unwrap();
+    sqlx::query("ALTER TABLE users DROP CONSTRAINT users_role_check").execute(&pool).await.unwrap();
+    sqlx::query("ALTER TABLE users ALTER COLUMN role DROP DEFAULT").execute(&pool).await.unwrap();
+    sqlx::query("ALTER TABLE users ALTER COLUMN role TYPE user_role USING role::user_role")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let err = check_role_column_type(&pool).await.unwrap_err();
+    assert!(err.contains("user_role") && err.contains("0004"), "{}", err);
+
+    // Очистка после тестов (тип user_role удаляется вместе со схемой)
+    common::cleanup_test_db(&pool).await;
+}
+
+// Создает пользователя с заданной ролью напрямую в БД
+async fn insert_user(email: &str, role: UserRole, pool: &PgPool) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, name, email, password_hash, age, role) VALUES ($1, $2, $3, $4, $5, $6)")
+        .bind(id)
+        .bind("Пользователь с ролями")
+        .bind(email)
+        .bind("hash")
+        .bind(30)
+        .bind(role)
+        .execute(pool)
+        .await
+        .expect("Не удалось создать пользователя");
+    id
+}
@@ -0,0 +1,73 @@
+use std::env;
+
+use webapi::models::{ChangePasswordRequest, LoginRequest, UserRequest};
+use webapi::services::user::{change_password_service, create_user_service, login_service, security_status_service};
+
+mod common;
+use common::TEST_DB_URL;
+
+#[tokio::test]
+async fn test_security_status() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Осторожный".to_string(),
+        email: "careful@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 30,
+    };
+    let user = create_user_service(request, &pool).await.unwrap();
+
+    // Тест 1: Сразу после регистрации входов не было, возраст пароля считается от создания
+    let status = security_status_service(user.id, &mut pool.acquire().await.unwrap()).await.unwrap();
+    assert_eq!(status.active_sessions, Some(0));
+    assert!(status.last_login_at.is_none());
+    assert_eq!((status.password_changed_at - user.created_at).num_seconds(), 0);
+    assert_eq!(status.password_age_days, 0);
+
+    // Тест 2: Вход создает сессию, неудачная попытка увеличивает счетчик
+    let login = |password: &str| LoginRequest {
+        email: "careful@example.com".to_string(),
+        password: password.to_string(),
+        remember_me: false,
+        reactivate: false,
+    };
+    login_service(login("Password123!"), &common::test_config(), &pool).await.unwrap();
+    assert!(login_service(login("WrongPassword1!"), &common::test_config(), &pool).await.is_err());
+
+    let status = security_status_service(user.id, &mut pool.acquire().await.unwrap()).await.unwrap();
+    assert_eq!(status.active_sessions, Some(1));
+    assert!(status.last_login_at.is_some());
+    assert_eq!(status.failed_login_attempts, 1);
+    assert!(status.locked_until.is_none());
+
+    // Тест 3: Второй успешный вход увеличивает счетчик входов и сдвигает время последнего входа
+    assert_eq!(status.login_count, 1);
+    let first_login_at = status.last_login_at.unwrap();
+    let auth = login_service(login("Password123!"), &common::test_config(), &pool).await.unwrap();
+    assert_eq!(auth.user.login_count, 2);
+    let status = security_status_service(user.id, &mut pool.acquire().await.unwrap()).await.unwrap();
+    assert_eq!(status.login_count, 2);
+    assert!(status.last_login_at.unwrap() > first_login_at);
+
+    // Тест 4: Без таблицы кодов восстановления поле пустое, остальные данные возвращаются
+    sqlx::query("DROP TABLE user_recovery_codes").execute(&pool).await.unwrap();
+    let status = security_status_service(user.id, &mut pool.acquire().await.unwrap()).await.unwrap();
+    assert!(status.recovery_codes_remaining.is_none());
+    assert_eq!(status.login_count, 2);
+
+    // Тест 5: Смена пароля обновляет время смены пароля
+    let change = ChangePasswordRequest {
+        current_password: "Password123!".to_string(),
+        new_password: "NewPassword456!".to_string(),
+        confirm_password: "NewPassword456!".to_string(),
+    };
+    change_password_service(user.id, &change, &pool).await.unwrap();
+    let status = security_status_service(user.id, &mut pool.acquire().await.unwrap()).await.unwrap();
+    assert!(status.password_changed_at > user.created_at);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
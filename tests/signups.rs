@@ -0,0 +1,44 @@
+use hyper::{Body, Request, StatusCode};
+
+use webapi::controllers::user::create_user;
+
+mod common;
+
+// Запрос на регистрацию с корректным телом
+fn signup_request(email: &str) -> Request<Body> {
+    let body = serde_json::json!({
+        "name": "Новый пользователь",
+        "email": email,
+        "password": "Password123!",
+        "age": 25,
+    });
+    Request::post("/api/v1/users")
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_signups_follow_feature_flag() {
+    let pool = common::setup_test_db().await;
+    let mut config = common::test_config();
+
+    // Тест 1: При FEATURE_SIGNUPS_OPEN=false регистрация отклоняется с 403, пользователь не создается
+    config.features.signups_open = false;
+    let response = create_user(common::with_config(signup_request("closed@example.com"), &config), pool.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users").fetch_one(&pool).await.unwrap();
+    assert_eq!(users, 0);
+
+    // Тест 2: При открытой регистрации тот же запрос создает пользователя
+    config.features.signups_open = true;
+    let response = create_user(common::with_config(signup_request("open@example.com"), &config), pool.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
@@ -0,0 +1,61 @@
+use std::env;
+use std::time::{Duration, Instant};
+
+use webapi::config::{LoginLimitMode, LoginSlowdownLimits};
+use webapi::errors::AppError;
+use webapi::models::{LoginRequest, UserRequest};
+use webapi::services::user::{create_user_service, login_service};
+
+mod common;
+use common::TEST_DB_URL;
+
+#[tokio::test]
+async fn test_login_slowdown_mode() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let pool = common::setup_test_db().await;
+
+    let mut config = common::test_config();
+    config.login_limit_mode = LoginLimitMode::Slowdown;
+    config.login_slowdown = LoginSlowdownLimits { base_ms: 100, max_ms: 400 };
+    config.login_lockout.max_failed_attempts = 2;
+
+    // Тест 1: Задержка удваивается с каждой попыткой и ограничена сверху
+    assert_eq!(config.login_slowdown.delay(0), Duration::from_millis(0));
+    assert_eq!(config.login_slowdown.delay(1), Duration::from_millis(100));
+    assert_eq!(config.login_slowdown.delay(2), Duration::from_millis(200));
+    assert_eq!(config.login_slowdown.delay(3), Duration::from_millis(400));
+    assert_eq!(config.login_slowdown.delay(10), Duration::from_millis(400));
+
+    let request = UserRequest {
+        name: "Медленный Вход".to_string(),
+        email: "slowdown@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 28,
+    };
+    create_user_service(request, &pool).await.unwrap();
+
+    let login = |password: &str| LoginRequest {
+        email: "slowdown@example.com".to_string(),
+        password: password.to_string(),
+        remember_me: false,
+        reactivate: false,
+    };
+
+    // Тест 2: Третья неудачная попытка подряд задерживается не меньше чем на 400 мс,
+    // а аккаунт не блокируется даже после превышения MAX_FAILED_LOGIN_ATTEMPTS
+    for _ in 0..2 {
+        let result = login_service(login("WrongPassword1!"), &config, &pool).await;
+        assert!(matches!(result, Err(AppError::Unauthorized)));
+    }
+    let started = Instant::now();
+    let result = login_service(login("WrongPassword1!"), &config, &pool).await;
+    assert!(matches!(result, Err(AppError::Unauthorized)));
+    assert!(started.elapsed() >= Duration::from_millis(400));
+
+    // Тест 3: Верный пароль по-прежнему принимается
+    assert!(login_service(login("Password123!"), &config, &pool).await.is_ok());
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
@@ -0,0 +1,89 @@
+use hyper::header::{AUTHORIZATION, COOKIE, SET_COOKIE};
+use hyper::{Body, Request, StatusCode};
+use std::env;
+
+use webapi::controllers::user::{exchange_token_for_cookie, verify_token};
+use webapi::middleware::auth::auth_middleware;
+use webapi::models::{LoginRequest, UserRequest};
+use webapi::services::user::{create_user_service, login_service};
+
+mod common;
+use common::TEST_DB_URL;
+// Запрос с токеном в заголовке Authorization и снимком конфигурации, как его передает сервер
+// Запрос с токеном в заголовке Authorization
+fn bearer_request(uri: &str, token: &str) -> Request<Body> {
+    let request = Request::post(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    common::with_config(request, &common::test_config())
+}
+
+#[tokio::test]
+async fn test_exchange_bearer_token_for_cookie() {
+    env::set_var("DATABASE_URL", TEST_DB_URL);
+    env::set_var("JWT_SECRET", "test_secret_key_for_jwt_token_generation");
+    let pool = common::setup_test_db().await;
+
+    let request = UserRequest {
+        name: "Одностраничник".to_string(),
+        email: "spa@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 28,
+    };
+    create_user_service(request, &pool).await.unwrap();
+    let login = || LoginRequest {
+        email: "spa@example.com".to_string(),
+        password: "Password123!".to_string(),
+        remember_me: false,
+        reactivate: false,
+    };
+    let auth = login_service(login(), &common::test_config(), &pool).await.unwrap();
+
+    // Тест 1: Обмен выдает HttpOnly cookie с новым токеном, тело токен не содержит
+    let request = bearer_request("/api/v1/auth/to-cookie", &auth.token);
+    let response = auth_middleware(request, pool.clone(), exchange_token_for_cookie).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response.headers()[SET_COOKIE].to_str().unwrap().to_string();
+    assert!(cookie.starts_with("auth_token="));
+    assert!(cookie.contains("HttpOnly"));
+    assert!(cookie.contains("SameSite=Strict"));
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert!(body.get("token").is_none());
+    assert!(body["expires_in"].as_i64().unwrap() <= auth.expires_in);
+    assert_eq!(body["bearer_revoked"], false);
+
+    // Тест 2: Новая cookie-сессия принимается, исходный токен без revoke_bearer продолжает работать
+    let cookie_pair = cookie.split(';').next().unwrap().to_string();
+    let request = Request::get("/api/v1/auth/verify")
+        .header(COOKIE, cookie_pair.as_str())
+        .body(Body::empty())
+        .unwrap();
+    let response = auth_middleware(request, pool.clone(), verify_token).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let request = bearer_request("/api/v1/auth/verify", &auth.token);
+    let response = auth_middleware(request, pool.clone(), verify_token).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Тест 3: С revoke_bearer=true исходный токен после обмена отклоняется
+    let auth = login_service(login(), &common::test_config(), &pool).await.unwrap();
+    let request = bearer_request("/api/v1/auth/to-cookie?revoke_bearer=true", &auth.token);
+    let response = auth_middleware(request, pool.clone(), exchange_token_for_cookie).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value =
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body["bearer_revoked"], true);
+
+    let request = bearer_request("/api/v1/auth/verify", &auth.token);
+    let response = auth_middleware(request, pool.clone(), verify_token).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Тест 4: Без токена обмен невозможен
+    let request = Request::post("/api/v1/auth/to-cookie").body(Body::empty()).unwrap();
+    let response = auth_middleware(request, pool.clone(), exchange_token_for_cookie).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
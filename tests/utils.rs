@@ -0,0 +1,118 @@
+use serde_json::json;
+use std::time::{Duration, Instant};
+use ulid::Ulid;
+
+use webapi::utils::{
+    assign_request_id, camel_to_snake, current_request_id, generate_request_id_with, pagination_link_header, pagination_link_header_with_query,
+    rename_json_keys, strip_json_preamble, to_response_json, to_response_json_with, JsonCase, RequestIdFormat, RequestIdTracker,
+};
+
+#[test]
+fn test_request_id_formats() {
+    // Тест 1: По умолчанию используется UUID без дефисов
+    let uuid_id = generate_request_id_with(RequestIdFormat::Uuid);
+    assert_eq!(uuid_id.len(), 32);
+    assert!(uuid::Uuid::parse_str(&uuid_id).is_ok());
+
+    // Тест 2: ULID, сгенерированные подряд, сортируются в порядке создания
+    let first = generate_request_id_with(RequestIdFormat::Ulid);
+    let second = generate_request_id_with(RequestIdFormat::Ulid);
+    assert!(Ulid::from_string(&first).is_ok());
+    assert!(Ulid::from_string(&second).is_ok());
+    assert!(first < second);
+}
+
+#[test]
+fn test_reused_request_id_gets_distinct_internal_id() {
+    let tracker = RequestIdTracker::new(Duration::from_secs(60));
+    let now = Instant::now();
+
+    // Тест 1: Два запроса с одинаковым X-Request-ID получают разные внутренние ID
+    let first = tracker.internal_id("client-req-1", now);
+    let second = tracker.internal_id("client-req-1", now);
+    assert_eq!(first, "client-req-1");
+    assert_eq!(second, "client-req-1-2");
+    assert_ne!(first, second);
+
+    // Тест 2: Другой ID не затрагивается, а после окна ID снова используется как есть
+    assert_eq!(tracker.internal_id("client-req-2", now), "client-req-2");
+    assert_eq!(tracker.internal_id("client-req-1", now + Duration::from_secs(61)), "client-req-1");
+
+    // Тест 3: С нулевым окном повторы не отслеживаются
+    let disabled = RequestIdTracker::new(Duration::ZERO);
+    assert_eq!(disabled.internal_id("client-req-1", now), "client-req-1");
+    assert_eq!(disabled.internal_id("client-req-1", now), "client-req-1");
+}
+
+#[test]
+fn test_assigned_request_id_matches_current_request_id() {
+    // Тест 1: Без X-Request-ID генерируется ID, и его же отдает current_request_id
+    let mut req = hyper::Request::new(());
+    let assigned = assign_request_id(&mut req);
+    assert!(!assigned.is_empty());
+    assert_eq!(current_request_id(&req), Some(assigned));
+
+    // Тест 2: ID клиента используется как внутренний, повтор получает суффикс
+    let client_id = format!("assign-{}", uuid::Uuid::new_v4().as_simple());
+    let with_header = || hyper::Request::builder().header("X-Request-ID", client_id.as_str()).body(()).unwrap();
+    let mut first = with_header();
+    assert_eq!(assign_request_id(&mut first), client_id);
+    let mut second = with_header();
+    let assigned = assign_request_id(&mut second);
+    assert_eq!(assigned, format!("{}-2", client_id));
+    assert_eq!(current_request_id(&second), Some(assigned));
+}
+
+#[test]
+fn test_pagination_link_header() {
+    // Тест 1: Первая неполная выборка из 45 записей содержит ссылку на следующую страницу
+    let links = pagination_link_header("/api/v1/admin/users", 45, 0, 20);
+    assert!(links.contains(r#"</api/v1/admin/users?offset=20&limit=20>; rel="next""#));
+    assert!(links.contains(r#"</api/v1/admin/users?offset=0&limit=20>; rel="first""#));
+    assert!(links.contains(r#"</api/v1/admin/users?offset=40&limit=20>; rel="last""#));
+    assert!(!links.contains(r#"rel="prev""#));
+
+    // Тест 2: На последней странице нет next, но есть prev
+    let links = pagination_link_header("/api/v1/admin/users", 45, 40, 20);
+    assert!(links.contains(r#"</api/v1/admin/users?offset=20&limit=20>; rel="prev""#));
+    assert!(!links.contains(r#"rel="next""#));
+
+    // Тест 3: Дополнительные параметры (строка поиска) сохраняются в ссылках
+    let links = pagination_link_header_with_query("/api/v1/admin/users", "q=spam", 45, 0, 20);
+    assert!(links.contains(r#"</api/v1/admin/users?q=spam&offset=20&limit=20>; rel="next""#));
+}
+
+#[test]
+fn test_json_case() {
+    let data = json!({
+        "created_at": "2025-08-01T00:00:00Z",
+        "user": { "must_change_password": false },
+        "items": [{ "expires_at": 1 }]
+    });
+
+    // Тест 1: По умолчанию ключи остаются в snake_case
+    assert!(to_response_json(&data).unwrap().contains(r#""created_at""#));
+
+    // Тест 2: В режиме camel переименовываются ключи на всех уровнях
+    let body = to_response_json_with(&data, JsonCase::Camel).unwrap();
+    assert!(body.contains(r#""createdAt""#));
+    assert!(body.contains(r#""mustChangePassword""#));
+    assert!(body.contains(r#""expiresAt""#));
+    assert!(!body.contains("created_at"));
+
+    // Тест 3: Ключи запроса в camelCase приводятся к snake_case, snake_case не меняется
+    let request = rename_json_keys(json!({ "rememberMe": true, "new_password": "x" }), camel_to_snake);
+    assert_eq!(request, json!({ "remember_me": true, "new_password": "x" }));
+}
+
+#[test]
+fn test_strip_json_preamble() {
+    // Тест 1: BOM и пробелы перед JSON удаляются, тело разбирается
+    let body = strip_json_preamble(b"\xEF\xBB\xBF \r\n{\"email\":\"a@example.com\"}");
+    let value: serde_json::Value = serde_json::from_slice(body).unwrap();
+    assert_eq!(value["email"], "a@example.com");
+
+    // Тест 2: Тело без преамбулы не меняется, тело из одного BOM становится пустым
+    assert_eq!(strip_json_preamble(b"{}"), b"{}");
+    assert!(strip_json_preamble(b"\xEF\xBB\xBF").is_empty());
+}
@@ -0,0 +1,103 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use webauthn_authenticator_rs::softpasskey::SoftPasskey;
+use webauthn_authenticator_rs::WebauthnAuthenticator;
+use webauthn_rs::prelude::{CreationChallengeResponse, Url};
+
+use webapi::config::WebauthnSettings;
+use webapi::errors::AppError;
+use webapi::models::{UserRequest, WebauthnRegisterFinishRequest};
+use webapi::services::user::create_user_service;
+use webapi::services::webauthn::{finish_registration_service, relying_party, start_registration_service};
+
+mod common;
+
+static TEST_ORIGIN: &str = "https://app.example.com";
+static TEST_RP_ID: &str = "app.example.com";
+
+fn webauthn_settings(rp_id: &str, origin: &str) -> WebauthnSettings {
+    WebauthnSettings {
+        rp_id: rp_id.to_string(),
+        rp_name: "webapi".to_string(),
+        origin: origin.to_string(),
+    }
+}
+
+// Ответ программного аутентификатора на страницу с origin в том виде, в каком его присылает браузер
+fn authenticator_response(
+    authenticator: &mut WebauthnAuthenticator<SoftPasskey>,
+    origin: &str,
+    options: CreationChallengeResponse,
+) -> WebauthnRegisterFinishRequest {
+    let credential = authenticator
+        .do_registration(Url::parse(origin).unwrap(), options)
+        .unwrap();
+    serde_json::from_value(serde_json::to_value(credential).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_webauthn_registration_ceremony() {
+    let pool = common::setup_test_db().await;
+    let settings = webauthn_settings(TEST_RP_ID, TEST_ORIGIN);
+    let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+    let request = UserRequest {
+        name: "Пользователь passkey".to_string(),
+        email: "passkey@example.com".to_string(),
+        password: "Password123!".to_string(),
+        age: 30,
+    };
+    let user = create_user_service(request, &pool).await.unwrap();
+
+    // Тест 1: Начало регистрации выдает challenge и параметры для navigator.credentials.create
+    let options = start_registration_service(user.id, &settings, &pool).await.unwrap();
+    assert_eq!(options.public_key.rp.id, TEST_RP_ID);
+    assert_eq!(options.public_key.challenge.len(), 32);
+    assert!(options.public_key.exclude_credentials.as_deref().unwrap_or_default().is_empty());
+
+    // Тест 2: Ответ со страницы другого origin (поддомена) отклоняется и расходует регистрацию
+    let response = authenticator_response(&mut authenticator, "https://login.app.example.com", options);
+    let result = finish_registration_service(user.id, &settings, response, &pool).await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    // Тест 3: Ответ, проверяемый для другого RP ID, отклоняется
+    let options = start_registration_service(user.id, &settings, &pool).await.unwrap();
+    let response = authenticator_response(&mut authenticator, TEST_ORIGIN, options);
+    let other = webauthn_settings("other.example.com", "https://other.example.com");
+    let result = finish_registration_service(user.id, &other, response, &pool).await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    // Тест 4: Корректный ответ сохраняет ключ доступа
+    let options = start_registration_service(user.id, &settings, &pool).await.unwrap();
+    let response = authenticator_response(&mut authenticator, TEST_ORIGIN, options.clone());
+    let raw_id = URL_SAFE_NO_PAD.encode(&response.raw_id);
+    let credential = finish_registration_service(user.id, &settings, response, &pool).await.unwrap();
+    assert_eq!(credential.user_id, user.id);
+    assert_eq!(credential.credential_id, raw_id);
+
+    // Тест 5: Повтор ответа на тот же challenge отклоняется: регистрация одноразовая
+    let mut replayer = WebauthnAuthenticator::new(SoftPasskey::new(true));
+    let response = authenticator_response(&mut replayer, TEST_ORIGIN, options);
+    let result = finish_registration_service(user.id, &settings, response, &pool).await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    // Тест 6: Зарегистрированный ключ исключается при следующей регистрации
+    let options = start_registration_service(user.id, &settings, &pool).await.unwrap();
+    let excluded = options.public_key.exclude_credentials.unwrap();
+    assert_eq!(excluded.len(), 1);
+    assert_eq!(URL_SAFE_NO_PAD.encode(&excluded[0].id), credential.credential_id);
+
+    // Очистка после тестов
+    common::cleanup_test_db(&pool).await;
+}
+
+#[test]
+fn test_relying_party_requires_matching_origin() {
+    // Тест 1: RP ID должен быть доменом origin (или его родительским доменом)
+    assert!(relying_party(&webauthn_settings("app.example.com", "https://app.example.com")).is_ok());
+    assert!(relying_party(&webauthn_settings("example.com", "https://app.example.com")).is_ok());
+    assert!(relying_party(&webauthn_settings("evil.example.org", "https://app.example.com")).is_err());
+
+    // Тест 2: Origin, который не разбирается как URL, отклоняется
+    assert!(relying_party(&webauthn_settings("app.example.com", "app.example.com")).is_err());
+}